@@ -1,7 +1,17 @@
-use std::io::{Read, Write};
-use std::net::TcpStream;
-use std::process::Command;
-use std::time::Duration;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tauri::{Emitter, Window};
+
+/// How long a single CLI run may take before it is killed, unless
+/// overridden with `PROJECTBRIDGE_TIMEOUT_SECS`.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
 
 /// Resolve the `projectbridge` CLI binary path.
 /// Checks `PROJECTBRIDGE_BIN` env var first, then falls back to PATH lookup.
@@ -9,68 +19,596 @@ fn pb_binary() -> String {
     std::env::var("PROJECTBRIDGE_BIN").unwrap_or_else(|_| "projectbridge".to_string())
 }
 
-/// Write JSON to a temp file, run a CLI operation, then clean up.
-fn with_temp_json<F>(json: &str, filename: &str, f: F) -> Result<String, String>
+/// Resolve the CLI timeout from `PROJECTBRIDGE_TIMEOUT_SECS`, falling back to
+/// the default when unset, unparsable, or zero.
+fn pb_timeout() -> Duration {
+    let secs = std::env::var("PROJECTBRIDGE_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&s| s > 0)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// How a CLI run failed.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind")]
+enum PbErrorKind {
+    /// The binary could not be started (not installed, not executable, ...).
+    SpawnFailed,
+    /// The CLI ran and exited unsuccessfully; `code` is `None` when it was
+    /// killed by a signal.
+    NonZeroExit { code: Option<i32> },
+    /// The CLI succeeded but its stdout was not valid UTF-8.
+    InvalidUtf8,
+    /// The CLI succeeded but its stdout was not what the command expected.
+    UnexpectedOutput,
+    /// The CLI ran past `pb_timeout()` and was killed.
+    TimedOut,
+    /// The request was rejected before the CLI was run.
+    InvalidInput,
+    /// The run was stopped by `cancel_analysis`.
+    Cancelled,
+}
+
+/// Error returned to the frontend by the CLI-backed commands. Serializes
+/// as `{ "kind": ..., "code"?: ..., "stderr": ..., "message": ... }`.
+#[derive(Debug, serde::Serialize)]
+struct PbError {
+    #[serde(flatten)]
+    kind: PbErrorKind,
+    /// Whatever the CLI wrote to stderr, empty if it never ran.
+    stderr: String,
+    /// Human-readable summary.
+    message: String,
+}
+
+impl PbError {
+    fn new(kind: PbErrorKind, message: impl Into<String>) -> Self {
+        PbError {
+            kind,
+            stderr: String::new(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Callback receiving each line of CLI output as it arrives.
+type LineSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// The analysis currently in flight, if any. Managed by Tauri so
+/// `cancel_analysis` can kill it while the run command waits.
+#[derive(Default)]
+struct RunningAnalysis(Arc<Mutex<AnalysisSlot>>);
+
+/// Who holds the single analysis slot. Each claim carries the id of the
+/// run that made it, so a run only ever changes a slot it still owns.
+#[derive(Default)]
+enum AnalysisSlot {
+    #[default]
+    Idle,
+    /// Claimed by a run (see `claim_analysis`) whose CLI isn't spawned yet.
+    Starting(u64),
+    /// The CLI child of the run.
+    Running(u64, Child),
+}
+
+/// Source of run ids for `claim_analysis`. Starts at 1; 0 is the id of
+/// runs that don't use the shared slot.
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Claim `slot` for a new run, failing if another run is starting or
+/// running, and return the run's id. Checking and claiming happen under
+/// one lock, so two concurrent runs can't both get through;
+/// `execute_pb_streaming` frees the slot.
+fn claim_analysis(slot: &Mutex<AnalysisSlot>) -> Result<u64, PbError> {
+    let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+    if !matches!(*slot, AnalysisSlot::Idle) {
+        return Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            "An analysis is already running",
+        ));
+    }
+    let id = NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed);
+    *slot = AnalysisSlot::Starting(id);
+    Ok(id)
+}
+
+/// Park the spawned `child` of run `id` in `slot`, unless the run lost its
+/// claim (it was cancelled, and maybe another run claimed the slot since);
+/// then the child is handed back for the run to kill.
+fn install_child(slot: &Mutex<AnalysisSlot>, id: u64, child: Child) -> Result<(), Child> {
+    let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+    match *slot {
+        AnalysisSlot::Starting(owner) if owner == id => {
+            *slot = AnalysisSlot::Running(id, child);
+            Ok(())
+        }
+        _ => Err(child),
+    }
+}
+
+/// Free `slot` if run `id` still holds the claim (but not a child).
+fn release_analysis(slot: &Mutex<AnalysisSlot>, id: u64) {
+    let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+    if matches!(*slot, AnalysisSlot::Starting(owner) if owner == id) {
+        *slot = AnalysisSlot::Idle;
+    }
+}
+
+/// Empty `slot`, killing the CLI if one is running. Returns whether the
+/// slot was claimed.
+fn cancel_slot(slot: &Mutex<AnalysisSlot>) -> bool {
+    let slot = std::mem::take(&mut *slot.lock().unwrap_or_else(PoisonError::into_inner));
+    match slot {
+        AnalysisSlot::Running(_, mut child) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            true
+        }
+        // The run kills its CLI itself once it finds its claim gone.
+        AnalysisSlot::Starting(_) => true,
+        AnalysisSlot::Idle => false,
+    }
+}
+
+/// The CLI version once `cached_pb_version` has read it, so it only runs
+/// `projectbridge --version` once per app session.
+#[derive(Default)]
+struct CachedPbVersion(Mutex<Option<String>>);
+
+/// Pull the version out of `projectbridge --version` output such as
+/// `projectbridge 0.1.0` or `projectbridge v0.1.0`: the first word that
+/// starts with a digit (after an optional `v`). Falls back to the whole
+/// trimmed output when there is none.
+fn parse_pb_version(stdout: &str) -> Option<String> {
+    let version = stdout
+        .split_whitespace()
+        .map(|word| word.strip_prefix('v').unwrap_or(word))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .unwrap_or_else(|| stdout.trim());
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// Read a child pipe to the end on a background thread, so a chatty child
+/// never blocks on a full pipe buffer while we wait for it. With a sink,
+/// every line is also forwarded as soon as it is read.
+fn drain_pipe<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    on_line: Option<LineSink>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return buf;
+        };
+        let Some(on_line) = on_line else {
+            let _ = pipe.read_to_end(&mut buf);
+            return buf;
+        };
+
+        let mut reader = BufReader::new(pipe);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    on_line(String::from_utf8_lossy(&line).trim_end());
+                    buf.extend_from_slice(&line);
+                }
+            }
+        }
+        buf
+    })
+}
+
+/// Source of the per-call suffix that keeps `with_temp_json` files apart.
+static TEMP_FILE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Write JSON to a temp file, run a CLI operation, then clean up. The file
+/// is named after `filename` plus the process id and a counter, so
+/// overlapping calls never share one.
+fn with_temp_json<F>(json: &str, filename: &str, f: F) -> Result<String, PbError>
 where
-    F: FnOnce(&str) -> Result<String, String>,
+    F: FnOnce(&str) -> Result<String, PbError>,
 {
-    let tmp = std::env::temp_dir().join(filename);
-    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write temp file: {}", e))?;
+    let (stem, ext) = filename.rsplit_once('.').unwrap_or((filename, "json"));
+    let id = TEMP_FILE_ID.fetch_add(1, Ordering::Relaxed);
+    let tmp = std::env::temp_dir().join(format!("{}_{}_{}.{}", stem, std::process::id(), id, ext));
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp)
+        .and_then(|mut file| file.write_all(json.as_bytes()))
+        .map_err(|e| {
+            PbError::new(
+                PbErrorKind::InvalidInput,
+                format!("Failed to write temp file: {}", e),
+            )
+        })?;
     let result = f(tmp.to_str().unwrap());
     let _ = std::fs::remove_file(&tmp);
     result
 }
 
 /// Execute the `projectbridge` CLI with the given args and optional env vars.
-fn execute_pb(args: Vec<String>, env_vars: Vec<(String, String)>) -> Result<String, String> {
+fn execute_pb(args: Vec<String>, env_vars: Vec<(String, String)>) -> Result<String, PbError> {
+    execute_pb_streaming(args, env_vars, None, None)
+}
+
+/// Like `execute_pb`, forwarding stdout and stderr lines to `on_line` while
+/// the CLI runs. The return value is still the complete stdout.
+///
+/// With `running`, a slot and the run id `claim_analysis` returned for it,
+/// the child is parked there while it runs and the slot is freed when it
+/// ends; if the run loses its claim (see `cancel_analysis`) it ends as
+/// `Cancelled`.
+fn execute_pb_streaming(
+    args: Vec<String>,
+    env_vars: Vec<(String, String)>,
+    on_line: Option<LineSink>,
+    running: Option<(&Mutex<AnalysisSlot>, u64)>,
+) -> Result<String, PbError> {
+    let local = Mutex::new(AnalysisSlot::Starting(0));
+    let (slot, id) = running.unwrap_or((&local, 0));
+
     let mut cmd = Command::new(pb_binary());
     cmd.args(&args);
     for (key, val) in &env_vars {
         cmd.env(key, val);
     }
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to run projectbridge: {}", e))?;
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| {
+        release_analysis(slot, id);
+        PbError::new(
+            PbErrorKind::SpawnFailed,
+            format!("Failed to run {}: {}", pb_binary(), e),
+        )
+    })?;
+    let stdout = drain_pipe(child.stdout.take(), on_line.clone());
+    let stderr = drain_pipe(child.stderr.take(), on_line);
+
+    if let Err(mut child) = install_child(slot, id, child) {
+        // Cancelled before the CLI started.
+        let _ = child.kill();
+        let _ = child.wait();
+        let _ = stdout.join();
+        return Err(PbError {
+            stderr: String::from_utf8_lossy(&stderr.join().unwrap_or_default()).into_owned(),
+            ..PbError::new(PbErrorKind::Cancelled, "projectbridge was cancelled")
+        });
+    }
+
+    let deadline = Instant::now() + pb_timeout();
+    let status = loop {
+        let mut guard = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        // Anything but our own child means we were cancelled, and another
+        // run may have claimed the slot since.
+        let child = match &mut *guard {
+            AnalysisSlot::Running(owner, child) if *owner == id => child,
+            _ => {
+                drop(guard);
+                return Err(PbError {
+                    stderr: String::from_utf8_lossy(&stderr.join().unwrap_or_default())
+                        .into_owned(),
+                    ..PbError::new(PbErrorKind::Cancelled, "projectbridge was cancelled")
+                });
+            }
+        };
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                *guard = AnalysisSlot::Idle;
+                break status;
+            }
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                *guard = AnalysisSlot::Idle;
+                drop(guard);
+                return Err(PbError {
+                    stderr: String::from_utf8_lossy(&stderr.join().unwrap_or_default())
+                        .into_owned(),
+                    ..PbError::new(PbErrorKind::TimedOut, "projectbridge timed out")
+                });
+            }
+            Ok(None) => {}
+            Err(e) => {
+                // Don't leave a child we can no longer watch running.
+                let _ = child.kill();
+                let _ = child.wait();
+                *guard = AnalysisSlot::Idle;
+                drop(guard);
+                let _ = stdout.join();
+                return Err(PbError {
+                    stderr: String::from_utf8_lossy(&stderr.join().unwrap_or_default())
+                        .into_owned(),
+                    ..PbError::new(
+                        PbErrorKind::SpawnFailed,
+                        format!("Failed to wait for projectbridge: {}", e),
+                    )
+                });
+            }
+        }
+        drop(guard);
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
 
-    if output.status.success() {
-        String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 output: {}", e))
+    let stderr = String::from_utf8_lossy(&stderr).into_owned();
+
+    if status.success() {
+        String::from_utf8(stdout).map_err(|e| PbError {
+            stderr,
+            ..PbError::new(
+                PbErrorKind::InvalidUtf8,
+                format!("Invalid UTF-8 output: {}", e),
+            )
+        })
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("projectbridge failed: {}", stderr))
+        let code = status.code();
+        let message = match code {
+            Some(code) => format!("projectbridge failed with exit code {}", code),
+            None => "projectbridge was terminated by a signal".to_string(),
+        };
+        Err(PbError {
+            stderr,
+            ..PbError::new(PbErrorKind::NonZeroExit { code }, message)
+        })
     }
 }
 
-#[tauri::command]
-fn run_analysis(args: Vec<String>) -> Result<String, String> {
-    execute_pb(args, vec![])
+/// Env vars `run_analysis` callers may not set: they decide which binary,
+/// interpreter or libraries run rather than how the CLI behaves.
+const BLOCKED_ENV_VARS: &[&str] = &[
+    "PATH",
+    "PROJECTBRIDGE_BIN",
+    "PYTHONPATH",
+    "PYTHONHOME",
+    "PYTHONSTARTUP",
+    "PYTHONUSERBASE",
+];
+/// Prefixes of dynamic-loader variables, blocked for the same reason.
+const BLOCKED_ENV_PREFIXES: &[&str] = &["LD_", "DYLD_"];
+
+/// Check that every key is a plain env var name (`[A-Za-z_][A-Za-z0-9_]*`)
+/// that isn't blocked, and that no value contains a NUL byte.
+fn validate_env(env: &[(String, String)]) -> Result<(), PbError> {
+    for (key, value) in env {
+        let mut chars = key.chars();
+        let well_formed = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !well_formed {
+            return Err(PbError::new(
+                PbErrorKind::InvalidInput,
+                format!("Invalid environment variable name: {:?}", key),
+            ));
+        }
+        let upper = key.to_ascii_uppercase();
+        if BLOCKED_ENV_VARS.contains(&upper.as_str())
+            || BLOCKED_ENV_PREFIXES.iter().any(|p| upper.starts_with(p))
+        {
+            return Err(PbError::new(
+                PbErrorKind::InvalidInput,
+                format!("Environment variable {} cannot be overridden", key),
+            ));
+        }
+        if value.contains('\0') {
+            return Err(PbError::new(
+                PbErrorKind::InvalidInput,
+                format!("Value of {} contains a NUL byte", key),
+            ));
+        }
+    }
+    Ok(())
 }
 
+/// Run the CLI with raw `args`, optionally with extra env vars such as
+/// `OPENAI_BASE_URL` or `HTTPS_PROXY` (see `validate_env`).
 #[tauri::command]
+fn run_analysis(args: Vec<String>, env: Option<Vec<(String, String)>>) -> Result<String, PbError> {
+    let env = env.unwrap_or_default();
+    validate_env(&env)?;
+    execute_pb(args, env)
+}
+
+// Runs off the main thread so progress events reach the webview while the
+// CLI is still working.
+#[tauri::command(async)]
 fn run_analysis_form(
+    window: Window,
+    running: tauri::State<'_, RunningAnalysis>,
     github_user: String,
-    job_text: String,
+    job_text: Option<String>,
+    job_path: Option<String>,
     resume_text: Option<String>,
+    resume_path: Option<String>,
     provider: String,
     api_key: Option<String>,
     ollama_model: Option<String>,
-) -> Result<String, String> {
-    let job_is_url = job_text.starts_with("http://") || job_text.starts_with("https://");
+) -> Result<String, PbError> {
     let mut cmd_args = vec![
         "analyze".to_string(),
         "--github-user".to_string(),
         github_user,
-        if job_is_url { "--job-url" } else { "--job-text" }.to_string(),
-        job_text,
     ];
 
-    if let Some(resume) = resume_text {
-        if !resume.is_empty() {
+    push_job_args(&mut cmd_args, job_text, job_path)?;
+    push_resume_args(&mut cmd_args, resume_text, resume_path)?;
+
+    let env_vars = push_provider_args(&mut cmd_args, provider, api_key, ollama_model)?;
+
+    let progress: LineSink = Arc::new(move |line: &str| {
+        let _ = window.emit("pb-progress", line);
+    });
+    let id = claim_analysis(&running.0)?;
+    execute_pb_streaming(cmd_args, env_vars, Some(progress), Some((&running.0, id)))
+}
+
+/// Kill the analysis started by `run_analysis_form`, which then fails with
+/// `Cancelled`. Returns whether there was one to stop.
+#[tauri::command]
+fn cancel_analysis(running: tauri::State<'_, RunningAnalysis>) -> bool {
+    cancel_slot(&running.0)
+}
+
+/// Analyze against a job using a `ScanResult` the app already has (from
+/// `scan_repos_native`), instead of scanning the repos again. The scan is
+/// handed to the CLI as a file via `--dev-context`.
+///
+/// The in-repo CLI (`engine/projectbridge/cli.py`) has no `--dev-context`
+/// yet, so until it accepts a serialized `ScanResult` there in place of
+/// `--github-user`/`--local-repos`, every call fails with a `NonZeroExit`
+/// usage error (exit code 2).
+///
+/// Shares the analysis slot with `run_analysis_form`, so only one of them
+/// runs at a time and `cancel_analysis` stops either.
+#[tauri::command(async)]
+fn analyze_from_scan(
+    window: Window,
+    running: tauri::State<'_, RunningAnalysis>,
+    scan_json: String,
+    job_text: String,
+    provider: String,
+    api_key: Option<String>,
+    ollama_model: Option<String>,
+) -> Result<String, PbError> {
+    if let Err(e) = serde_json::from_str::<serde_json::Value>(&scan_json) {
+        return Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            format!("Scan result is not valid JSON: {}", e),
+        ));
+    }
+
+    with_temp_json(&scan_json, "pb_dev_context.json", |path| {
+        let mut cmd_args = vec![
+            "analyze".to_string(),
+            "--dev-context".to_string(),
+            path.to_string(),
+            job_flag(&job_text).to_string(),
+            job_text.clone(),
+        ];
+        let env_vars = push_provider_args(
+            &mut cmd_args,
+            provider.clone(),
+            api_key.clone(),
+            ollama_model.clone(),
+        )?;
+
+        let window = window.clone();
+        let progress: LineSink = Arc::new(move |line: &str| {
+            let _ = window.emit("pb-progress", line);
+        });
+        let id = claim_analysis(&running.0)?;
+        execute_pb_streaming(cmd_args, env_vars, Some(progress), Some((&running.0, id)))
+    })
+}
+
+/// Append the resume to `analyze` args: inline as `--resume-text`, or as
+/// a file the CLI reads itself (`--resume`), so a large resume never has
+/// to cross the IPC boundary. Empty values count as unset; setting both is
+/// an error, as is a path that can't be opened as a file.
+fn push_resume_args(
+    cmd_args: &mut Vec<String>,
+    resume_text: Option<String>,
+    resume_path: Option<String>,
+) -> Result<(), PbError> {
+    let resume_text = resume_text.filter(|t| !t.is_empty());
+    let resume_path = resume_path.filter(|p| !p.is_empty());
+    match (resume_text, resume_path) {
+        (Some(_), Some(_)) => Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            "Give either resume text or a resume file, not both",
+        )),
+        (Some(text), None) => {
             cmd_args.push("--resume-text".to_string());
-            cmd_args.push(resume);
+            cmd_args.push(text);
+            Ok(())
         }
+        (None, Some(path)) => {
+            check_readable_file(&path, "Resume")?;
+            cmd_args.push("--resume".to_string());
+            cmd_args.push(path);
+            Ok(())
+        }
+        (None, None) => Ok(()),
     }
+}
 
+/// Append the job to `analyze` args: as a posting URL or inline text (see
+/// `job_flag`), or as a saved description the CLI reads itself (`--job`).
+/// The CLI is expected to extract the text of a `.pdf`; `.txt` and `.md`
+/// files are read as they are. Empty values count as unset; exactly one of
+/// the two must be given, and a path must open as a file.
+fn push_job_args(
+    cmd_args: &mut Vec<String>,
+    job_text: Option<String>,
+    job_path: Option<String>,
+) -> Result<(), PbError> {
+    let job_text = job_text.filter(|t| !t.is_empty());
+    let job_path = job_path.filter(|p| !p.is_empty());
+    match (job_text, job_path) {
+        (Some(_), Some(_)) => Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            "Give either a job description or a job file, not both",
+        )),
+        (Some(text), None) => {
+            cmd_args.push(job_flag(&text).to_string());
+            cmd_args.push(text);
+            Ok(())
+        }
+        (None, Some(path)) => {
+            check_readable_file(&path, "Job")?;
+            cmd_args.push("--job".to_string());
+            cmd_args.push(path);
+            Ok(())
+        }
+        (None, None) => Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            "A job description or job file is required",
+        )),
+    }
+}
+
+/// Check that `path` opens as a regular file, naming it as the `what`
+/// (e.g. `Resume`) file in the error.
+fn check_readable_file(path: &str, what: &str) -> Result<(), PbError> {
+    match std::fs::File::open(path).and_then(|f| f.metadata()) {
+        Ok(meta) if meta.is_file() => Ok(()),
+        Ok(_) => Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            format!("{} path is not a file: {}", what, path),
+        )),
+        Err(e) => Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            format!("Cannot read {} file {}: {}", what.to_lowercase(), path, e),
+        )),
+    }
+}
+
+/// The `analyze` flag for a job given as either a posting URL or its text.
+fn job_flag(job_text: &str) -> &'static str {
+    if job_text.starts_with("http://") || job_text.starts_with("https://") {
+        "--job-url"
+    } else {
+        "--job-text"
+    }
+}
+
+/// Append the `--provider` and `--ollama-model` args, returning the env var
+/// that carries `api_key` for the provider, if one was given.
+fn push_provider_args(
+    cmd_args: &mut Vec<String>,
+    provider: String,
+    api_key: Option<String>,
+    ollama_model: Option<String>,
+) -> Result<Vec<(String, String)>, PbError> {
     cmd_args.push("--provider".to_string());
     cmd_args.push(provider.clone());
 
@@ -84,19 +622,25 @@ fn run_analysis_form(
     let mut env_vars: Vec<(String, String)> = vec![];
     if let Some(key) = api_key {
         if !key.is_empty() {
-            match provider.as_str() {
-                "openai" => env_vars.push(("OPENAI_API_KEY".to_string(), key)),
-                "anthropic" => env_vars.push(("ANTHROPIC_API_KEY".to_string(), key)),
-                _ => {}
-            }
+            let key_var = match provider.as_str() {
+                "openai" => "OPENAI_API_KEY",
+                "anthropic" => "ANTHROPIC_API_KEY",
+                "gemini" => "GOOGLE_API_KEY",
+                other => {
+                    return Err(PbError::new(
+                        PbErrorKind::InvalidInput,
+                        format!("Provider '{}' does not take an API key", other),
+                    ))
+                }
+            };
+            env_vars.push((key_var.to_string(), key));
         }
     }
-
-    execute_pb(cmd_args, env_vars)
+    Ok(env_vars)
 }
 
 #[tauri::command]
-fn export_analysis(analysis_json: String, format: String) -> Result<String, String> {
+fn export_analysis(analysis_json: String, format: String) -> Result<String, PbError> {
     with_temp_json(&analysis_json, "pb_export_input.json", |path| {
         execute_pb(
             vec![
@@ -111,8 +655,10 @@ fn export_analysis(analysis_json: String, format: String) -> Result<String, Stri
     })
 }
 
+/// Scan local repositories through the Python CLI. Kept for compatibility;
+/// prefer `scan_repos_native`, which needs no CLI install.
 #[tauri::command]
-fn scan_local_repos(paths: Vec<String>) -> Result<String, String> {
+fn scan_local_repos(paths: Vec<String>) -> Result<String, PbError> {
     let mut cmd_args = vec!["analyze".to_string(), "--provider".to_string(), "none".to_string()];
     cmd_args.push("--local-repos".to_string());
     cmd_args.extend(paths);
@@ -123,6 +669,36 @@ fn scan_local_repos(paths: Vec<String>) -> Result<String, String> {
     execute_pb(cmd_args, vec![])
 }
 
+/// Scan local repositories in-process with `pb_scan`, skipping the CLI and
+/// its placeholder job text. Returns the serialized `ScanResult`.
+// Runs off the main thread so a large walk doesn't freeze the webview.
+#[tauri::command(async)]
+fn scan_repos_native(paths: Vec<String>) -> Result<String, PbError> {
+    if paths.is_empty() {
+        return Err(PbError::new(
+            PbErrorKind::InvalidInput,
+            "No repository paths given",
+        ));
+    }
+    let roots: Vec<&Path> = paths.iter().map(Path::new).collect();
+    for root in &roots {
+        if !root.is_dir() {
+            return Err(PbError::new(
+                PbErrorKind::InvalidInput,
+                format!("Not a directory: {}", root.display()),
+            ));
+        }
+    }
+
+    let result = pb_scan::scan_directories(&roots);
+    serde_json::to_string(&result).map_err(|e| {
+        PbError::new(
+            PbErrorKind::UnexpectedOutput,
+            format!("Failed to serialize scan result: {}", e),
+        )
+    })
+}
+
 #[tauri::command]
 fn export_project_spec(
     analysis_json: String,
@@ -130,7 +706,7 @@ fn export_project_spec(
     difficulty: String,
     format: Option<String>,
     no_ai: bool,
-) -> Result<String, String> {
+) -> Result<String, PbError> {
     let fmt = format.unwrap_or_else(|| "markdown".to_string());
 
     with_temp_json(&analysis_json, "pb_export_project_input.json", |path| {
@@ -154,40 +730,150 @@ fn export_project_spec(
     })
 }
 
+/// Where Ollama listens unless `OLLAMA_HOST` says otherwise.
+const DEFAULT_OLLAMA_HOST: &str = "127.0.0.1";
+const DEFAULT_OLLAMA_PORT: u16 = 11434;
+
+/// Parse an `OLLAMA_HOST` value (`host`, `host:port` or `http://host:port`)
+/// into the `host:port` endpoint and its resolved socket address.
+fn parse_ollama_host(raw: &str) -> Result<(String, SocketAddr), String> {
+    let invalid = || {
+        format!(
+            "Invalid OLLAMA_HOST '{}': expected host:port or http://host:port",
+            raw
+        )
+    };
+    let trimmed = raw.trim();
+    let host = trimmed
+        .strip_prefix("http://")
+        .unwrap_or(trimmed)
+        .trim_end_matches('/');
+    if host.is_empty() || host.contains("://") || host.contains('/') {
+        return Err(invalid());
+    }
+
+    // Bare hosts (including bracketed IPv6 like "[::1]") get the default port.
+    let has_port = match host.rsplit_once(':') {
+        Some((h, port)) => !h.is_empty() && !h.ends_with(':') && port.parse::<u16>().is_ok(),
+        None => false,
+    };
+    let endpoint = if has_port {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, DEFAULT_OLLAMA_PORT)
+    };
+
+    let addr = endpoint
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(invalid)?;
+    Ok((endpoint, addr))
+}
+
+/// Resolve the Ollama endpoint from `OLLAMA_HOST`, falling back to the local
+/// default when it is unset or empty.
+fn ollama_endpoint() -> Result<(String, SocketAddr), String> {
+    match std::env::var("OLLAMA_HOST") {
+        Ok(raw) if !raw.trim().is_empty() => parse_ollama_host(&raw),
+        _ => parse_ollama_host(DEFAULT_OLLAMA_HOST),
+    }
+}
+
+/// Open a connection to the Ollama server, returning the stream and the
+/// `host:port` endpoint it was reached at.
+fn connect_ollama() -> Result<(TcpStream, String), String> {
+    let (endpoint, addr) = ollama_endpoint()?;
+    let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(3))
+        .map_err(|_| format!("Ollama server is not reachable at {}", endpoint))?;
+    Ok((stream, endpoint))
+}
+
+/// What the frontend needs to know to enable or disable features.
+#[derive(serde::Serialize)]
+struct EnvironmentStatus {
+    /// The resolved `projectbridge` binary (from `PROJECTBRIDGE_BIN` or PATH).
+    pb_binary: String,
+    /// Whether `projectbridge --version` ran and reported a version.
+    pb_available: bool,
+    /// Version reported by the CLI, when it ran.
+    pb_version: Option<String>,
+    /// Whether the local Ollama server accepted a connection.
+    ollama_reachable: bool,
+}
+
+// Runs off the main thread: the CLI check can take up to `pb_timeout()`
+// and the Ollama probe a few seconds, which would freeze the webview.
+#[tauri::command(async)]
+fn check_environment(cache: tauri::State<'_, CachedPbVersion>) -> EnvironmentStatus {
+    let version = cached_pb_version(&cache).ok();
+    EnvironmentStatus {
+        pb_binary: pb_binary(),
+        pb_available: version.is_some(),
+        pb_version: version,
+        ollama_reachable: connect_ollama().is_ok(),
+    }
+}
+
+/// The CLI version from `cache`, running `projectbridge --version` when
+/// there is none yet. Only a successful answer is cached, so installing
+/// the CLI while the app is open is picked up on the next call. The lock
+/// isn't held while the CLI runs; overlapping first calls may both run it.
+fn cached_pb_version(cache: &CachedPbVersion) -> Result<String, PbError> {
+    let cached = cache.0.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(version) = cached.as_ref() {
+        return Ok(version.clone());
+    }
+    drop(cached);
+    let stdout = execute_pb(vec!["--version".to_string()], vec![])?;
+    let version = parse_pb_version(&stdout).ok_or_else(|| {
+        PbError::new(
+            PbErrorKind::UnexpectedOutput,
+            "projectbridge --version printed nothing",
+        )
+    })?;
+    *cache.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(version.clone());
+    Ok(version)
+}
+
+/// The version of the `projectbridge` CLI the app talks to (see
+/// `cached_pb_version`).
+// Runs off the main thread, since the first call waits on the CLI.
+#[tauri::command(async)]
+fn pb_version(cache: tauri::State<'_, CachedPbVersion>) -> Result<String, PbError> {
+    cached_pb_version(&cache)
+}
+
 #[tauri::command]
 fn list_ollama_models() -> Result<Vec<String>, String> {
-    let mut stream = TcpStream::connect_timeout(
-        &"127.0.0.1:11434".parse().unwrap(),
-        Duration::from_secs(3),
-    )
-    .map_err(|_| "Ollama server is not reachable at localhost:11434".to_string())?;
+    let (mut stream, endpoint) = connect_ollama()?;
 
     stream
         .set_read_timeout(Some(Duration::from_secs(5)))
         .map_err(|e| format!("Failed to set timeout: {}", e))?;
 
-    let request = "GET /api/tags HTTP/1.0\r\nHost: localhost:11434\r\n\r\n";
+    let request = format!("GET /api/tags HTTP/1.0\r\nHost: {}\r\n\r\n", endpoint);
     stream
         .write_all(request.as_bytes())
-        .map_err(|e| format!("Failed to send request: {}", e))?;
+        .map_err(|e| format!("Failed to send request to {}: {}", endpoint, e))?;
 
     let mut response = String::new();
     stream
         .read_to_string(&mut response)
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+        .map_err(|e| format!("Failed to read response from {}: {}", endpoint, e))?;
 
     // Split HTTP headers from body.
     let body = response
         .split("\r\n\r\n")
         .nth(1)
-        .ok_or_else(|| "Invalid HTTP response from Ollama".to_string())?;
+        .ok_or_else(|| format!("Invalid HTTP response from Ollama at {}", endpoint))?;
 
-    let parsed: serde_json::Value =
-        serde_json::from_str(body).map_err(|e| format!("Invalid JSON from Ollama: {}", e))?;
+    let parsed: serde_json::Value = serde_json::from_str(body)
+        .map_err(|e| format!("Invalid JSON from Ollama at {}: {}", endpoint, e))?;
 
     let models = parsed["models"]
         .as_array()
-        .ok_or_else(|| "Unexpected response format from Ollama".to_string())?;
+        .ok_or_else(|| format!("Unexpected response format from Ollama at {}", endpoint))?;
 
     let names: Vec<String> = models
         .iter()
@@ -209,14 +895,64 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(RunningAnalysis::default())
+        .manage(CachedPbVersion::default())
         .invoke_handler(tauri::generate_handler![
             run_analysis,
             run_analysis_form,
+            cancel_analysis,
+            analyze_from_scan,
             export_analysis,
             scan_local_repos,
+            scan_repos_native,
             export_project_spec,
+            check_environment,
+            pb_version,
             list_ollama_models
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_analysis_twice() {
+        let slot = Mutex::new(AnalysisSlot::default());
+        let id = claim_analysis(&slot).unwrap();
+        let err = claim_analysis(&slot).unwrap_err();
+        assert!(matches!(err.kind, PbErrorKind::InvalidInput));
+        assert!(matches!(*slot.lock().unwrap(), AnalysisSlot::Starting(owner) if owner == id));
+
+        // Freed once the run ends.
+        *slot.lock().unwrap() = AnalysisSlot::Idle;
+        assert!(claim_analysis(&slot).is_ok());
+    }
+
+    #[test]
+    fn test_cancelled_run_does_not_steal_slot() {
+        let slot = Mutex::new(AnalysisSlot::default());
+        let first = claim_analysis(&slot).unwrap();
+        assert!(cancel_slot(&slot));
+        let second = claim_analysis(&slot).unwrap();
+        assert_ne!(first, second);
+
+        // The cancelled run neither frees nor takes over the new claim.
+        release_analysis(&slot, first);
+        assert!(matches!(*slot.lock().unwrap(), AnalysisSlot::Starting(owner) if owner == second));
+        // The test binary itself, which just lists its tests and exits.
+        let child = Command::new(std::env::current_exe().unwrap())
+            .arg("--list")
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        let mut child = install_child(&slot, first, child).unwrap_err();
+        let _ = child.wait();
+        assert!(matches!(*slot.lock().unwrap(), AnalysisSlot::Starting(owner) if owner == second));
+
+        assert!(cancel_slot(&slot));
+        assert!(!cancel_slot(&slot));
+    }
+}
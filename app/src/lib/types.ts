@@ -43,6 +43,44 @@ export interface ProjectSpec {
 
 export type View = "form" | "loading" | "results" | "export";
 
+/** Error returned by the commands that run the projectbridge CLI. */
+export interface PbError {
+  kind:
+    | "SpawnFailed"
+    | "NonZeroExit"
+    | "InvalidUtf8"
+    | "UnexpectedOutput"
+    | "TimedOut"
+    | "InvalidInput"
+    | "Cancelled";
+  /** Exit code, for `NonZeroExit` (null when killed by a signal). */
+  code?: number | null;
+  stderr: string;
+  message: string;
+}
+
+function isPbError(e: unknown): e is PbError {
+  return typeof e === "object" && e !== null && "kind" in e && "message" in e;
+}
+
+/** Whether a rejected `invoke` was stopped by `cancel_analysis`. */
+export function isCancelled(e: unknown): boolean {
+  return isPbError(e) && e.kind === "Cancelled";
+}
+
+/** Turn a rejected `invoke` into a message worth showing the user. */
+export function errorMessage(e: unknown): string {
+  if (!isPbError(e)) return String(e);
+  switch (e.kind) {
+    case "SpawnFailed":
+      return `${e.message}. Is projectbridge installed? Set PROJECTBRIDGE_BIN to its path if it is not on PATH.`;
+    case "NonZeroExit":
+      return e.stderr.trim() ? `${e.message}: ${e.stderr.trim()}` : e.message;
+    default:
+      return e.message;
+  }
+}
+
 export const categoryLabels: Record<string, string> = {
   language: "Languages",
   framework: "Frameworks",
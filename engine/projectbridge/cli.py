@@ -85,7 +85,7 @@ def _build_parser() -> argparse.ArgumentParser:
     )
     analyze.add_argument(
         "--provider",
-        choices=["none", "openai", "anthropic", "ollama"],
+        choices=["none", "openai", "anthropic", "gemini", "ollama"],
         default=None,
         help="AI provider to use. Overrides config file. 'none' = heuristic only.",
     )
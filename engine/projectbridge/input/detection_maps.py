@@ -41,9 +41,9 @@ FRAMEWORK_INDICATORS: dict[str, tuple[str, str]] = {
     "vite.config.ts": ("Vite", "tool"),
     "vite.config.js": ("Vite", "tool"),
     ".prettierrc": ("Prettier", "tool"),
-    "jest.config.js": ("Jest", "tool"),
-    "jest.config.ts": ("Jest", "tool"),
-    "pytest.ini": ("pytest", "tool"),
+    "jest.config.js": ("Jest", "testing"),
+    "jest.config.ts": ("Jest", "testing"),
+    "pytest.ini": ("pytest", "testing"),
     "pyproject.toml": ("Python Package", "tool"),
     "Cargo.toml": ("Rust", "language"),
     "go.mod": ("Go", "language"),
@@ -69,14 +69,20 @@ NPM_FRAMEWORK_MAP: dict[str, tuple[str, str]] = {
     "fastify": ("Fastify", "framework"),
     "gatsby": ("Gatsby", "framework"),
     "remix": ("Remix", "framework"),
+    "@remix-run/react": ("Remix", "framework"),
+    "astro": ("Astro", "framework"),
+    "solid-js": ("SolidJS", "framework"),
+    "@builder.io/qwik": ("Qwik", "framework"),
+    "preact": ("Preact", "framework"),
+    "@sveltejs/kit": ("SvelteKit", "framework"),
     "@nestjs/core": ("NestJS", "framework"),
     "koa": ("Koa", "framework"),
     "tailwindcss": ("Tailwind CSS", "framework"),
     "prisma": ("Prisma", "tool"),
     "mongoose": ("Mongoose", "tool"),
     "sequelize": ("Sequelize", "tool"),
-    "jest": ("Jest", "tool"),
-    "mocha": ("Mocha", "tool"),
+    "jest": ("Jest", "testing"),
+    "mocha": ("Mocha", "testing"),
     "webpack": ("Webpack", "tool"),
     "vite": ("Vite", "tool"),
     "typescript": ("TypeScript", "language"),
@@ -90,6 +96,7 @@ NPM_FRAMEWORK_MAP: dict[str, tuple[str, str]] = {
     "mongodb": ("MongoDB", "tool"),
     "supabase": ("Supabase", "tool"),
     "firebase": ("Firebase", "tool"),
+    "wasm-pack": ("wasm-pack", "tool"),
 }
 
 PYTHON_FRAMEWORK_MAP: dict[str, tuple[str, str]] = {
@@ -105,7 +112,7 @@ PYTHON_FRAMEWORK_MAP: dict[str, tuple[str, str]] = {
     "scikit-learn": ("scikit-learn", "framework"),
     "tensorflow": ("TensorFlow", "framework"),
     "torch": ("PyTorch", "framework"),
-    "pytest": ("pytest", "tool"),
+    "pytest": ("pytest", "testing"),
     "pydantic": ("Pydantic", "tool"),
     "requests": ("Requests", "tool"),
     "boto3": ("AWS SDK", "tool"),
@@ -125,13 +132,15 @@ RUST_CRATE_MAP: dict[str, tuple[str, str]] = {
     "yew": ("Yew", "framework"),
     "tauri": ("Tauri", "framework"),
     "wasm-bindgen": ("WebAssembly", "tool"),
+    "wasmtime": ("Wasmtime", "tool"),
+    "wasmer": ("Wasmer", "tool"),
 }
 
 RUBY_GEM_MAP: dict[str, tuple[str, str]] = {
     "rails": ("Ruby on Rails", "framework"),
     "sinatra": ("Sinatra", "framework"),
     "sidekiq": ("Sidekiq", "tool"),
-    "rspec": ("RSpec", "tool"),
+    "rspec": ("RSpec", "testing"),
 }
 
 GO_MODULE_MAP: dict[str, tuple[str, str]] = {
@@ -0,0 +1,10 @@
+import { useState } from "preact/hooks";
+
+interface Props {
+  start: number;
+}
+
+export default function Counter({ start }: Props) {
+  const [count, setCount] = useState(start);
+  return <button onClick={() => setCount(count + 1)}>Clicked {count} times</button>;
+}
@@ -0,0 +1,4 @@
+import os
+
+DATABASE_URL = os.environ.get("DATABASE_URL", "postgresql://postgres@db/postgres")
+REDIS_URL = os.environ.get("REDIS_URL", "redis://cache:6379/0")
@@ -0,0 +1,11 @@
+import numpy as np
+import torch
+
+
+def main():
+    data = torch.from_numpy(np.random.rand(16, 4))
+    print(data.mean())
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,7 @@
+using Xunit;
+
+public class HealthTests
+{
+    [Fact]
+    public void Passes() => Assert.True(true);
+}
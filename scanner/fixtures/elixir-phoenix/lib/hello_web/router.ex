@@ -0,0 +1,12 @@
+defmodule HelloWeb.Router do
+  use HelloWeb, :router
+
+  pipeline :api do
+    plug :accepts, ["json"]
+  end
+
+  scope "/api", HelloWeb do
+    pipe_through :api
+    get "/health", HealthController, :index
+  end
+end
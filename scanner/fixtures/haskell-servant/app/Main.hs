@@ -0,0 +1,9 @@
+module Main (main) where
+
+import Api (API, server)
+import Data.Proxy (Proxy (..))
+import Network.Wai.Handler.Warp (run)
+import Servant (serve)
+
+main :: IO ()
+main = run 8080 (serve (Proxy :: Proxy API) server)
@@ -0,0 +1,10 @@
+{-# LANGUAGE DataKinds #-}
+{-# LANGUAGE TypeOperators #-}
+module Api (API, server) where
+
+import Servant
+
+type API = "health" :> Get '[PlainText] String
+
+server :: Server API
+server = return "ok"
@@ -0,0 +1,6 @@
+def main():
+    print("app-b")
+
+
+if __name__ == "__main__":
+    main()
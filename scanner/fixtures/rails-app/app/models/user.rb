@@ -0,0 +1,5 @@
+class User < ApplicationRecord
+  has_many :posts, dependent: :destroy
+
+  validates :email, presence: true, uniqueness: true
+end
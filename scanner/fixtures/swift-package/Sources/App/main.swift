@@ -0,0 +1,5 @@
+import Alamofire
+
+AF.request("https://httpbin.org/get").response { response in
+    debugPrint(response)
+}
@@ -0,0 +1,21 @@
+const std = @import("std");
+
+pub fn build(b: *std.Build) void {
+    const target = b.standardTargetOptions(.{});
+    const optimize = b.standardOptimizeOption(.{});
+
+    const zap = b.dependency("zap", .{ .target = target, .optimize = optimize });
+    const pg = b.dependency("pg", .{ .target = target, .optimize = optimize });
+
+    const exe = b.addExecutable(.{
+        .name = "zig-http",
+        .root_module = b.createModule(.{
+            .root_source_file = b.path("src/main.zig"),
+            .target = target,
+            .optimize = optimize,
+        }),
+    });
+    exe.root_module.addImport("zap", zap.module("zap"));
+    exe.root_module.addImport("pg", pg.module("pg"));
+    b.installArtifact(exe);
+}
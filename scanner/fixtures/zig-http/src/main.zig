@@ -0,0 +1,18 @@
+const std = @import("std");
+const zap = @import("zap");
+
+fn onRequest(r: zap.Request) !void {
+    try r.sendBody("{\"status\":\"ok\"}");
+}
+
+pub fn main() !void {
+    var listener = zap.HttpListener.init(.{
+        .port = 8080,
+        .on_request = onRequest,
+        .log = true,
+    });
+    try listener.listen();
+
+    std.debug.print("Listening on 0.0.0.0:8080\n", .{});
+    zap.start(.{ .threads = 2, .workers = 1 });
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::output::ScanResult;
+use crate::scan::{ScanOptions, SKIP_DIRS};
+
+/// Previous scan results stored on disk, so an unchanged tree isn't read
+/// again. Each entry is keyed by the caller (roots plus whatever options
+/// shape the result) and is only reused while the tree's [`fingerprint`]
+/// still matches.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    fingerprint: String,
+    result: ScanResult,
+}
+
+impl ScanCache {
+    /// Read the cache at `path`. A missing or unreadable cache is empty.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the cache to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self).expect("Failed to serialize cache");
+        fs::write(path, json)
+    }
+
+    /// The cached result for `key` if `roots` haven't changed since it was
+    /// stored; otherwise run `scan` and store what it returns.
+    pub fn get_or_scan(
+        &mut self,
+        key: &str,
+        roots: &[&Path],
+        options: &ScanOptions,
+        scan: impl FnOnce() -> ScanResult,
+    ) -> ScanResult {
+        let fingerprint = fingerprint(roots, options);
+        if let Some(entry) = self.entries.get(key) {
+            if entry.fingerprint == fingerprint {
+                return entry.result.clone();
+            }
+        }
+        let result = scan();
+        self.entries.insert(
+            key.to_string(),
+            CacheEntry {
+                fingerprint,
+                result: result.clone(),
+            },
+        );
+        result
+    }
+}
+
+/// A cheap summary of the trees under `roots` from metadata alone: entry
+/// count, total file size and the newest mtime. Directories count too, so
+/// deleting or renaming a file changes it. Hidden files are included even
+/// when the scan skips them, since manifests like `.github/workflows` are
+/// still read.
+pub fn fingerprint(roots: &[&Path], options: &ScanOptions) -> String {
+    let mut entries: u64 = 0;
+    let mut bytes: u64 = 0;
+    let mut newest: u128 = 0;
+    for root in roots {
+        let walker = WalkBuilder::new(root)
+            .hidden(false)
+            .git_ignore(options.respect_gitignore)
+            .git_global(options.respect_gitignore)
+            .git_exclude(options.respect_gitignore)
+            .follow_links(options.follow_symlinks)
+            .filter_entry(|entry| {
+                let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+                !entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| name == ".git" || (is_dir && SKIP_DIRS.contains(&name)))
+            })
+            .build();
+        for entry in walker.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            entries += 1;
+            if meta.is_file() {
+                bytes += meta.len();
+            }
+            let mtime = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos());
+            newest = newest.max(mtime);
+        }
+    }
+    format!("{entries}:{bytes}:{newest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    use crate::scan::scan_directory_with;
+
+    #[test]
+    fn test_cache_reuses_unchanged_scan() {
+        let tmp = TempDir::new().unwrap();
+        let repo = tmp.path().join("repo");
+        fs::create_dir(&repo).unwrap();
+        fs::write(repo.join("main.py"), "print('hi')\n").unwrap();
+        let cache_path = tmp.path().join("cache.json");
+        let options = ScanOptions::default();
+        let scans = Cell::new(0);
+        let scan = || {
+            scans.set(scans.get() + 1);
+            scan_directory_with(&repo, &options)
+        };
+
+        let mut cache = ScanCache::load(&cache_path);
+        let first = cache.get_or_scan("repo", &[&repo], &options, scan);
+        cache.save(&cache_path).unwrap();
+
+        let mut cache = ScanCache::load(&cache_path);
+        let second = cache.get_or_scan("repo", &[&repo], &options, scan);
+        assert_eq!(scans.get(), 1, "second scan should come from the cache");
+        assert_eq!(first, second);
+
+        // A different key misses even for the same tree.
+        cache.get_or_scan("repo --lines", &[&repo], &options, scan);
+        assert_eq!(scans.get(), 2);
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_change() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hi')\n").unwrap();
+        let options = ScanOptions::default();
+        let before = fingerprint(&[tmp.path()], &options);
+        assert_eq!(before, fingerprint(&[tmp.path()], &options));
+
+        fs::write(tmp.path().join("lib.rs"), "fn f() {}\n").unwrap();
+        let added = fingerprint(&[tmp.path()], &options);
+        assert_ne!(before, added);
+
+        fs::write(tmp.path().join("lib.rs"), "fn g() {}\n").unwrap();
+        fs::remove_file(tmp.path().join("lib.rs")).unwrap();
+        assert_ne!(added, fingerprint(&[tmp.path()], &options));
+    }
+
+    #[test]
+    fn test_cache_load_corrupt() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("cache.json");
+        fs::write(&path, "not json").unwrap();
+        assert!(ScanCache::load(&path).entries.is_empty());
+    }
+}
@@ -1,12 +1,860 @@
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use globset::Glob;
+
+use crate::files::RepoFiles;
+use crate::frameworks::insert_signal;
+use crate::output::SignalEntry;
+use crate::rules::{rules, CustomRule};
+
+/// Extract a version string from a Cargo dependency value, which is either
+/// a bare requirement (`"4"`) or a table with a `version` key.
+fn cargo_dep_version(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(v) => Some(v.clone()),
+        toml::Value::Table(t) => t.get("version").and_then(|v| v.as_str()).map(String::from),
+        _ => None,
+    }
+}
+
+/// Find the version that follows a module path in go.mod, e.g. `v1.9.0`.
+fn go_module_version(content: &str, module: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut tokens = line.split_whitespace();
+        tokens.find(|t| t.starts_with(module))?;
+        tokens.next().map(String::from)
+    })
+}
+
+/// Collect package names and resolved versions from whichever npm lockfiles
+/// exist (`package-lock.json`, `yarn.lock`, `pnpm-lock.yaml`).
+pub fn detect_npm_lock(dir: &(impl RepoFiles + ?Sized)) -> HashMap<String, Option<String>> {
+    let mut packages = HashMap::new();
+    if let Ok(content) = dir.read_to_string(Path::new("package-lock.json")) {
+        packages.extend(parse_package_lock(&content));
+    }
+    if let Ok(content) = dir.read_to_string(Path::new("yarn.lock")) {
+        packages.extend(parse_yarn_lock(&content));
+    }
+    if let Ok(content) = dir.read_to_string(Path::new("pnpm-lock.yaml")) {
+        packages.extend(parse_pnpm_lock(&content));
+    }
+    packages
+}
+
+/// Lockfiles that give away which JavaScript package manager a project
+/// uses. Bun wrote the binary `bun.lockb` before switching to `bun.lock`.
+const PACKAGE_MANAGER_LOCKFILES: &[(&str, &str)] = &[
+    ("package-lock.json", "npm"),
+    ("yarn.lock", "Yarn"),
+    ("pnpm-lock.yaml", "pnpm"),
+    ("bun.lockb", "Bun"),
+    ("bun.lock", "Bun"),
+];
+
+/// Detect the JavaScript package managers in use from the lockfiles at the
+/// root, as `tool` signals. A repo mid-migration may report more than one.
+pub fn detect_package_managers(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let files: Vec<String> = dir
+        .read_dir(Path::new(""))
+        .into_iter()
+        .filter(|(_, is_dir)| !is_dir)
+        .map(|(name, _)| name)
+        .collect();
+    for &(lockfile, name) in PACKAGE_MANAGER_LOCKFILES {
+        if files.iter().any(|f| f == lockfile) {
+            insert_signal(frameworks, SignalEntry::new(name, "tool", None));
+        }
+    }
+}
+
+/// Top-level packages from package-lock.json. Lockfile v2+ lists them under
+/// `packages` as `node_modules/<name>`; v1 uses a `dependencies` object.
+fn parse_package_lock(content: &str) -> HashMap<String, Option<String>> {
+    let mut packages = HashMap::new();
+    let parsed: serde_json::Value = match serde_json::from_str(content) {
+        Ok(v) => v,
+        Err(_) => return packages,
+    };
+    let version_of = |v: &serde_json::Value| v["version"].as_str().map(String::from);
+
+    if let Some(obj) = parsed["packages"].as_object() {
+        for (key, value) in obj {
+            if let Some(name) = key.strip_prefix("node_modules/") {
+                if !name.contains("/node_modules/") {
+                    packages.insert(name.to_string(), version_of(value));
+                }
+            }
+        }
+    } else if let Some(obj) = parsed["dependencies"].as_object() {
+        for (name, value) in obj {
+            packages.insert(name.clone(), version_of(value));
+        }
+    }
+    packages
+}
+
+/// Packages from yarn.lock (classic and Berry). Each unindented header lists
+/// one or more specs like `react@^18.2.0`, followed by an indented `version`.
+fn parse_yarn_lock(content: &str) -> HashMap<String, Option<String>> {
+    let mut packages = HashMap::new();
+    let mut current: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            current = line
+                .trim_end_matches(':')
+                .split(", ")
+                .filter_map(|spec| {
+                    let spec = spec.trim_matches('"');
+                    // Skip a leading `@` so scoped names keep their scope.
+                    let (at, _) = spec.char_indices().skip(1).find(|&(_, c)| c == '@')?;
+                    Some(spec[..at].to_string())
+                })
+                .collect();
+            for name in &current {
+                packages.entry(name.clone()).or_insert(None);
+            }
+        } else if let Some(version) = line.trim().strip_prefix("version") {
+            let version = version.trim_start_matches(':').trim().trim_matches('"');
+            for name in current.drain(..) {
+                packages.insert(name, Some(version.to_string()));
+            }
+        }
+    }
+    packages
+}
+
+/// Top-level packages from pnpm-lock.yaml. Lockfile v9 nests them under
+/// `importers["."]`; older versions put them at the document root.
+fn parse_pnpm_lock(content: &str) -> HashMap<String, Option<String>> {
+    let mut packages = HashMap::new();
+    let parsed: serde_yaml::Value = match serde_yaml::from_str(content) {
+        Ok(v) => v,
+        Err(_) => return packages,
+    };
+    let root = match parsed.get("importers").and_then(|i| i.get(".")) {
+        Some(importer) => importer,
+        None => &parsed,
+    };
+
+    for key in &["dependencies", "devDependencies"] {
+        if let Some(deps) = root.get(key).and_then(|d| d.as_mapping()) {
+            for (name, value) in deps {
+                let Some(name) = name.as_str() else {
+                    continue;
+                };
+                // Either `react: 18.2.0` or `react: {specifier, version}`.
+                let version = value
+                    .as_str()
+                    .or_else(|| value.get("version").and_then(|v| v.as_str()))
+                    .map(String::from);
+                packages.insert(name.to_string(), version);
+            }
+        }
+    }
+    packages
+}
 
 /// Detect frameworks from package.json dependencies.
 /// Port of NPM_FRAMEWORK_MAP from github.py.
-pub fn detect_npm(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    let path = dir.join("package.json");
-    let content = match fs::read_to_string(&path) {
+pub fn detect_npm(dir: &(impl RepoFiles + ?Sized), frameworks: &mut HashMap<String, SignalEntry>) {
+    let all_deps = npm_dependencies(dir);
+    for rule in rules("package.json") {
+        if let Some(version) = all_deps.get(rule.key) {
+            insert_signal(
+                frameworks,
+                SignalEntry::new(rule.name, rule.category, version.clone()),
+            );
+        }
+    }
+}
+
+/// Declared package.json dependencies with their versions, plus whatever
+/// else the lockfiles resolve.
+fn npm_dependencies(dir: &(impl RepoFiles + ?Sized)) -> HashMap<String, Option<String>> {
+    let parsed: Option<serde_json::Value> = dir
+        .read_to_string(Path::new("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok());
+
+    let mut all_deps: HashMap<String, Option<String>> = HashMap::new();
+    for key in &["dependencies", "devDependencies"] {
+        if let Some(obj) = parsed.as_ref().and_then(|p| p.get(key)?.as_object()) {
+            for (dep, version) in obj {
+                all_deps
+                    .entry(dep.clone())
+                    .or_insert_with(|| version.as_str().map(String::from));
+            }
+        }
+    }
+    // Manifest entries win; the lockfile only fills in what isn't declared.
+    for (dep, version) in detect_npm_lock(dir) {
+        all_deps.entry(dep).or_insert(version);
+    }
+    all_deps
+}
+
+/// Split a PEP 508 requirement like `flask[async]>=2.3; python_version>"3.8"`
+/// into a lowercase package name and its version specifier.
+fn split_requirement(req: &str) -> (String, Option<String>) {
+    let req = req.split(';').next().unwrap_or("").trim();
+    let name_end = req
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(req.len());
+    let rest = req[name_end..].trim_start();
+    let rest = match rest.strip_prefix('[') {
+        Some(extras) => extras.split_once(']').map_or("", |(_, r)| r).trim(),
+        None => rest,
+    };
+    let version = (!rest.is_empty()).then(|| rest.to_string());
+    (req[..name_end].to_lowercase(), version)
+}
+
+/// Version from a Poetry/Pipenv dependency value: `"^1.0"` or `{ version = "^1.0" }`.
+/// A bare `*` means "any version" and yields `None`.
+fn toml_dep_version(value: &toml::Value) -> Option<String> {
+    cargo_dep_version(value).filter(|v| v != "*")
+}
+
+/// A Python package name in PEP 503 normalized form: lowercase, with runs
+/// of `-`, `_` and `.` as a single `-`, so `Django_REST.framework` and
+/// `djangorestframework` compare as the registry does.
+fn normalize_python_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Insert Python rule hits for a set of declared Python package names.
+/// Names match exactly once normalized, so `djangorestframework-stubs`
+/// is not Django and `pytest-cov` doesn't stand in for `pytest`.
+fn match_python_deps(
+    deps: &HashMap<String, Option<String>>,
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let deps: HashMap<String, &Option<String>> = deps
+        .iter()
+        .map(|(dep, version)| (normalize_python_name(dep), version))
+        .collect();
+    for rule in rules("requirements.txt") {
+        if let Some(version) = deps.get(rule.key) {
+            insert_signal(
+                frameworks,
+                SignalEntry::new(rule.name, rule.category, (*version).clone()),
+            );
+        }
+    }
+}
+
+/// Detect frameworks from requirements.txt.
+/// Port of PYTHON_FRAMEWORK_MAP from github.py.
+pub fn detect_python(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("requirements.txt")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let lower = content.to_lowercase();
+
+    for rule in rules("requirements.txt") {
+        if lower.contains(rule.key) {
+            insert_signal(frameworks, SignalEntry::new(rule.name, rule.category, None));
+        }
+    }
+}
+
+/// Read and parse a TOML file, returning `None` if missing or malformed.
+fn read_toml(dir: &(impl RepoFiles + ?Sized), path: &Path) -> Option<toml::Table> {
+    dir.read_to_string(path).ok()?.parse().ok()
+}
+
+/// Add the crates a manifest depends on to `all_deps`, keeping earlier entries.
+fn collect_cargo_deps(manifest: &toml::Table, all_deps: &mut HashMap<String, Option<String>>) {
+    for key in &["dependencies", "dev-dependencies", "build-dependencies"] {
+        if let Some(table) = manifest.get(*key).and_then(|v| v.as_table()) {
+            for (dep, value) in table {
+                all_deps
+                    .entry(dep.clone())
+                    .or_insert_with(|| cargo_dep_version(value));
+            }
+        }
+    }
+}
+
+/// Resolve a workspace `members` entry such as `crates/*` to directories,
+/// relative to the workspace root.
+fn expand_workspace_member(dir: &(impl RepoFiles + ?Sized), pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        if !component.contains(['*', '?', '[']) {
+            paths = paths.into_iter().map(|p| p.join(component)).collect();
+            continue;
+        }
+        let Ok(glob) = Glob::new(component) else {
+            return Vec::new();
+        };
+        let matcher = glob.compile_matcher();
+        paths = paths
+            .iter()
+            .flat_map(|p| {
+                dir.read_dir(p)
+                    .into_iter()
+                    .filter(|(name, is_dir)| *is_dir && matcher.is_match(name))
+                    .map(move |(name, _)| p.join(name))
+            })
+            .collect();
+    }
+    paths.retain(|p| dir.is_dir(p));
+    paths
+}
+
+/// Detect frameworks from Cargo.toml.
+/// Port of RUST_CRATE_MAP from github.py. Only keys in the dependency tables
+/// count, so crate names mentioned in comments or descriptions are ignored.
+/// Workspace members are inspected too, and Cargo.lock fills in crates that
+/// are only pulled in transitively.
+pub fn detect_rust(dir: &(impl RepoFiles + ?Sized), frameworks: &mut HashMap<String, SignalEntry>) {
+    let all_deps = cargo_dependencies(dir);
+    for rule in rules("Cargo.toml") {
+        if let Some(version) = all_deps.get(rule.key) {
+            insert_signal(
+                frameworks,
+                SignalEntry::new(rule.name, rule.category, version.clone()),
+            );
+        }
+    }
+}
+
+/// Crates in the dependency tables of Cargo.toml and its workspace members,
+/// plus the rest of Cargo.lock.
+fn cargo_dependencies(dir: &(impl RepoFiles + ?Sized)) -> HashMap<String, Option<String>> {
+    let mut all_deps: HashMap<String, Option<String>> = HashMap::new();
+
+    if let Some(root) = read_toml(dir, Path::new("Cargo.toml")) {
+        collect_cargo_deps(&root, &mut all_deps);
+
+        if let Some(workspace) = root.get("workspace").and_then(|w| w.as_table()) {
+            if let Some(shared) = workspace.get("dependencies").and_then(|d| d.as_table()) {
+                for (dep, value) in shared {
+                    all_deps
+                        .entry(dep.clone())
+                        .or_insert_with(|| cargo_dep_version(value));
+                }
+            }
+
+            let patterns = |key: &str| -> Vec<String> {
+                workspace
+                    .get(key)
+                    .and_then(|m| m.as_array())
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+            let excluded: Vec<PathBuf> = patterns("exclude").iter().map(PathBuf::from).collect();
+            for member in patterns("members") {
+                for member_dir in expand_workspace_member(dir, &member) {
+                    if excluded.contains(&member_dir) {
+                        continue;
+                    }
+                    if let Some(manifest) = read_toml(dir, &member_dir.join("Cargo.toml")) {
+                        collect_cargo_deps(&manifest, &mut all_deps);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(lock) = read_toml(dir, Path::new("Cargo.lock")) {
+        if let Some(packages) = lock.get("package").and_then(|p| p.as_array()) {
+            for package in packages {
+                if let Some(name) = package.get("name").and_then(|n| n.as_str()) {
+                    let version = package.get("version").and_then(|v| v.as_str());
+                    all_deps
+                        .entry(name.to_string())
+                        .or_insert_with(|| version.map(String::from));
+                }
+            }
+        }
+    }
+    all_deps
+}
+
+/// Detect frameworks from Gemfile.
+pub fn detect_ruby(dir: &(impl RepoFiles + ?Sized), frameworks: &mut HashMap<String, SignalEntry>) {
+    let content = match dir.read_to_string(Path::new("Gemfile")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let lower = content.to_lowercase();
+
+    for rule in rules("Gemfile") {
+        if lower.contains(rule.key) {
+            insert_signal(frameworks, SignalEntry::new(rule.name, rule.category, None));
+        }
+    }
+}
+
+/// Detect frameworks from the resolved gems in Gemfile.lock, including
+/// transitive ones. Only the top-level `name (version)` lines of the
+/// `GEM` section's `specs:` are read; their indented dependency lines are
+/// constraints, not installed gems.
+pub fn detect_gemfile_lock(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("Gemfile.lock")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    let mut section = "";
+    let mut in_specs = false;
+    for line in content.lines() {
+        if !line.starts_with(' ') {
+            section = line.trim();
+            in_specs = false;
+            continue;
+        }
+        if section != "GEM" {
+            continue;
+        }
+        if line.trim() == "specs:" {
+            in_specs = true;
+            continue;
+        }
+        let Some(spec) = line.strip_prefix("    ").filter(|s| !s.starts_with(' ')) else {
+            continue;
+        };
+        if !in_specs {
+            continue;
+        }
+        let (gem, version) = match spec.split_once(" (") {
+            Some((gem, rest)) => (gem, rest.strip_suffix(')')),
+            None => (spec, None),
+        };
+        for rule in rules("Gemfile") {
+            if gem.eq_ignore_ascii_case(rule.key) {
+                insert_signal(
+                    frameworks,
+                    SignalEntry::new(rule.name, rule.category, version.map(String::from)),
+                );
+            }
+        }
+    }
+}
+
+fn swift_lookup(package: &str) -> Option<(&'static str, &'static str)> {
+    let key = package.to_lowercase();
+    rules("Package.swift")
+        .find(|rule| rule.key == key)
+        .map(|rule| (rule.name, rule.category))
+}
+
+/// First double-quoted string after `key` in `text`.
+fn quoted_after<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let rest = &text[text.find(key)? + key.len()..];
+    let start = rest.find('"')? + 1;
+    let end = rest[start..].find('"')?;
+    Some(&rest[start..start + end])
+}
+
+/// Detect frameworks from `.package(url: ...)` entries in Package.swift.
+pub fn detect_swift(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("Package.swift")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    for decl in content.split(".package(").skip(1) {
+        let Some(url) = quoted_after(decl, "url:") else {
+            continue;
+        };
+        let repo = url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(url)
+            .trim_end_matches(".git");
+        if let Some((name, category)) = swift_lookup(repo) {
+            let version = quoted_after(decl, "from:")
+                .or_else(|| quoted_after(decl, "exact:"))
+                .map(str::to_string);
+            insert_signal(frameworks, SignalEntry::new(name, category, version));
+        }
+    }
+}
+
+/// Detect frameworks from `pod '<name>', '<version>'` lines in a Podfile.
+pub fn detect_cocoapods(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("Podfile")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    for line in content.lines() {
+        let Some(args) = line.trim().strip_prefix("pod ") else {
+            continue;
+        };
+        let mut quoted = args
+            .split(',')
+            .map(|part| part.trim().trim_matches(|c| c == '\'' || c == '"'));
+        let Some(pod) = quoted.next() else {
+            continue;
+        };
+        // Subspecs ("Firebase/Auth") count as their parent pod.
+        let pod = pod.split('/').next().unwrap_or(pod);
+        if let Some((name, category)) = swift_lookup(pod) {
+            let version = quoted
+                .next()
+                .filter(|v| !v.is_empty() && !v.contains(':'))
+                .map(str::to_string);
+            insert_signal(frameworks, SignalEntry::new(name, category, version));
+        }
+    }
+}
+
+/// Detect frameworks from the `deps` tuples (`{:phoenix, "~> 1.7"}`) in mix.exs.
+pub fn detect_mix(dir: &(impl RepoFiles + ?Sized), frameworks: &mut HashMap<String, SignalEntry>) {
+    let content = match dir.read_to_string(Path::new("mix.exs")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    for tuple in content.split("{:").skip(1) {
+        let atom_end = tuple
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(tuple.len());
+        let atom = &tuple[..atom_end];
+        let Some(rule) = rules("mix.exs").find(|rule| rule.key == atom) else {
+            continue;
+        };
+        // The requirement is the string right after the atom, if there is one.
+        let version = tuple[atom_end..]
+            .trim_start()
+            .strip_prefix(',')
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('"'))
+            .and_then(|rest| rest.split('"').next())
+            .map(str::to_string);
+        insert_signal(
+            frameworks,
+            SignalEntry::new(rule.name, rule.category, version),
+        );
+    }
+}
+
+/// Detect frameworks from the `dependencies` and `dev_dependencies` maps in
+/// a Dart/Flutter `pubspec.yaml`.
+pub fn detect_pubspec(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("pubspec.yaml")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let parsed: serde_yaml::Value = match serde_yaml::from_str(&content) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+
+    for key in &["dependencies", "dev_dependencies"] {
+        let Some(deps) = parsed.get(*key).and_then(|d| d.as_mapping()) else {
+            continue;
+        };
+        for (dep, value) in deps {
+            let Some(dep) = dep.as_str() else {
+                continue;
+            };
+            if let Some(rule) = rules("pubspec.yaml").find(|rule| rule.key == dep) {
+                // `dio: ^5.4.0`, or a map like `flutter: {sdk: flutter}`.
+                let version = value.as_str().map(str::to_string);
+                insert_signal(
+                    frameworks,
+                    SignalEntry::new(rule.name, rule.category, version),
+                );
+            }
+        }
+    }
+}
+
+/// Split a Haskell dependency like `servant-server ^>=0.20` into its
+/// package and (non-empty) version constraint.
+fn split_haskell_dep(dep: &str) -> Option<(&str, Option<String>)> {
+    let dep = dep.trim();
+    let end = dep
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(dep.len());
+    let package = &dep[..end];
+    if package.is_empty() {
+        return None;
+    }
+    let version = Some(dep[end..].trim())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string);
+    Some((package, version))
+}
+
+/// The dependencies listed in every `build-depends:` field of a `.cabal`
+/// file, including continuation lines indented below the field.
+fn cabal_build_depends(content: &str) -> Vec<&str> {
+    let mut deps = Vec::new();
+    let mut field_indent: Option<usize> = None;
+    for line in content.lines() {
+        let text = line.trim_start();
+        let indent = line.len() - text.len();
+        if text.is_empty() || text.starts_with("--") {
+            continue;
+        }
+        if let Some(field) = field_indent {
+            if indent > field {
+                deps.extend(text.split(','));
+                continue;
+            }
+            field_indent = None;
+        }
+        if let Some((key, rest)) = text.split_once(':') {
+            if key.trim().eq_ignore_ascii_case("build-depends") {
+                field_indent = Some(indent);
+                deps.extend(rest.split(','));
+            }
+        }
+    }
+    deps
+}
+
+/// Detect frameworks from `build-depends:` in the root `*.cabal` files and
+/// from the `dependencies:` lists of an hpack `package.yaml` (top level and
+/// per library, executable and test suite).
+pub fn detect_haskell(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let mut deps: Vec<String> = Vec::new();
+    for (name, is_dir) in dir.read_dir(Path::new("")) {
+        if is_dir || !name.ends_with(".cabal") {
+            continue;
+        }
+        if let Ok(content) = dir.read_to_string(Path::new(&name)) {
+            deps.extend(
+                cabal_build_depends(&content)
+                    .into_iter()
+                    .map(str::to_string),
+            );
+        }
+    }
+
+    if let Some(parsed) = dir
+        .read_to_string(Path::new("package.yaml"))
+        .ok()
+        .and_then(|c| serde_yaml::from_str::<serde_yaml::Value>(&c).ok())
+    {
+        let mut lists = vec![parsed.get("dependencies")];
+        lists.push(parsed.get("library").and_then(|l| l.get("dependencies")));
+        for section in ["executables", "tests", "benchmarks"] {
+            if let Some(components) = parsed.get(section).and_then(|s| s.as_mapping()) {
+                lists.extend(components.values().map(|c| c.get("dependencies")));
+            }
+        }
+        for list in lists.into_iter().flatten() {
+            match list {
+                // - servant-server >= 0.19
+                serde_yaml::Value::Sequence(items) => {
+                    deps.extend(items.iter().filter_map(|i| i.as_str()).map(str::to_string));
+                }
+                // servant-server: ">= 0.19"
+                serde_yaml::Value::Mapping(map) => {
+                    for (package, version) in map {
+                        if let Some(package) = package.as_str() {
+                            let version = version.as_str().unwrap_or_default();
+                            deps.push(format!("{package} {version}"));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for dep in &deps {
+        let Some((package, version)) = split_haskell_dep(dep) else {
+            continue;
+        };
+        if let Some(rule) = rules("*.cabal").find(|rule| rule.key == package) {
+            insert_signal(
+                frameworks,
+                SignalEntry::new(rule.name, rule.category, version),
+            );
+        }
+    }
+}
+
+/// The fields of the `.dependencies` struct in a `build.zig.zon`, with the
+/// `.url` of each when it has one. ZON is Zig syntax rather than a data
+/// format, so this tracks braces line by line instead of parsing it.
+fn zon_dependencies(content: &str) -> Vec<(String, Option<String>)> {
+    let mut deps: Vec<(String, Option<String>)> = Vec::new();
+    // Brace depth inside `.dependencies`; 1 is directly inside it.
+    let mut level: Option<i32> = None;
+    for line in content.lines() {
+        let text = line.trim();
+        if text.starts_with("//") {
+            continue;
+        }
+        let delta = text.matches('{').count() as i32 - text.matches('}').count() as i32;
+        let Some(depth) = level else {
+            if text.starts_with(".dependencies") {
+                if delta <= 0 {
+                    break;
+                }
+                level = Some(delta);
+            }
+            continue;
+        };
+
+        if depth == 1 && text.starts_with('.') && text.contains('=') {
+            let field = &text[1..];
+            let name = match field.strip_prefix("@\"") {
+                Some(quoted) => quoted.split('"').next().unwrap_or(""),
+                None => field
+                    .split(|c: char| c.is_whitespace() || c == '=')
+                    .next()
+                    .unwrap_or(""),
+            };
+            if !name.is_empty() {
+                deps.push((name.to_string(), None));
+            }
+        }
+        if let (Some(url), Some(dep)) = (quoted_after(text, ".url"), deps.last_mut()) {
+            dep.1 = Some(url.to_string());
+        }
+
+        if depth + delta <= 0 {
+            break;
+        }
+        level = Some(depth + delta);
+    }
+    deps
+}
+
+/// The release in a dependency tarball URL such as
+/// `.../archive/refs/tags/v0.9.1.tar.gz`. Commit-pinned `git+https://`
+/// URLs have none.
+fn zon_url_version(url: &str) -> Option<String> {
+    let file = url.rsplit('/').next()?;
+    let stem = [".tar.gz", ".tgz", ".tar.xz", ".tar.zst", ".zip"]
+        .iter()
+        .find_map(|ext| file.strip_suffix(ext))?;
+    let number = stem.strip_prefix('v').unwrap_or(stem);
+    let is_version = number.starts_with(|c: char| c.is_ascii_digit())
+        && number.chars().all(|c| c.is_ascii_digit() || c == '.');
+    is_version.then(|| stem.to_string())
+}
+
+/// Detect frameworks from the `.dependencies` of `build.zig.zon`, taking
+/// versions from release tarball URLs.
+pub fn detect_zig(dir: &(impl RepoFiles + ?Sized), frameworks: &mut HashMap<String, SignalEntry>) {
+    let Ok(content) = dir.read_to_string(Path::new("build.zig.zon")) else {
+        return;
+    };
+
+    for (package, url) in zon_dependencies(&content) {
+        if let Some(rule) = rules("build.zig.zon").find(|rule| rule.key == package) {
+            let version = url.as_deref().and_then(zon_url_version);
+            insert_signal(
+                frameworks,
+                SignalEntry::new(rule.name, rule.category, version),
+            );
+        }
+    }
+}
+
+/// Detect frameworks from go.mod.
+/// Port of GO_MODULE_MAP from github.py.
+pub fn detect_go(dir: &(impl RepoFiles + ?Sized), frameworks: &mut HashMap<String, SignalEntry>) {
+    let content = match dir.read_to_string(Path::new("go.mod")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    for rule in rules("go.mod") {
+        if content.contains(rule.key) {
+            let version = go_module_version(&content, rule.key);
+            insert_signal(
+                frameworks,
+                SignalEntry::new(rule.name, rule.category, version),
+            );
+        }
+    }
+}
+
+/// Detect frameworks from composer.json.
+pub fn detect_php(dir: &(impl RepoFiles + ?Sized), frameworks: &mut HashMap<String, SignalEntry>) {
+    let all_deps = composer_dependencies(dir);
+    for rule in rules("composer.json") {
+        if let Some(version) = all_deps.get(rule.key) {
+            insert_signal(
+                frameworks,
+                SignalEntry::new(rule.name, rule.category, version.clone()),
+            );
+        }
+    }
+}
+
+/// Packages composer.json requires, with their constraints.
+fn composer_dependencies(dir: &(impl RepoFiles + ?Sized)) -> HashMap<String, Option<String>> {
+    let parsed: Option<serde_json::Value> = dir
+        .read_to_string(Path::new("composer.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok());
+
+    let mut all_deps: HashMap<String, Option<String>> = HashMap::new();
+    for key in &["require", "require-dev"] {
+        if let Some(obj) = parsed.as_ref().and_then(|p| p.get(key)?.as_object()) {
+            for (dep, version) in obj {
+                all_deps
+                    .entry(dep.clone())
+                    .or_insert_with(|| version.as_str().map(String::from));
+            }
+        }
+    }
+    all_deps
+}
+
+/// Detect frameworks from the installed packages in composer.lock,
+/// including transitive ones, with their resolved versions.
+pub fn detect_composer_lock(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("composer.lock")) {
         Ok(c) => c,
         Err(_) => return,
     };
@@ -15,361 +863,2006 @@ pub fn detect_npm(dir: &Path, frameworks: &mut HashMap<String, String>) {
         Err(_) => return,
     };
 
-    let mut all_deps: Vec<String> = Vec::new();
-    for key in &["dependencies", "devDependencies"] {
-        if let Some(obj) = parsed.get(key).and_then(|v| v.as_object()) {
-            all_deps.extend(obj.keys().cloned());
-        }
-    }
-
-    const NPM_MAP: &[(&str, &str, &str)] = &[
-        ("react", "React", "framework"),
-        ("react-native", "React Native", "framework"),
-        ("next", "Next.js", "framework"),
-        ("vue", "Vue", "framework"),
-        ("nuxt", "Nuxt", "framework"),
-        ("svelte", "Svelte", "framework"),
-        ("@angular/core", "Angular", "framework"),
-        ("express", "Express", "framework"),
-        ("fastify", "Fastify", "framework"),
-        ("gatsby", "Gatsby", "framework"),
-        ("remix", "Remix", "framework"),
-        ("@nestjs/core", "NestJS", "framework"),
-        ("koa", "Koa", "framework"),
-        ("tailwindcss", "Tailwind CSS", "framework"),
-        ("prisma", "Prisma", "tool"),
-        ("mongoose", "Mongoose", "tool"),
-        ("sequelize", "Sequelize", "tool"),
-        ("jest", "Jest", "tool"),
-        ("mocha", "Mocha", "tool"),
-        ("webpack", "Webpack", "tool"),
-        ("vite", "Vite", "tool"),
-        ("typescript", "TypeScript", "language"),
-        ("three", "Three.js", "framework"),
-        ("electron", "Electron", "framework"),
-        ("socket.io", "Socket.IO", "framework"),
-        ("graphql", "GraphQL", "tool"),
-        ("@apollo/client", "Apollo", "framework"),
-        ("redis", "Redis", "tool"),
-        ("pg", "PostgreSQL", "tool"),
-        ("mongodb", "MongoDB", "tool"),
-        ("supabase", "Supabase", "tool"),
-        ("firebase", "Firebase", "tool"),
-    ];
+    for key in &["packages", "packages-dev"] {
+        let Some(packages) = parsed.get(key).and_then(|v| v.as_array()) else {
+            continue;
+        };
+        for package in packages {
+            let Some(dep) = package.get("name").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let version = package.get("version").and_then(|v| v.as_str());
+            for rule in rules("composer.json") {
+                if dep == rule.key {
+                    insert_signal(
+                        frameworks,
+                        SignalEntry::new(rule.name, rule.category, version.map(String::from)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Detect frameworks from pyproject.toml dependencies.
+/// Fallback for Python projects that don't use requirements.txt. Reads PEP 621
+/// `[project]` dependencies and Poetry's `[tool.poetry]` dependency tables.
+pub fn detect_pyproject(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let parsed = match read_toml(dir, Path::new("pyproject.toml")) {
+        Some(t) => t,
+        None => return,
+    };
+    let mut all_deps: HashMap<String, Option<String>> = HashMap::new();
+
+    if let Some(project) = parsed.get("project") {
+        let optional = project
+            .get("optional-dependencies")
+            .and_then(|o| o.as_table())
+            .into_iter()
+            .flat_map(|t| t.values());
+        for list in project.get("dependencies").into_iter().chain(optional) {
+            for req in list
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|r| r.as_str())
+            {
+                let (dep, version) = split_requirement(req);
+                all_deps.entry(dep).or_insert(version);
+            }
+        }
+    }
+
+    if let Some(poetry) = parsed.get("tool").and_then(|t| t.get("poetry")) {
+        let groups = poetry
+            .get("group")
+            .and_then(|g| g.as_table())
+            .into_iter()
+            .flat_map(|t| t.values())
+            .filter_map(|g| g.get("dependencies"));
+        let tables = ["dependencies", "dev-dependencies"]
+            .iter()
+            .filter_map(|key| poetry.get(*key))
+            .chain(groups);
+        for table in tables.filter_map(|t| t.as_table()) {
+            for (dep, value) in table {
+                all_deps
+                    .entry(dep.to_lowercase())
+                    .or_insert_with(|| toml_dep_version(value));
+            }
+        }
+    }
+
+    match_python_deps(&all_deps, frameworks);
+}
+
+/// Detect frameworks from a Pipenv `Pipfile`.
+pub fn detect_pipfile(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let parsed = match read_toml(dir, Path::new("Pipfile")) {
+        Some(t) => t,
+        None => return,
+    };
+
+    let mut all_deps: HashMap<String, Option<String>> = HashMap::new();
+    for key in &["packages", "dev-packages"] {
+        if let Some(table) = parsed.get(*key).and_then(|v| v.as_table()) {
+            for (dep, value) in table {
+                all_deps
+                    .entry(dep.to_lowercase())
+                    .or_insert_with(|| toml_dep_version(value));
+            }
+        }
+    }
+
+    match_python_deps(&all_deps, frameworks);
+}
+
+/// Detect frameworks from a Conda `environment.yml`, including packages in
+/// its nested `pip:` list.
+pub fn detect_conda(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("environment.yml")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let parsed: serde_yaml::Value = match serde_yaml::from_str(&content) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+    let Some(deps) = parsed.get("dependencies").and_then(|d| d.as_sequence()) else {
+        return;
+    };
+
+    let mut all_deps: HashMap<String, Option<String>> = HashMap::new();
+    let mut add = |spec: &str| {
+        // Conda specs may carry a channel prefix: `conda-forge::numpy=1.26`.
+        let spec = spec.rsplit("::").next().unwrap_or(spec);
+        let (name, version) = split_requirement(spec);
+        if !name.is_empty() {
+            all_deps.entry(name).or_insert(version);
+        }
+    };
+    for dep in deps {
+        if let Some(spec) = dep.as_str() {
+            add(spec);
+        } else if let Some(pip) = dep.get("pip").and_then(|p| p.as_sequence()) {
+            for spec in pip.iter().filter_map(|p| p.as_str()) {
+                add(spec);
+            }
+        }
+    }
+
+    match_python_deps(&all_deps, frameworks);
+}
+
+/// Concatenate the bodies of every `name { ... }` block in a Gradle script.
+fn gradle_blocks(content: &str, name: &str) -> String {
+    let mut bodies = String::new();
+    let mut rest = content;
+    while let Some(pos) = rest.find(name) {
+        let after = rest[pos + name.len()..].trim_start();
+        rest = &rest[pos + name.len()..];
+        let Some(body) = after.strip_prefix('{') else {
+            continue;
+        };
+        let mut depth = 1;
+        let end = body
+            .char_indices()
+            .find_map(|(i, c)| {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                (depth == 0).then_some(i)
+            })
+            .unwrap_or(body.len());
+        bodies.push_str(&body[..end]);
+        bodies.push('\n');
+    }
+    bodies
+}
+
+/// Detect frameworks from build.gradle / build.gradle.kts.
+/// Only `dependencies { }` and `plugins { }` blocks are inspected, since
+/// Android and Spring Boot are usually applied as plugins.
+pub fn detect_gradle(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    for file in &["build.gradle", "build.gradle.kts"] {
+        let content = match dir.read_to_string(Path::new(file)) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        let declared =
+            gradle_blocks(&content, "dependencies") + &gradle_blocks(&content, "plugins");
+
+        for rule in rules("build.gradle") {
+            if declared.contains(rule.key) {
+                insert_signal(frameworks, SignalEntry::new(rule.name, rule.category, None));
+            }
+        }
+    }
+}
+
+/// Detect frameworks from pom.xml `<dependency>` and `<parent>` coordinates.
+pub fn detect_maven(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let content = match dir.read_to_string(Path::new("pom.xml")) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let doc = match roxmltree::Document::parse(&content) {
+        Ok(d) => d,
+        Err(_) => return,
+    };
+
+    let child_text = |node: roxmltree::Node, tag: &str| -> Option<String> {
+        node.children()
+            .find(|c| c.has_tag_name(tag))
+            .and_then(|c| c.text())
+            .map(|t| t.trim().to_string())
+    };
+
+    for node in doc.descendants() {
+        let is_dependency = node.has_tag_name("dependency")
+            && node
+                .parent()
+                .is_some_and(|p| p.has_tag_name("dependencies"));
+        if !is_dependency && !node.has_tag_name("parent") {
+            continue;
+        }
+        let group = child_text(node, "groupId").unwrap_or_default();
+        let artifact = child_text(node, "artifactId").unwrap_or_default();
+        // Property placeholders like ${spring.version} carry no useful version.
+        let version = child_text(node, "version").filter(|v| !v.starts_with("${"));
+
+        for rule in rules("pom.xml") {
+            let (group_prefix, artifact_prefix) =
+                rule.key.split_once(':').unwrap_or((rule.key, ""));
+            if group.starts_with(group_prefix) && artifact.starts_with(artifact_prefix) {
+                insert_signal(
+                    frameworks,
+                    SignalEntry::new(rule.name, rule.category, version.clone()),
+                );
+            }
+        }
+    }
+}
+
+/// Directories never searched for project files: build output and
+/// dependency caches.
+const PROJECT_SEARCH_SKIP: &[&str] = &["bin", "obj", "node_modules", "target", "vendor"];
+
+/// Collect files under `relative` (at most `max_depth` directories down)
+/// whose extension is one of `extensions`. Hidden directories are skipped.
+fn find_by_extension(
+    dir: &(impl RepoFiles + ?Sized),
+    relative: &Path,
+    extensions: &[&str],
+    max_depth: usize,
+    found: &mut Vec<PathBuf>,
+) {
+    for (name, is_dir) in dir.read_dir(relative) {
+        let path = relative.join(&name);
+        if is_dir {
+            if max_depth > 0
+                && !name.starts_with('.')
+                && !PROJECT_SEARCH_SKIP.contains(&name.as_str())
+            {
+                find_by_extension(dir, &path, extensions, max_depth - 1, found);
+            }
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.contains(&e))
+        {
+            found.push(path);
+        }
+    }
+}
+
+/// Detect frameworks from `<PackageReference>` entries in `.csproj`/`.fsproj`
+/// files at the root or up to three directories down (e.g. `src/Api/Api.csproj`).
+pub fn detect_dotnet(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let mut projects = Vec::new();
+    find_by_extension(dir, Path::new(""), &["csproj", "fsproj"], 3, &mut projects);
+    projects.sort();
+
+    for project in projects {
+        let Ok(content) = dir.read_to_string(&project) else {
+            continue;
+        };
+        let Ok(doc) = roxmltree::Document::parse(&content) else {
+            continue;
+        };
+
+        // Web SDK projects are ASP.NET Core even without explicit packages.
+        if doc.root_element().attribute("Sdk") == Some("Microsoft.NET.Sdk.Web") {
+            insert_signal(
+                frameworks,
+                SignalEntry::new("ASP.NET Core", "framework", None),
+            );
+        }
+
+        for node in doc
+            .descendants()
+            .filter(|n| n.has_tag_name("PackageReference"))
+        {
+            let Some(id) = node.attribute("Include") else {
+                continue;
+            };
+            let id = id.to_lowercase();
+            let version = node.attribute("Version").map(str::to_string).or_else(|| {
+                node.children()
+                    .find(|c| c.has_tag_name("Version"))
+                    .and_then(|c| c.text())
+                    .map(|t| t.trim().to_string())
+            });
+            for rule in rules("*.csproj") {
+                if id.starts_with(rule.key) {
+                    insert_signal(
+                        frameworks,
+                        SignalEntry::new(rule.name, rule.category, version.clone()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Detect backing services from the `services.*.image` fields of a Compose
+/// file. Databases and brokers are tools; proxies are infrastructure.
+pub fn detect_compose_services(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+    infra: &mut HashMap<String, SignalEntry>,
+) {
+    const COMPOSE_FILES: &[&str] = &[
+        "docker-compose.yml",
+        "docker-compose.yaml",
+        "compose.yml",
+        "compose.yaml",
+    ];
+
+    for file in COMPOSE_FILES {
+        let content = match dir.read_to_string(Path::new(file)) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        let parsed: serde_yaml::Value = match serde_yaml::from_str(&content) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let Some(services) = parsed.get("services").and_then(|s| s.as_mapping()) else {
+            continue;
+        };
+
+        for service in services.values() {
+            let Some(image) = service.get("image").and_then(|i| i.as_str()) else {
+                continue;
+            };
+            // "docker.io/library/postgres:16-alpine" → ("postgres", Some("16-alpine"))
+            let image = image.split('@').next().unwrap_or(image);
+            let last = image.rsplit('/').next().unwrap_or(image);
+            let (base, tag) = match last.split_once(':') {
+                Some((base, tag)) => (base, Some(tag)),
+                None => (last, None),
+            };
+            let version = tag.filter(|t| *t != "latest").map(str::to_string);
+
+            for rule in rules("compose.yaml") {
+                if base == rule.key {
+                    let target = if rule.category == "infrastructure" {
+                        &mut *infra
+                    } else {
+                        &mut *frameworks
+                    };
+                    insert_signal(
+                        target,
+                        SignalEntry::new(rule.name, rule.category, version.clone()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Detect base images and exposed ports from the root `Dockerfile`. Each
+/// `FROM` image (every stage of a multi-stage build) becomes a
+/// `Docker: <image>` signal with its tag as the version; when stages share
+/// an image, the last stage's tag wins, so the final runtime image is what
+/// is reported. `EXPOSE` ports become `Docker: port <n>` signals.
+pub fn detect_dockerfile_details(
+    dir: &(impl RepoFiles + ?Sized),
+    infra: &mut HashMap<String, SignalEntry>,
+) {
+    let Ok(content) = dir.read_to_string(Path::new("Dockerfile")) else {
+        return;
+    };
+
+    // Stage names (`FROM ... AS build`), which later stages may build on.
+    let mut stages: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let mut words = line.split_whitespace();
+        let Some(instruction) = words.next() else {
+            continue;
+        };
+        if instruction.eq_ignore_ascii_case("FROM") {
+            // FROM [--platform=...] image[:tag][@digest] [AS name]
+            let mut args = words.filter(|w| !w.starts_with("--"));
+            let Some(image) = args.next() else {
+                continue;
+            };
+            // Earlier stages, `scratch` and `${ARG}` images name no base.
+            let is_base = image != "scratch"
+                && !image.contains('$')
+                && !stages.contains(&image.to_lowercase());
+            if let (Some(as_kw), Some(name)) = (args.next(), args.next()) {
+                if as_kw.eq_ignore_ascii_case("AS") {
+                    stages.push(name.to_lowercase());
+                }
+            }
+            if !is_base {
+                continue;
+            }
+            // "docker.io/library/python:3.12" → ("python", Some("3.12"))
+            let image = image.split('@').next().unwrap_or(image);
+            let last = image.rsplit('/').next().unwrap_or(image);
+            let (base, tag) = match last.split_once(':') {
+                Some((base, tag)) => (base, Some(tag)),
+                None => (last, None),
+            };
+            let version = tag.filter(|t| *t != "latest").map(str::to_string);
+            insert_signal(
+                infra,
+                SignalEntry::new(&format!("Docker: {base}"), "infrastructure", version),
+            );
+        } else if instruction.eq_ignore_ascii_case("EXPOSE") {
+            for port in words {
+                // "8000/tcp" → "8000"
+                let port = port.split('/').next().unwrap_or(port);
+                if port.parse::<u16>().is_ok() {
+                    insert_signal(
+                        infra,
+                        SignalEntry::new(&format!("Docker: port {port}"), "infrastructure", None),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Detect cloud providers from `provider "<name>"` blocks and
+/// `required_providers` sources in Terraform files at the root or one
+/// directory down (e.g. `terraform/main.tf`).
+pub fn detect_terraform(dir: &(impl RepoFiles + ?Sized), infra: &mut HashMap<String, SignalEntry>) {
+    let mut files = Vec::new();
+    find_by_extension(dir, Path::new(""), &["tf"], 1, &mut files);
+    if files.is_empty() {
+        return;
+    }
+    insert_signal(infra, SignalEntry::new("Terraform", "infrastructure", None));
+
+    for file in files {
+        let Ok(content) = dir.read_to_string(&file) else {
+            continue;
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            // provider "aws" {   |   source = "hashicorp/aws"
+            let provider = if let Some(rest) = line.strip_prefix("provider") {
+                rest.trim_start()
+                    .strip_prefix('"')
+                    .and_then(|r| r.split('"').next())
+            } else if let Some(rest) = line.strip_prefix("source") {
+                rest.trim_start()
+                    .strip_prefix('=')
+                    .and_then(|r| r.trim().strip_prefix('"'))
+                    .and_then(|r| r.split('"').next())
+                    .and_then(|source| source.rsplit('/').next())
+            } else {
+                None
+            };
+            let Some(provider) = provider else {
+                continue;
+            };
+            if let Some(rule) = rules("*.tf").find(|rule| rule.key == provider) {
+                insert_signal(infra, SignalEntry::new(rule.name, rule.category, None));
+            }
+        }
+    }
+}
+
+/// Detect deploy targets from the actions GitHub Actions workflows use:
+/// `uses:` on steps (and on jobs calling reusable workflows) in every
+/// `.github/workflows/*.yml` (or `.yaml`).
+pub fn detect_workflow_actions(
+    dir: &(impl RepoFiles + ?Sized),
+    infra: &mut HashMap<String, SignalEntry>,
+) {
+    for content in workflow_files(dir) {
+        let parsed: serde_yaml::Value = match serde_yaml::from_str(&content) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let Some(jobs) = parsed.get("jobs").and_then(|j| j.as_mapping()) else {
+            continue;
+        };
+
+        let steps = jobs
+            .values()
+            .filter_map(|job| job.get("steps")?.as_sequence())
+            .flatten();
+        let uses = jobs
+            .values()
+            .chain(steps)
+            .filter_map(|item| item.get("uses")?.as_str());
+        for action in uses {
+            // "aws-actions/configure-aws-credentials@v4" → "aws-actions/configure-aws-credentials"
+            let action = action.split('@').next().unwrap_or(action).to_lowercase();
+            for rule in rules(".github/workflows") {
+                let key = rule.key;
+                let matched = if key.ends_with('/') {
+                    action.starts_with(key)
+                } else {
+                    action == key || action.starts_with(&format!("{key}/"))
+                };
+                if matched {
+                    insert_signal(infra, SignalEntry::new(rule.name, rule.category, None));
+                }
+            }
+        }
+    }
+}
+
+/// Contents of every `.github/workflows/*.yml` (or `.yaml`), by file name.
+fn workflow_files(dir: &(impl RepoFiles + ?Sized)) -> Vec<String> {
+    let workflows = Path::new(".github/workflows");
+    let mut names: Vec<String> = dir
+        .read_dir(workflows)
+        .into_iter()
+        .filter(|(name, is_dir)| !is_dir && (name.ends_with(".yml") || name.ends_with(".yaml")))
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names
+        .iter()
+        .filter_map(|name| dir.read_to_string(&workflows.join(name)).ok())
+        .collect()
+}
+
+/// Workflow keys naming the versions a job runs on → language or tool.
+const CI_VERSION_KEYS: &[(&str, &str, &str)] = &[
+    ("python-version", "Python", "language"),
+    ("node-version", "Node.js", "tool"),
+    ("go-version", "Go", "language"),
+    ("ruby-version", "Ruby", "language"),
+];
+
+/// Values of `key` in a workflow, in order: flow lists (`[3.9, "3.10"]`),
+/// block lists, `- key: value` matrix `include` entries and plain scalars.
+/// `${{ ... }}` expressions are left out. Read line by line rather than
+/// through YAML, which would turn an unquoted `3.10` into `3.1`.
+fn ci_versions(content: &str, key: &str) -> Vec<String> {
+    let mut versions: Vec<String> = Vec::new();
+    let mut push = |value: &str| {
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        if !value.is_empty() && !value.contains("${{") && !versions.iter().any(|v| v == value) {
+            versions.push(value.to_string());
+        }
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let item = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+        let Some(rest) = item.strip_prefix(key).and_then(|r| r.strip_prefix(':')) else {
+            continue;
+        };
+        let rest = rest.split(" #").next().unwrap_or(rest).trim();
+        if let Some(list) = rest.strip_prefix('[') {
+            list.split(']')
+                .next()
+                .unwrap_or(list)
+                .split(',')
+                .for_each(&mut push);
+        } else if rest.is_empty() {
+            let indent = line.len() - trimmed.len();
+            for next in &lines[i + 1..] {
+                let entry = next.trim_start();
+                if entry.is_empty() || entry.starts_with('#') {
+                    continue;
+                }
+                match entry.strip_prefix('-') {
+                    Some(value) if next.len() - entry.len() >= indent => {
+                        push(value.split(" #").next().unwrap_or(value))
+                    }
+                    _ => break,
+                }
+            }
+        } else {
+            push(rest);
+        }
+    }
+    versions
+}
+
+/// Record the language and runtime versions GitHub Actions workflows test
+/// against (`python-version`, `node-version`, `go-version`,
+/// `ruby-version`, usually a matrix) as the version of the matching
+/// signal, e.g. Python `3.10, 3.11, 3.12`.
+pub fn detect_workflow_versions(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let workflows = workflow_files(dir);
+    for &(key, name, category) in CI_VERSION_KEYS {
+        let mut versions: Vec<String> = Vec::new();
+        for version in workflows
+            .iter()
+            .flat_map(|content| ci_versions(content, key))
+        {
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+        if !versions.is_empty() {
+            insert_signal(
+                frameworks,
+                SignalEntry::new(name, category, Some(versions.join(", "))),
+            );
+        }
+    }
+}
+
+/// Build files whose rules or recipes are reported as build targets.
+const BUILD_FILES: &[&str] = &[
+    "Makefile",
+    "makefile",
+    "GNUmakefile",
+    "justfile",
+    "Justfile",
+];
+
+/// Target names from the root Makefile and justfile (`build:`, `test:`),
+/// in the order they first appear. Only plain names count, so `.PHONY`,
+/// pattern rules (`%.o:`) and variable targets (`$(OUT):`) are skipped,
+/// as are `:=` assignments and indented recipe lines. A justfile recipe's
+/// name is its first word, after a quiet `@`, so recipes with parameters
+/// (`deploy env:`, `test *args:`) count too.
+pub fn detect_make_targets(dir: &(impl RepoFiles + ?Sized)) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for name in BUILD_FILES {
+        let Ok(content) = dir.read_to_string(Path::new(name)) else {
+            continue;
+        };
+        let is_justfile = name.eq_ignore_ascii_case("justfile");
+        for line in content.lines() {
+            let Some((mut target, rest)) = line.split_once(':') else {
+                continue;
+            };
+            if is_justfile {
+                if line.starts_with(char::is_whitespace) {
+                    continue;
+                }
+                let recipe = target.strip_prefix('@').unwrap_or(target);
+                target = recipe.split_whitespace().next().unwrap_or_default();
+            }
+            let is_name = !target.is_empty()
+                && target
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+            if is_name && !rest.starts_with('=') && !targets.iter().any(|t| t == target) {
+                targets.push(target.to_string());
+            }
+        }
+    }
+    targets
+}
+
+/// Dependency names that contain a known key as a word but aren't the
+/// framework it names. Most arrive transitively through lockfiles:
+/// browserslist pulls in `electron-to-chromium`, webpack `jest-worker`.
+const FUZZY_BLOCKLIST: &[&str] = &[
+    "electron-to-chromium",
+    "jest-worker",
+    "koa-compose",
+    "next-tick",
+];
+
+/// Whether `key` appears in `dep` as a whole word, delimited by the start
+/// or end of the name or by a character that isn't a letter or digit:
+/// `react-dom` and `@myorg/react-wrapper` contain `react`, `preact` doesn't.
+fn contains_word(dep: &str, key: &str) -> bool {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric();
+    dep.match_indices(key).any(|(start, _)| {
+        let before = dep[..start].chars().next_back();
+        let after = dep[start + key.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+/// With `--fuzzy`, detect frameworks from npm, Cargo and Composer
+/// dependencies that contain a known name as a word (see [`contains_word`])
+/// instead of matching it exactly, such as forks and wrappers. Only names
+/// the exact readers missed are added, with `low` confidence and no version,
+/// since the dependency's version isn't the framework's.
+pub fn detect_fuzzy(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    let manifests = [
+        ("package.json", npm_dependencies(dir)),
+        ("Cargo.toml", cargo_dependencies(dir)),
+        ("composer.json", composer_dependencies(dir)),
+    ];
+    for (manifest, deps) in &manifests {
+        for rule in rules(manifest) {
+            if frameworks.contains_key(rule.name) {
+                continue;
+            }
+            let found = deps.keys().any(|dep| {
+                dep != rule.key
+                    && !FUZZY_BLOCKLIST.contains(&dep.as_str())
+                    && contains_word(dep, rule.key)
+            });
+            if found {
+                let entry = SignalEntry {
+                    confidence: Some("low".to_string()),
+                    ..SignalEntry::new(rule.name, rule.category, None)
+                };
+                insert_signal(frameworks, entry);
+            }
+        }
+    }
+}
+
+/// Apply custom rules loaded with `--rules`, matching each the way the
+/// built-in rules for its manifest are (see
+/// [`CUSTOM_RULE_MANIFESTS`](crate::rules::CUSTOM_RULE_MANIFESTS)).
+/// `infrastructure` rules add to `infra`, the rest to `frameworks`.
+pub fn detect_custom(
+    dir: &(impl RepoFiles + ?Sized),
+    custom: &[CustomRule],
+    frameworks: &mut HashMap<String, SignalEntry>,
+    infra: &mut HashMap<String, SignalEntry>,
+) {
+    if custom.is_empty() {
+        return;
+    }
+    let npm = npm_dependencies(dir);
+    let cargo = cargo_dependencies(dir);
+    let composer = composer_dependencies(dir);
+    let requirements = dir
+        .read_to_string(Path::new("requirements.txt"))
+        .map(|c| c.to_lowercase())
+        .unwrap_or_default();
+    let go_mod = dir.read_to_string(Path::new("go.mod")).unwrap_or_default();
+
+    for rule in custom {
+        let found = match rule.manifest.as_str() {
+            "package.json" => npm.get(&rule.key).cloned(),
+            "Cargo.toml" => cargo.get(&rule.key).cloned(),
+            "composer.json" => composer.get(&rule.key).cloned(),
+            "requirements.txt" => requirements
+                .contains(&rule.key.to_lowercase())
+                .then_some(None),
+            "go.mod" => go_mod
+                .contains(&rule.key)
+                .then(|| go_module_version(&go_mod, &rule.key)),
+            _ => None,
+        };
+        let Some(version) = found else {
+            continue;
+        };
+        let entry = SignalEntry::new(&rule.name, &rule.category, version);
+        if rule.category == "infrastructure" {
+            insert_signal(infra, entry);
+        } else {
+            insert_signal(frameworks, entry);
+        }
+    }
+}
+
+/// Version manager files pinning a runtime → the signal they version, with
+/// a prefix some tools write before the version. Later files for the same
+/// signal win.
+const RUNTIME_VERSION_FILES: &[(&str, &str, &str, &str)] = &[
+    (".nvmrc", "", "Node.js", "tool"),
+    (".node-version", "", "Node.js", "tool"),
+    (".python-version", "", "Python", "language"),
+    // Heroku's `python-3.12.1`.
+    ("runtime.txt", "python-", "Python", "language"),
+    (".go-version", "", "Go", "language"),
+    (".ruby-version", "ruby-", "Ruby", "language"),
+];
+
+/// The version pinned in a version manager file: its first line that isn't
+/// blank or a comment, without `prefix` or a leading `v`. Aliases such as
+/// `lts/*` or `system` pin no particular version and yield `None`.
+fn pinned_version(content: &str, prefix: &str) -> Option<String> {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('#'))?;
+    let version = line.strip_prefix(prefix).unwrap_or(line);
+    let version = version.strip_prefix('v').unwrap_or(version);
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_string())
+}
+
+/// Record the runtime versions a repo pins (`.nvmrc`, `.python-version`,
+/// `runtime.txt`, `.go-version`, `.ruby-version`, or the `go` directive of
+/// go.mod) as the version of the matching signal, e.g. Node.js `20.11.0`.
+/// A pin is what the project means to run on, so it replaces versions
+/// taken from CI workflows.
+pub fn detect_runtime_versions(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    // The go.mod directive is only a minimum; `.go-version` overrides it.
+    if let Ok(content) = dir.read_to_string(Path::new("go.mod")) {
+        let directive = content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("go "))
+            .and_then(|rest| pinned_version(rest, ""));
+        if let Some(version) = directive {
+            insert_signal(
+                frameworks,
+                SignalEntry::new("Go", "language", Some(version)),
+            );
+        }
+    }
+    for &(file, prefix, name, category) in RUNTIME_VERSION_FILES {
+        let version = dir
+            .read_to_string(Path::new(file))
+            .ok()
+            .and_then(|content| pinned_version(&content, prefix));
+        if let Some(version) = version {
+            insert_signal(frameworks, SignalEntry::new(name, category, Some(version)));
+        }
+    }
+}
+
+/// Run all dependency parsers for a given directory.
+pub fn detect_all(
+    dir: &(impl RepoFiles + ?Sized),
+    frameworks: &mut HashMap<String, SignalEntry>,
+    infra: &mut HashMap<String, SignalEntry>,
+) {
+    detect_npm(dir, frameworks);
+    detect_package_managers(dir, frameworks);
+    detect_python(dir, frameworks);
+    detect_pyproject(dir, frameworks);
+    detect_pipfile(dir, frameworks);
+    detect_conda(dir, frameworks);
+    detect_rust(dir, frameworks);
+    detect_ruby(dir, frameworks);
+    detect_gemfile_lock(dir, frameworks);
+    detect_go(dir, frameworks);
+    detect_php(dir, frameworks);
+    detect_composer_lock(dir, frameworks);
+    detect_gradle(dir, frameworks);
+    detect_maven(dir, frameworks);
+    detect_swift(dir, frameworks);
+    detect_cocoapods(dir, frameworks);
+    detect_mix(dir, frameworks);
+    detect_pubspec(dir, frameworks);
+    detect_haskell(dir, frameworks);
+    detect_zig(dir, frameworks);
+    detect_dotnet(dir, frameworks);
+    detect_compose_services(dir, frameworks, infra);
+    detect_terraform(dir, infra);
+    detect_workflow_actions(dir, infra);
+    detect_workflow_versions(dir, frameworks);
+    detect_runtime_versions(dir, frameworks);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_npm_react() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package.json"),
+            r#"{"dependencies": {"react": "^18.0.0", "express": "^4.0.0"}}"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert!(fw.contains_key("React"));
+        assert!(fw.contains_key("Express"));
+        assert_eq!(fw["React"].version.as_deref(), Some("^18.0.0"));
+    }
+
+    #[test]
+    fn test_detect_python_flask() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("requirements.txt"),
+            "flask==2.3.0\nrequests\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_python(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Flask"));
+        assert!(fw.contains_key("Requests"));
+    }
+
+    #[test]
+    fn test_detect_rust_actix() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Cargo.toml"),
+            "[dependencies]\nactix-web = \"4\"\ntokio = { version = \"1\" }\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_rust(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Actix Web"));
+        assert!(fw.contains_key("Tokio"));
+        assert_eq!(fw["Actix Web"].version.as_deref(), Some("4"));
+        assert_eq!(fw["Tokio"].version.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_detect_rust_ignores_description() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\ndescription = \"A tokio-powered server\"\n\n[dependencies]\nserde = \"1\"\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_rust(tmp.path(), &mut fw);
+        assert!(!fw.contains_key("Tokio"));
+        assert!(fw.contains_key("Serde"));
+    }
+
+    #[test]
+    fn test_detect_rust_exact_crate_names() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Cargo.toml"),
+            "# uses tokio\n[dependencies]\ntokio-console = \"0.1\"\n\n[dev-dependencies]\naxum = \"0.7\"\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_rust(tmp.path(), &mut fw);
+        assert!(!fw.contains_key("Tokio"));
+        assert!(fw.contains_key("Axum"));
+    }
+
+    #[test]
+    fn test_detect_go_gin() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("go.mod"),
+            "module example.com/app\nrequire github.com/gin-gonic/gin v1.9.0\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_go(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Gin"));
+        assert_eq!(fw["Gin"].version.as_deref(), Some("v1.9.0"));
+    }
+
+    #[test]
+    fn test_detect_missing_file() {
+        let tmp = TempDir::new().unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert!(fw.is_empty());
+    }
+
+    #[test]
+    fn test_detect_php_laravel() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("composer.json"),
+            r#"{"require": {"laravel/framework": "^10.0"}}"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_php(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Laravel"));
+        assert_eq!(fw["Laravel"].version.as_deref(), Some("^10.0"));
+    }
+
+    #[test]
+    fn test_detect_composer_lock_transitive() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("composer.lock"),
+            r#"{
+    "packages": [
+        {"name": "acme/starter-kit", "version": "1.2.0"},
+        {"name": "laravel/framework", "version": "v10.48.4"}
+    ],
+    "packages-dev": [
+        {"name": "slim/slim", "version": "4.12.0"}
+    ]
+}"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_composer_lock(tmp.path(), &mut fw);
+        assert_eq!(fw["Laravel"].version.as_deref(), Some("v10.48.4"));
+        assert_eq!(fw["Slim"].version.as_deref(), Some("4.12.0"));
+        assert_eq!(fw.len(), 2);
+    }
+
+    #[test]
+    fn test_detect_pyproject_flask() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pyproject.toml"),
+            "[project]\ndependencies = [\n  \"flask>=2.3.0\",\n  \"pydantic>=2.0\",\n]\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_pyproject(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Flask"));
+        assert!(fw.contains_key("Pydantic"));
+    }
+
+    #[test]
+    fn test_detect_ruby_rails() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Gemfile"),
+            "source 'https://rubygems.org'\ngem 'rails', '~> 7.0'\ngem 'rspec'\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_ruby(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Ruby on Rails"));
+        assert!(fw.contains_key("RSpec"));
+    }
+
+    #[test]
+    fn test_detect_gemfile_lock_specs() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Gemfile.lock"),
+            "GEM
+  remote: https://rubygems.org/
+  specs:
+    mustermann (3.0.0)
+    sinatra (3.2.0)
+      mustermann (~> 3.0)
+      rack-protection (= 3.2.0)
+    web-console (4.2.1)
+      rails (>= 6.0)
+
+PLATFORMS
+  ruby
+
+DEPENDENCIES
+  rspec
+  web-console
+",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_gemfile_lock(tmp.path(), &mut fw);
+        assert_eq!(fw["Sinatra"].version.as_deref(), Some("3.2.0"));
+        // Dependency constraints and the DEPENDENCIES list aren't installed gems.
+        assert!(!fw.contains_key("Ruby on Rails"));
+        assert!(!fw.contains_key("RSpec"));
+    }
+
+    #[test]
+    fn test_detect_gradle_kts_spring_boot() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("build.gradle.kts"),
+            r#"plugins {
+    kotlin("jvm") version "1.9.22"
+}
+
+// junit is configured in the test suite
+repositories {
+    mavenCentral()
+}
+
+dependencies {
+    implementation("org.springframework.boot:spring-boot-starter-web")
+    implementation("com.squareup.retrofit2:retrofit:2.9.0")
+}
+"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_gradle(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Spring Boot"));
+        assert!(fw.contains_key("Retrofit"));
+        assert!(!fw.contains_key("JUnit"));
+    }
+
+    #[test]
+    fn test_detect_gradle_android_plugin() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("build.gradle"),
+            "plugins {\n    id 'com.android.application'\n}\n\ndependencies {\n    testImplementation 'junit:junit:4.13.2'\n}\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_gradle(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Android"));
+        assert!(fw.contains_key("JUnit"));
+    }
+
+    #[test]
+    fn test_detect_maven_ignores_malformed_pom() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("pom.xml"), "<project><dependencies>").unwrap();
+        let mut fw = HashMap::new();
+        detect_maven(tmp.path(), &mut fw);
+        assert!(fw.is_empty());
+    }
+
+    #[test]
+    fn test_detect_npm_package_lock() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package.json"),
+            r#"{"dependencies": {"next": "^14.0.0"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("package-lock.json"),
+            r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": {"dependencies": {"next": "^14.0.0"}},
+    "node_modules/next": {"version": "14.1.0"},
+    "node_modules/react": {"version": "18.2.0"},
+    "node_modules/next/node_modules/express": {"version": "4.18.2"}
+  }
+}"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert_eq!(fw["Next.js"].version.as_deref(), Some("^14.0.0"));
+        assert_eq!(fw["React"].version.as_deref(), Some("18.2.0"));
+        assert!(!fw.contains_key("Express"));
+    }
+
+    #[test]
+    fn test_detect_npm_package_lock_v1() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package-lock.json"),
+            r#"{"lockfileVersion": 1, "dependencies": {"express": {"version": "4.18.2"}}}"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert_eq!(fw["Express"].version.as_deref(), Some("4.18.2"));
+    }
+
+    #[test]
+    fn test_detect_npm_yarn_lock() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("yarn.lock"),
+            r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+react@^18.2.0:
+  version "18.2.0"
+  resolved "https://registry.yarnpkg.com/react/-/react-18.2.0.tgz"
+
+"@angular/core@^17.0.0", "@angular/core@^17.1.0":
+  version "17.1.2"
+"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert_eq!(fw["React"].version.as_deref(), Some("18.2.0"));
+        assert_eq!(fw["Angular"].version.as_deref(), Some("17.1.2"));
+    }
+
+    #[test]
+    fn test_detect_npm_yarn_berry_lock() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("yarn.lock"),
+            "__metadata:\n  version: 8\n\n\"vue@npm:^3.4.0\":\n  version: 3.4.15\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert_eq!(fw["Vue"].version.as_deref(), Some("3.4.15"));
+    }
+
+    #[test]
+    fn test_detect_npm_pnpm_lock() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pnpm-lock.yaml"),
+            r#"lockfileVersion: '9.0'
+
+importers:
+  .:
+    dependencies:
+      svelte:
+        specifier: ^4.2.0
+        version: 4.2.9
+    devDependencies:
+      vite:
+        specifier: ^5.0.0
+        version: 5.0.12
+
+packages:
+  express@4.18.2:
+    resolution: {integrity: sha512-abc}
+"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert_eq!(fw["Svelte"].version.as_deref(), Some("4.2.9"));
+        assert_eq!(fw["Vite"].version.as_deref(), Some("5.0.12"));
+        assert!(!fw.contains_key("Express"));
+    }
+
+    #[test]
+    fn test_detect_npm_pnpm_lock_v6() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pnpm-lock.yaml"),
+            "lockfileVersion: '6.0'\n\ndependencies:\n  koa:\n    specifier: ^2.15.0\n    version: 2.15.0\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert_eq!(fw["Koa"].version.as_deref(), Some("2.15.0"));
+    }
+
+    #[test]
+    fn test_detect_custom() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package.json"),
+            r#"{"dependencies": {"@acme/ui": "^2.1.0"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("go.mod"),
+            "module example.com/app\n\nrequire acme.dev/deploy v0.4.0\n",
+        )
+        .unwrap();
+        let rule = |manifest: &str, key: &str, name: &str, category: &str| CustomRule {
+            manifest: manifest.to_string(),
+            key: key.to_string(),
+            name: name.to_string(),
+            category: category.to_string(),
+        };
+        let custom = vec![
+            rule("package.json", "@acme/ui", "Acme UI", "framework"),
+            rule("package.json", "@acme/missing", "Missing", "tool"),
+            rule("go.mod", "acme.dev/deploy", "Acme Deploy", "infrastructure"),
+        ];
+
+        let mut fw = HashMap::new();
+        let mut infra = HashMap::new();
+        detect_custom(tmp.path(), &custom, &mut fw, &mut infra);
+        assert_eq!(fw["Acme UI"].version.as_deref(), Some("^2.1.0"));
+        assert_eq!(fw.len(), 1);
+        assert_eq!(infra["Acme Deploy"].version.as_deref(), Some("v0.4.0"));
+    }
+
+    #[test]
+    fn test_detect_fuzzy() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package.json"),
+            r#"{"dependencies": {"react-dom": "^18.2.0", "@myorg/vue-wrapper": "1.0.0", "reactive-store": "1.0.0"},
+                "devDependencies": {"electron-to-chromium": "^1.4.0"}}"#,
+        )
+        .unwrap();
+
+        let mut fw = HashMap::new();
+        detect_npm(tmp.path(), &mut fw);
+        assert!(fw.is_empty());
+
+        detect_fuzzy(tmp.path(), &mut fw);
+        assert_eq!(fw["React"].confidence.as_deref(), Some("low"));
+        assert_eq!(fw["React"].version, None);
+        assert_eq!(fw["Vue"].confidence.as_deref(), Some("low"));
+        assert!(!fw.contains_key("Electron"));
+        assert_eq!(fw.len(), 2);
+
+        // An exact match elsewhere restores full confidence.
+        insert_signal(&mut fw, SignalEntry::new("React", "framework", None));
+        assert_eq!(fw["React"].confidence, None);
+    }
+
+    #[test]
+    fn test_contains_word() {
+        assert!(contains_word("react-dom", "react"));
+        assert!(contains_word("@myorg/react-wrapper", "react"));
+        assert!(contains_word("serde_json", "serde"));
+        assert!(!contains_word("preact", "react"));
+        assert!(!contains_word("reactive", "react"));
+    }
+
+    #[test]
+    fn test_detect_package_managers() {
+        for (lockfile, name) in [
+            ("package-lock.json", "npm"),
+            ("yarn.lock", "Yarn"),
+            ("pnpm-lock.yaml", "pnpm"),
+            ("bun.lockb", "Bun"),
+        ] {
+            let tmp = TempDir::new().unwrap();
+            fs::write(tmp.path().join(lockfile), "").unwrap();
+            let mut fw = HashMap::new();
+            detect_package_managers(tmp.path(), &mut fw);
+            assert_eq!(fw.len(), 1, "{lockfile}");
+            assert_eq!(fw[name].category, "tool");
+        }
+    }
+
+    #[test]
+    fn test_detect_multiple_package_managers() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("package-lock.json"), "{}").unwrap();
+        fs::write(tmp.path().join("yarn.lock"), "").unwrap();
+        fs::write(tmp.path().join("package.json"), "{}").unwrap();
+        let mut fw = HashMap::new();
+        detect_package_managers(tmp.path(), &mut fw);
+        let mut names: Vec<&str> = fw.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["Yarn", "npm"]);
+    }
+
+    #[test]
+    fn test_detect_rust_cargo_lock() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\n\n[dependencies]\naxum = \"0.7\"\n",
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("Cargo.lock"),
+            "version = 3\n\n[[package]]\nname = \"axum\"\nversion = \"0.7.4\"\n\n[[package]]\nname = \"tokio\"\nversion = \"1.36.0\"\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_rust(tmp.path(), &mut fw);
+        assert_eq!(fw["Axum"].version.as_deref(), Some("0.7"));
+        assert_eq!(fw["Tokio"].version.as_deref(), Some("1.36.0"));
+    }
+
+    #[test]
+    fn test_detect_rust_workspace_exclude() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/legacy\"]\n",
+        )
+        .unwrap();
+        for (name, dep) in [("web", "rocket"), ("legacy", "diesel")] {
+            let crate_dir = tmp.path().join("crates").join(name);
+            fs::create_dir_all(&crate_dir).unwrap();
+            fs::write(
+                crate_dir.join("Cargo.toml"),
+                format!("[package]\nname = \"{name}\"\n\n[dependencies]\n{dep} = \"1\"\n"),
+            )
+            .unwrap();
+        }
+        let mut fw = HashMap::new();
+        detect_rust(tmp.path(), &mut fw);
+        assert!(fw.contains_key("Rocket"));
+        assert!(!fw.contains_key("Diesel"));
+    }
+
+    #[test]
+    fn test_split_requirement() {
+        assert_eq!(
+            split_requirement("Flask[async]>=2.3; python_version > '3.8'"),
+            ("flask".to_string(), Some(">=2.3".to_string()))
+        );
+        assert_eq!(split_requirement("numpy"), ("numpy".to_string(), None));
+    }
 
-    for &(dep, name, category) in NPM_MAP {
-        if all_deps.iter().any(|d| d == dep) {
-            frameworks.insert(name.to_string(), category.to_string());
-        }
+    #[test]
+    fn test_detect_pyproject_ignores_description() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pyproject.toml"),
+            "[project]\nname = \"app\"\ndescription = \"Flask-like toy server\"\ndependencies = [\"fastapi>=0.110\"]\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_pyproject(tmp.path(), &mut fw);
+        assert!(!fw.contains_key("Flask"));
+        assert_eq!(fw["FastAPI"].version.as_deref(), Some(">=0.110"));
     }
-}
 
-/// Detect frameworks from requirements.txt.
-/// Port of PYTHON_FRAMEWORK_MAP from github.py.
-pub fn detect_python(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    let path = dir.join("requirements.txt");
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-    let lower = content.to_lowercase();
+    #[test]
+    fn test_detect_poetry_fastapi() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pyproject.toml"),
+            r#"[tool.poetry]
+name = "api"
+version = "0.1.0"
 
-    const PYTHON_MAP: &[(&str, &str, &str)] = &[
-        ("django", "Django", "framework"),
-        ("flask", "Flask", "framework"),
-        ("fastapi", "FastAPI", "framework"),
-        ("tornado", "Tornado", "framework"),
-        ("celery", "Celery", "tool"),
-        ("sqlalchemy", "SQLAlchemy", "tool"),
-        ("pandas", "pandas", "framework"),
-        ("numpy", "NumPy", "framework"),
-        ("scipy", "SciPy", "framework"),
-        ("scikit-learn", "scikit-learn", "framework"),
-        ("tensorflow", "TensorFlow", "framework"),
-        ("torch", "PyTorch", "framework"),
-        ("pytest", "pytest", "tool"),
-        ("pydantic", "Pydantic", "tool"),
-        ("requests", "Requests", "tool"),
-        ("boto3", "AWS SDK", "tool"),
-        ("redis", "Redis", "tool"),
-        ("psycopg2", "PostgreSQL", "tool"),
-    ];
+[tool.poetry.dependencies]
+python = "^3.11"
+fastapi = "^0.110.0"
+SQLAlchemy = { version = "^2.0", extras = ["asyncio"] }
 
-    for &(key, name, category) in PYTHON_MAP {
-        if lower.contains(key) {
-            frameworks.insert(name.to_string(), category.to_string());
-        }
+[tool.poetry.group.dev.dependencies]
+pytest = "^8.0"
+"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_pyproject(tmp.path(), &mut fw);
+        assert_eq!(fw["FastAPI"].version.as_deref(), Some("^0.110.0"));
+        assert_eq!(fw["SQLAlchemy"].version.as_deref(), Some("^2.0"));
+        assert!(fw.contains_key("pytest"));
     }
-}
-
-/// Detect frameworks from Cargo.toml.
-/// Port of RUST_CRATE_MAP from github.py.
-pub fn detect_rust(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    let path = dir.join("Cargo.toml");
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-    let lower = content.to_lowercase();
 
-    const RUST_MAP: &[(&str, &str, &str)] = &[
-        ("actix-web", "Actix Web", "framework"),
-        ("axum", "Axum", "framework"),
-        ("rocket", "Rocket", "framework"),
-        ("tokio", "Tokio", "tool"),
-        ("serde", "Serde", "tool"),
-        ("diesel", "Diesel", "tool"),
-        ("sqlx", "SQLx", "tool"),
-        ("leptos", "Leptos", "framework"),
-        ("yew", "Yew", "framework"),
-        ("tauri", "Tauri", "framework"),
-        ("wasm-bindgen", "WebAssembly", "tool"),
-    ];
+    #[test]
+    fn test_detect_poetry_matches_exact_names() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pyproject.toml"),
+            r#"[tool.poetry.dependencies]
+djangorestframework-stubs = "^3.14"
+pytest-cov = "^4.1"
+Flask_SQLAlchemy = "^3.1"
 
-    for &(key, name, category) in RUST_MAP {
-        if lower.contains(key) {
-            frameworks.insert(name.to_string(), category.to_string());
-        }
+[tool.poetry.group.dev.dependencies]
+pytest = "^8.0"
+"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_pyproject(tmp.path(), &mut fw);
+        assert!(!fw.contains_key("Django"));
+        assert!(!fw.contains_key("Flask"));
+        assert!(!fw.contains_key("SQLAlchemy"));
+        assert_eq!(fw["pytest"].version.as_deref(), Some("^8.0"));
+        assert_eq!(
+            normalize_python_name("Flask_SQLAlchemy"),
+            "flask-sqlalchemy"
+        );
+        assert_eq!(normalize_python_name("zope..interface"), "zope-interface");
     }
-}
 
-/// Detect frameworks from Gemfile.
-/// Port of RUBY_GEM_MAP from github.py.
-pub fn detect_ruby(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    let path = dir.join("Gemfile");
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-    let lower = content.to_lowercase();
+    #[test]
+    fn test_detect_pipfile_fastapi() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Pipfile"),
+            r#"[[source]]
+url = "https://pypi.org/simple"
+verify_ssl = true
+name = "pypi"
 
-    const RUBY_MAP: &[(&str, &str, &str)] = &[
-        ("rails", "Ruby on Rails", "framework"),
-        ("sinatra", "Sinatra", "framework"),
-        ("sidekiq", "Sidekiq", "tool"),
-        ("rspec", "RSpec", "tool"),
-    ];
+[packages]
+fastapi = "*"
+uvicorn = "==0.27.0"
 
-    for &(key, name, category) in RUBY_MAP {
-        if lower.contains(key) {
-            frameworks.insert(name.to_string(), category.to_string());
-        }
+[dev-packages]
+pytest = "==8.0.0"
+
+[requires]
+python_version = "3.12"
+"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_pipfile(tmp.path(), &mut fw);
+        assert!(fw.contains_key("FastAPI"));
+        assert_eq!(fw["FastAPI"].version, None);
+        assert_eq!(fw["pytest"].version.as_deref(), Some("==8.0.0"));
+        assert_eq!(fw["pytest"].category, "testing");
     }
-}
 
-/// Detect frameworks from go.mod.
-/// Port of GO_MODULE_MAP from github.py.
-pub fn detect_go(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    let path = dir.join("go.mod");
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
+    #[test]
+    fn test_detect_swift_package() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Package.swift"),
+            r#"// swift-tools-version:5.9
+let package = Package(
+    name: "App",
+    dependencies: [
+        .package(url: "https://github.com/Alamofire/Alamofire.git", from: "5.8.0"),
+        .package(url: "https://github.com/ReactiveX/RxSwift", exact: "6.6.0"),
+        .package(url: "https://github.com/example/unknown.git", branch: "main"),
+    ]
+)"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_swift(tmp.path(), &mut fw);
+        assert_eq!(fw["Alamofire"].version.as_deref(), Some("5.8.0"));
+        assert_eq!(fw["RxSwift"].version.as_deref(), Some("6.6.0"));
+        assert_eq!(fw["RxSwift"].category, "framework");
+        assert_eq!(fw.len(), 2);
+    }
 
-    const GO_MAP: &[(&str, &str, &str)] = &[
-        ("github.com/gin-gonic/gin", "Gin", "framework"),
-        ("github.com/gorilla/mux", "Gorilla Mux", "framework"),
-        ("github.com/labstack/echo", "Echo", "framework"),
-        ("github.com/gofiber/fiber", "Fiber", "framework"),
-        ("gorm.io/gorm", "GORM", "tool"),
-    ];
+    #[test]
+    fn test_detect_cocoapods() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Podfile"),
+            "platform :ios, '15.0'\n\ntarget 'App' do\n  pod 'SnapKit', '~> 5.6'\n  pod 'Firebase/Auth'\n  pod 'Kingfisher', :git => 'https://example.com'\nend\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_cocoapods(tmp.path(), &mut fw);
+        assert_eq!(fw["SnapKit"].version.as_deref(), Some("~> 5.6"));
+        assert!(fw.contains_key("Firebase"));
+        assert_eq!(fw["Kingfisher"].version, None);
+    }
 
-    for &(key, name, category) in GO_MAP {
-        if content.contains(key) {
-            frameworks.insert(name.to_string(), category.to_string());
-        }
+    #[test]
+    fn test_detect_mix() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("mix.exs"),
+            r#"defp deps do
+    [
+      {:phoenix, "~> 1.7.10"},
+      {:ecto_sql, "~> 3.10"},
+      {:plug_cowboy, "~> 2.5"},
+      {:absinthe, github: "absinthe-graphql/absinthe"},
+      {:jason, "~> 1.2"}
+    ]
+  end"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_mix(tmp.path(), &mut fw);
+        assert_eq!(fw["Phoenix"].version.as_deref(), Some("~> 1.7.10"));
+        assert_eq!(fw["Ecto"].version.as_deref(), Some("~> 3.10"));
+        assert!(fw.contains_key("Plug"));
+        assert_eq!(fw["Absinthe"].version, None);
+        assert_eq!(fw.len(), 4);
     }
-}
 
-/// Detect frameworks from composer.json.
-/// Port of PHP_PACKAGE_MAP from github.py.
-pub fn detect_php(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    let path = dir.join("composer.json");
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-    let parsed: serde_json::Value = match serde_json::from_str(&content) {
-        Ok(v) => v,
-        Err(_) => return,
-    };
+    #[test]
+    fn test_detect_dotnet() {
+        let tmp = TempDir::new().unwrap();
+        let project = tmp.path().join("src/Api");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join("Api.csproj"),
+            r#"<Project Sdk="Microsoft.NET.Sdk">
+  <ItemGroup>
+    <PackageReference Include="Microsoft.EntityFrameworkCore.SqlServer" Version="8.0.1" />
+    <PackageReference Include="Serilog.AspNetCore">
+      <Version>8.0.0</Version>
+    </PackageReference>
+    <PackageReference Include="Newtonsoft.Json" Version="13.0.3" />
+  </ItemGroup>
+</Project>"#,
+        )
+        .unwrap();
+        // Build output is never searched.
+        fs::create_dir_all(tmp.path().join("bin")).unwrap();
+        fs::write(
+            tmp.path().join("bin/Copy.csproj"),
+            r#"<Project><ItemGroup><PackageReference Include="Dapper" /></ItemGroup></Project>"#,
+        )
+        .unwrap();
 
-    let mut all_deps: Vec<String> = Vec::new();
-    for key in &["require", "require-dev"] {
-        if let Some(obj) = parsed.get(key).and_then(|v| v.as_object()) {
-            all_deps.extend(obj.keys().cloned());
-        }
+        let mut fw = HashMap::new();
+        detect_dotnet(tmp.path(), &mut fw);
+        assert_eq!(
+            fw["Entity Framework Core"].version.as_deref(),
+            Some("8.0.1")
+        );
+        assert_eq!(fw["Serilog"].version.as_deref(), Some("8.0.0"));
+        assert!(!fw.contains_key("Dapper"));
+        assert_eq!(fw.len(), 2);
     }
 
-    const PHP_MAP: &[(&str, &str, &str)] = &[
-        ("laravel/framework", "Laravel", "framework"),
-        ("symfony/symfony", "Symfony", "framework"),
-        ("slim/slim", "Slim", "framework"),
-    ];
+    #[test]
+    fn test_detect_dotnet_web_sdk() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("App.fsproj"),
+            r#"<Project Sdk="Microsoft.NET.Sdk.Web"></Project>"#,
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_dotnet(tmp.path(), &mut fw);
+        assert!(fw.contains_key("ASP.NET Core"));
+    }
 
-    for &(dep, name, category) in PHP_MAP {
-        if all_deps.iter().any(|d| d == dep) {
-            frameworks.insert(name.to_string(), category.to_string());
-        }
+    #[test]
+    fn test_detect_workflow_actions() {
+        let tmp = TempDir::new().unwrap();
+        let workflows = tmp.path().join(".github/workflows");
+        fs::create_dir_all(&workflows).unwrap();
+        fs::write(
+            workflows.join("release.yml"),
+            r#"jobs:
+  image:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: docker/build-push-action@v5
+      - uses: Azure/login@v2
+  pages:
+    steps:
+      - uses: actions/deploy-pages@v4
+  shared:
+    uses: aws-actions/shared/.github/workflows/deploy.yml@main
+"#,
+        )
+        .unwrap();
+        fs::write(
+            workflows.join("notes.md"),
+            "uses: google-github-actions/auth",
+        )
+        .unwrap();
+
+        let mut infra = HashMap::new();
+        detect_workflow_actions(tmp.path(), &mut infra);
+        let mut names: Vec<&str> = infra.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["AWS", "Azure", "Docker", "GitHub Pages"]);
     }
-}
 
-/// Detect frameworks from pyproject.toml dependencies.
-/// Fallback for Python projects that don't use requirements.txt.
-pub fn detect_pyproject(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    let path = dir.join("pyproject.toml");
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-    let lower = content.to_lowercase();
+    #[test]
+    fn test_detect_make_targets() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Makefile"),
+            ".PHONY: build test\nCC := gcc\n\nbuild: main.o\n\t$(CC) -o app main.o\n\n%.o: %.c\n\t$(CC) -c $<\n\ntest: build\n\t./app --test\n",
+        )
+        .unwrap();
+        assert_eq!(detect_make_targets(tmp.path()), vec!["build", "test"]);
 
-    const PYTHON_MAP: &[(&str, &str, &str)] = &[
-        ("django", "Django", "framework"),
-        ("flask", "Flask", "framework"),
-        ("fastapi", "FastAPI", "framework"),
-        ("tornado", "Tornado", "framework"),
-        ("celery", "Celery", "tool"),
-        ("sqlalchemy", "SQLAlchemy", "tool"),
-        ("pandas", "pandas", "framework"),
-        ("numpy", "NumPy", "framework"),
-        ("scipy", "SciPy", "framework"),
-        ("scikit-learn", "scikit-learn", "framework"),
-        ("tensorflow", "TensorFlow", "framework"),
-        ("torch", "PyTorch", "framework"),
-        ("pytest", "pytest", "tool"),
-        ("pydantic", "Pydantic", "tool"),
-        ("requests", "Requests", "tool"),
-        ("boto3", "AWS SDK", "tool"),
-        ("redis", "Redis", "tool"),
-        ("psycopg2", "PostgreSQL", "tool"),
-    ];
+        fs::write(
+            tmp.path().join("justfile"),
+            "set shell := [\"bash\", \"-c\"]\nversion := \"1.0\"\nalias t := test\n\n# Run the tests: all of them\ntest *args:\n    cargo test {{args}}\n@lint:\n    echo linting: now\n    cargo clippy\ndeploy env=\"staging\": build\n    ./deploy.sh {{env}}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_make_targets(tmp.path()),
+            vec!["build", "test", "lint", "deploy"]
+        );
+    }
 
-    for &(key, name, category) in PYTHON_MAP {
-        if lower.contains(key) {
-            frameworks.insert(name.to_string(), category.to_string());
-        }
+    #[test]
+    fn test_detect_runtime_versions_nvmrc() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join(".nvmrc"), "20.11.0\n").unwrap();
+        let mut fw = HashMap::new();
+        detect_runtime_versions(tmp.path(), &mut fw);
+        assert_eq!(fw["Node.js"].version.as_deref(), Some("20.11.0"));
+        assert_eq!(fw["Node.js"].category, "tool");
+        assert_eq!(fw.len(), 1);
     }
-}
 
-/// Run all dependency parsers for a given directory.
-pub fn detect_all(dir: &Path, frameworks: &mut HashMap<String, String>) {
-    detect_npm(dir, frameworks);
-    detect_python(dir, frameworks);
-    detect_pyproject(dir, frameworks);
-    detect_rust(dir, frameworks);
-    detect_ruby(dir, frameworks);
-    detect_go(dir, frameworks);
-    detect_php(dir, frameworks);
-}
+    #[test]
+    fn test_detect_runtime_versions() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join(".node-version"), "v18.19.0\n").unwrap();
+        fs::write(tmp.path().join("runtime.txt"), "python-3.12.1\n").unwrap();
+        fs::write(tmp.path().join(".ruby-version"), "ruby-3.3.0\n").unwrap();
+        fs::write(
+            tmp.path().join("go.mod"),
+            "module example.com/app\n\ngo 1.21\n\ntoolchain go1.22.1\n",
+        )
+        .unwrap();
+        let mut fw = HashMap::new();
+        detect_runtime_versions(tmp.path(), &mut fw);
+        assert_eq!(fw["Node.js"].version.as_deref(), Some("18.19.0"));
+        assert_eq!(fw["Python"].version.as_deref(), Some("3.12.1"));
+        assert_eq!(fw["Python"].category, "language");
+        assert_eq!(fw["Ruby"].version.as_deref(), Some("3.3.0"));
+        assert_eq!(fw["Go"].version.as_deref(), Some("1.21"));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
+        // A pin file beats the go.mod minimum, and aliases pin nothing.
+        fs::write(tmp.path().join(".go-version"), "1.22.1\n").unwrap();
+        fs::write(tmp.path().join(".python-version"), "system\n").unwrap();
+        let mut fw = HashMap::new();
+        detect_runtime_versions(tmp.path(), &mut fw);
+        assert_eq!(fw["Go"].version.as_deref(), Some("1.22.1"));
+        assert_eq!(fw["Python"].version.as_deref(), Some("3.12.1"));
+        assert_eq!(pinned_version("# pinned\nlts/*\n", ""), None);
+    }
 
     #[test]
-    fn test_detect_npm_react() {
+    fn test_detect_workflow_versions() {
         let tmp = TempDir::new().unwrap();
+        let workflows = tmp.path().join(".github/workflows");
+        fs::create_dir_all(&workflows).unwrap();
         fs::write(
-            tmp.path().join("package.json"),
-            r#"{"dependencies": {"react": "^18.0.0", "express": "^4.0.0"}}"#,
+            workflows.join("ci.yml"),
+            r#"jobs:
+  test:
+    strategy:
+      matrix:
+        python-version: [3.9, 3.10, "3.11"] # oldest supported first
+        node-version:
+          - 18
+          - '20'
+        include:
+          - python-version: "3.12"
+    steps:
+      - uses: actions/setup-python@v5
+        with:
+          python-version: ${{ matrix.python-version }}
+"#,
+        )
+        .unwrap();
+        fs::write(
+            workflows.join("lint.yaml"),
+            "jobs:\n  lint:\n    steps:\n      - with:\n          go-version: '1.22'\n          python-version: '3.9'\n",
         )
         .unwrap();
+
         let mut fw = HashMap::new();
-        detect_npm(tmp.path(), &mut fw);
-        assert!(fw.contains_key("React"));
-        assert!(fw.contains_key("Express"));
+        detect_workflow_versions(tmp.path(), &mut fw);
+        assert_eq!(
+            fw["Python"].version.as_deref(),
+            Some("3.9, 3.10, 3.11, 3.12")
+        );
+        assert_eq!(fw["Python"].category, "language");
+        assert_eq!(fw["Node.js"].version.as_deref(), Some("18, 20"));
+        assert_eq!(fw["Go"].version.as_deref(), Some("1.22"));
+        assert!(!fw.contains_key("Ruby"));
     }
 
     #[test]
-    fn test_detect_python_flask() {
+    fn test_detect_workflow_actions_none() {
+        let tmp = TempDir::new().unwrap();
+        let mut infra = HashMap::new();
+        detect_workflow_actions(tmp.path(), &mut infra);
+        assert!(infra.is_empty());
+    }
+
+    #[test]
+    fn test_detect_dockerfile_details() {
         let tmp = TempDir::new().unwrap();
         fs::write(
-            tmp.path().join("requirements.txt"),
-            "flask==2.3.0\nrequests\n",
+            tmp.path().join("Dockerfile"),
+            r#"FROM node:20-alpine AS assets
+RUN npm ci && npm run build
+
+from golang:1.22 as builder
+RUN go build -o /app
+
+FROM builder AS test
+RUN go test ./...
+
+FROM gcr.io/distroless/base@sha256:abc123
+COPY --from=builder /app /app
+COPY --from=assets /dist /static
+EXPOSE 8080 9090/tcp $EXTRA
+"#,
         )
         .unwrap();
-        let mut fw = HashMap::new();
-        detect_python(tmp.path(), &mut fw);
-        assert!(fw.contains_key("Flask"));
-        assert!(fw.contains_key("Requests"));
+        let mut infra = HashMap::new();
+        detect_dockerfile_details(tmp.path(), &mut infra);
+
+        let mut names: Vec<&str> = infra.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "Docker: base",
+                "Docker: golang",
+                "Docker: node",
+                "Docker: port 8080",
+                "Docker: port 9090",
+            ]
+        );
+        assert_eq!(infra["Docker: node"].version.as_deref(), Some("20-alpine"));
+        assert_eq!(infra["Docker: golang"].version.as_deref(), Some("1.22"));
+        assert_eq!(infra["Docker: base"].version, None);
     }
 
     #[test]
-    fn test_detect_rust_actix() {
+    fn test_detect_haskell_cabal() {
         let tmp = TempDir::new().unwrap();
         fs::write(
-            tmp.path().join("Cargo.toml"),
-            "[dependencies]\nactix-web = \"4\"\ntokio = { version = \"1\" }\n",
+            tmp.path().join("api.cabal"),
+            r#"cabal-version: 2.4
+name:          api
+
+library
+  exposed-modules: Api
+  build-depends:
+      base >=4.14 && <5
+    , servant-server ^>=0.20
+    -- JSON
+    , aeson
+  default-language: Haskell2010
+
+executable api
+  main-is: Main.hs
+  Build-Depends: base, warp >= 3.3, api
+"#,
         )
         .unwrap();
         let mut fw = HashMap::new();
-        detect_rust(tmp.path(), &mut fw);
-        assert!(fw.contains_key("Actix Web"));
-        assert!(fw.contains_key("Tokio"));
+        detect_haskell(tmp.path(), &mut fw);
+        assert_eq!(fw["Servant"].version.as_deref(), Some("^>=0.20"));
+        assert_eq!(fw["Servant"].category, "framework");
+        assert_eq!(fw["Aeson"].version, None);
+        assert_eq!(fw["Warp"].version.as_deref(), Some(">= 3.3"));
+        assert_eq!(fw.len(), 3);
     }
 
     #[test]
-    fn test_detect_go_gin() {
+    fn test_detect_zig() {
         let tmp = TempDir::new().unwrap();
         fs::write(
-            tmp.path().join("go.mod"),
-            "module example.com/app\nrequire github.com/gin-gonic/gin v1.9.0\n",
+            tmp.path().join("build.zig.zon"),
+            r#".{
+    .name = .server,
+    .version = "0.1.0",
+    .dependencies = .{
+        // HTTP server
+        .httpz = .{
+            .url = "git+https://github.com/karlseguin/http.zig#2d8a2ab8ba1dce8e0b7f6a6b3c2b1f0e9d8c7b6a",
+            .hash = "httpz-0.0.0-PNVzrJSuBgDFvO7mtd2qDzaq8_hXIu1BqFuL1jwAV8Ar",
+        },
+        .@"zig-clap" = .{ .url = "https://github.com/Hejsil/zig-clap/archive/refs/tags/0.10.0.tar.gz", .hash = "clap-0.10.0" },
+        .local = .{ .path = "../local" },
+    },
+    .paths = .{ "build.zig", "build.zig.zon", "src" },
+}
+"#,
         )
         .unwrap();
         let mut fw = HashMap::new();
-        detect_go(tmp.path(), &mut fw);
-        assert!(fw.contains_key("Gin"));
+        detect_zig(tmp.path(), &mut fw);
+        assert_eq!(fw["http.zig"].category, "framework");
+        assert_eq!(fw["http.zig"].version, None);
+        assert_eq!(fw["zig-clap"].version.as_deref(), Some("0.10.0"));
+        assert_eq!(fw.len(), 2);
     }
 
     #[test]
-    fn test_detect_missing_file() {
+    fn test_zon_dependencies_empty() {
+        let zon = ".{\n    .name = .empty,\n    .dependencies = .{},\n    .paths = .{\"\"},\n}\n";
+        assert!(zon_dependencies(zon).is_empty());
+        assert_eq!(
+            zon_url_version("https://github.com/zigzap/zap/archive/v0.9.1.tar.gz").as_deref(),
+            Some("v0.9.1")
+        );
+        assert_eq!(zon_url_version("https://example.com/main.tar.gz"), None);
+    }
+
+    #[test]
+    fn test_detect_haskell_package_yaml() {
         let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package.yaml"),
+            r#"name: site
+dependencies:
+  - base >= 4.7 && < 5
+  - yesod
+executables:
+  site:
+    main: Main.hs
+    dependencies:
+      lens: ">= 5"
+tests:
+  spec:
+    dependencies:
+      - hspec
+"#,
+        )
+        .unwrap();
         let mut fw = HashMap::new();
-        detect_npm(tmp.path(), &mut fw);
-        assert!(fw.is_empty());
+        detect_haskell(tmp.path(), &mut fw);
+        let mut names: Vec<&str> = fw.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Hspec", "Yesod", "lens"]);
+        assert_eq!(fw["lens"].version.as_deref(), Some(">= 5"));
     }
 
     #[test]
-    fn test_detect_php_laravel() {
+    fn test_detect_compose_services() {
         let tmp = TempDir::new().unwrap();
         fs::write(
-            tmp.path().join("composer.json"),
-            r#"{"require": {"laravel/framework": "^10.0"}}"#,
+            tmp.path().join("docker-compose.yml"),
+            r#"services:
+  db:
+    image: postgres:16-alpine
+  cache:
+    image: bitnami/redis:latest
+  proxy:
+    image: docker.io/library/nginx
+  app:
+    build: .
+"#,
         )
         .unwrap();
         let mut fw = HashMap::new();
-        detect_php(tmp.path(), &mut fw);
-        assert!(fw.contains_key("Laravel"));
+        let mut infra = HashMap::new();
+        detect_compose_services(tmp.path(), &mut fw, &mut infra);
+        assert_eq!(fw["PostgreSQL"].version.as_deref(), Some("16-alpine"));
+        assert_eq!(fw["Redis"].version, None);
+        assert_eq!(fw.len(), 2);
+        assert_eq!(infra["Nginx"].category, "infrastructure");
     }
 
     #[test]
-    fn test_detect_pyproject_flask() {
+    fn test_detect_conda() {
         let tmp = TempDir::new().unwrap();
         fs::write(
-            tmp.path().join("pyproject.toml"),
-            "[project]\ndependencies = [\n  \"flask>=2.3.0\",\n  \"pydantic>=2.0\",\n]\n",
+            tmp.path().join("environment.yml"),
+            r#"name: ml
+channels:
+  - conda-forge
+dependencies:
+  - python=3.11
+  - conda-forge::numpy=1.26
+  - pip
+  - pip:
+      - fastapi>=0.110
+"#,
         )
         .unwrap();
         let mut fw = HashMap::new();
-        detect_pyproject(tmp.path(), &mut fw);
-        assert!(fw.contains_key("Flask"));
-        assert!(fw.contains_key("Pydantic"));
+        detect_conda(tmp.path(), &mut fw);
+        assert_eq!(fw["NumPy"].version.as_deref(), Some("=1.26"));
+        assert_eq!(fw["FastAPI"].version.as_deref(), Some(">=0.110"));
+        assert_eq!(fw.len(), 2);
     }
 
     #[test]
-    fn test_detect_ruby_rails() {
+    fn test_detect_terraform() {
         let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("infra")).unwrap();
         fs::write(
-            tmp.path().join("Gemfile"),
-            "source 'https://rubygems.org'\ngem 'rails', '~> 7.0'\ngem 'rspec'\n",
+            tmp.path().join("infra/main.tf"),
+            r#"terraform {
+  required_providers {
+    google = {
+      source  = "hashicorp/google"
+      version = "~> 5.0"
+    }
+  }
+}
+
+provider "azurerm" {
+  features {}
+}
+"#,
+        )
+        .unwrap();
+        let mut infra = HashMap::new();
+        detect_terraform(tmp.path(), &mut infra);
+        assert!(infra.contains_key("Terraform"));
+        assert!(infra.contains_key("GCP"));
+        assert!(infra.contains_key("Azure"));
+        assert_eq!(infra.len(), 3);
+    }
+
+    #[test]
+    fn test_detect_terraform_no_files() {
+        let tmp = TempDir::new().unwrap();
+        let mut infra = HashMap::new();
+        detect_terraform(tmp.path(), &mut infra);
+        assert!(infra.is_empty());
+    }
+
+    #[test]
+    fn test_detect_pubspec() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pubspec.yaml"),
+            r#"name: app
+dependencies:
+  flutter:
+    sdk: flutter
+  flutter_riverpod: ^2.4.9
+  dio: ^5.4.0
+dev_dependencies:
+  bloc_test: ^9.1.0
+"#,
         )
         .unwrap();
         let mut fw = HashMap::new();
-        detect_ruby(tmp.path(), &mut fw);
-        assert!(fw.contains_key("Ruby on Rails"));
-        assert!(fw.contains_key("RSpec"));
+        detect_pubspec(tmp.path(), &mut fw);
+        assert_eq!(fw["Flutter"].version, None);
+        assert_eq!(fw["Riverpod"].version.as_deref(), Some("^2.4.9"));
+        assert_eq!(fw["Dio"].version.as_deref(), Some("^5.4.0"));
+        assert_eq!(fw.len(), 3);
     }
 }
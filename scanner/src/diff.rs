@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::output::ScanResult;
+
+/// How two scans of the same tree differ, for tracking a stack over time.
+#[derive(Debug, Default, Serialize, Clone, PartialEq)]
+pub struct ScanDiff {
+    /// Languages, frameworks and structures only in the newer scan.
+    pub added: DiffSection,
+    /// Languages, frameworks and structures only in the baseline.
+    pub removed: DiffSection,
+    /// Languages in both scans whose share moved by more than the
+    /// threshold, largest move first.
+    pub changed: Vec<LanguageChange>,
+}
+
+/// Names grouped by what they are, each list sorted.
+#[derive(Debug, Default, Serialize, Clone, PartialEq)]
+pub struct DiffSection {
+    pub languages: Vec<String>,
+    pub frameworks: Vec<String>,
+    pub structures: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LanguageChange {
+    pub name: String,
+    /// Percentage in the baseline.
+    pub before: f64,
+    /// Percentage in the newer scan.
+    pub after: f64,
+}
+
+/// Compare `current` against `baseline`. Language shares that moved by
+/// `threshold` percentage points or less are left out of `changed`.
+pub fn diff_scans(baseline: &ScanResult, current: &ScanResult, threshold: f64) -> ScanDiff {
+    let before: BTreeMap<&str, f64> = baseline
+        .languages
+        .iter()
+        .map(|l| (l.name.as_str(), l.percentage))
+        .collect();
+    let after: BTreeMap<&str, f64> = current
+        .languages
+        .iter()
+        .map(|l| (l.name.as_str(), l.percentage))
+        .collect();
+    let before_langs: BTreeSet<&str> = before.keys().copied().collect();
+    let after_langs: BTreeSet<&str> = after.keys().copied().collect();
+
+    let framework_names = |result: &ScanResult| -> BTreeSet<String> {
+        result.frameworks.iter().map(|f| f.name.clone()).collect()
+    };
+    let before_fw = framework_names(baseline);
+    let after_fw = framework_names(current);
+    let before_st: BTreeSet<String> = baseline.project_structures.iter().cloned().collect();
+    let after_st: BTreeSet<String> = current.project_structures.iter().cloned().collect();
+
+    let mut changed: Vec<LanguageChange> = before
+        .iter()
+        .filter_map(|(&name, &was)| {
+            let now = *after.get(name)?;
+            ((now - was).abs() > threshold).then(|| LanguageChange {
+                name: name.to_string(),
+                before: was,
+                after: now,
+            })
+        })
+        .collect();
+    changed.sort_by(|a, b| {
+        let moved = |c: &LanguageChange| (c.after - c.before).abs();
+        moved(b).total_cmp(&moved(a)).then(a.name.cmp(&b.name))
+    });
+
+    ScanDiff {
+        added: DiffSection {
+            languages: difference(&after_langs, &before_langs),
+            frameworks: difference(&after_fw, &before_fw),
+            structures: difference(&after_st, &before_st),
+        },
+        removed: DiffSection {
+            languages: difference(&before_langs, &after_langs),
+            frameworks: difference(&before_fw, &after_fw),
+            structures: difference(&before_st, &after_st),
+        },
+        changed,
+    }
+}
+
+/// Items of `a` missing from `b`, sorted.
+fn difference<T: Ord + ToString>(a: &BTreeSet<T>, b: &BTreeSet<T>) -> Vec<String> {
+    a.difference(b).map(ToString::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{LanguageEntry, SignalEntry};
+    use crate::scan::scan_from_entries;
+
+    fn result(languages: &[(&str, f64)], frameworks: &[&str], structures: &[&str]) -> ScanResult {
+        ScanResult {
+            languages: languages
+                .iter()
+                .map(|&(name, percentage)| LanguageEntry {
+                    name: name.to_string(),
+                    category: "programming".to_string(),
+                    percentage,
+                })
+                .collect(),
+            frameworks: frameworks
+                .iter()
+                .map(|name| SignalEntry::new(name, "framework", None))
+                .collect(),
+            project_structures: structures.iter().map(|s| s.to_string()).collect(),
+            ..scan_from_entries(&[])
+        }
+    }
+
+    #[test]
+    fn test_diff_scans() {
+        let baseline = result(
+            &[("Python", 80.0), ("Shell", 15.0), ("Go", 5.0)],
+            &["Flask", "pytest"],
+            &["src_layout"],
+        );
+        let current = result(
+            &[("Python", 60.0), ("TypeScript", 24.5), ("Shell", 15.5)],
+            &["Flask", "React"],
+            &["src_layout", "node_project"],
+        );
+
+        let diff = diff_scans(&baseline, &current, 1.0);
+        assert_eq!(diff.added.languages, vec!["TypeScript"]);
+        assert_eq!(diff.added.frameworks, vec!["React"]);
+        assert_eq!(diff.added.structures, vec!["node_project"]);
+        assert_eq!(diff.removed.languages, vec!["Go"]);
+        assert_eq!(diff.removed.frameworks, vec!["pytest"]);
+        assert!(diff.removed.structures.is_empty());
+        // Shell moved by only half a point.
+        assert_eq!(
+            diff.changed,
+            vec![LanguageChange {
+                name: "Python".to_string(),
+                before: 80.0,
+                after: 60.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical_scans() {
+        let scan = result(&[("Rust", 100.0)], &["Tokio"], &["src_layout"]);
+        assert_eq!(diff_scans(&scan, &scan, 0.0), ScanDiff::default());
+    }
+}
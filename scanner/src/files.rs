@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use globset::GlobSet;
+
+/// Read access to a repository's files, for the detectors that parse
+/// manifests. Paths are relative to the repository root. Implemented for
+/// [`Path`] (the repo on disk, rooted at that path) and for [`MemoryTree`].
+pub trait RepoFiles {
+    /// Contents of the file at `relative`, which must be UTF-8.
+    fn read_to_string(&self, relative: &Path) -> io::Result<String>;
+    /// Names of the entries directly inside the directory at `relative`,
+    /// each with whether it is a directory. Empty if there is no such
+    /// directory.
+    fn read_dir(&self, relative: &Path) -> Vec<(String, bool)>;
+    /// Whether `relative` is a directory.
+    fn is_dir(&self, relative: &Path) -> bool;
+}
+
+impl RepoFiles for Path {
+    fn read_to_string(&self, relative: &Path) -> io::Result<String> {
+        fs::read_to_string(self.join(relative))
+    }
+
+    fn read_dir(&self, relative: &Path) -> Vec<(String, bool)> {
+        let Ok(entries) = fs::read_dir(self.join(relative)) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                (name, entry.path().is_dir())
+            })
+            .collect()
+    }
+
+    fn is_dir(&self, relative: &Path) -> bool {
+        self.join(relative).is_dir()
+    }
+}
+
+/// A view of another [`RepoFiles`] in which paths matching `exclude`, or
+/// under a directory that does, read as absent, so the manifest readers
+/// honor `--exclude` the same way the walk does.
+#[derive(Debug)]
+pub struct Excluding<'a, F: ?Sized> {
+    files: &'a F,
+    exclude: &'a GlobSet,
+}
+
+impl<'a, F: RepoFiles + ?Sized> Excluding<'a, F> {
+    pub fn new(files: &'a F, exclude: &'a GlobSet) -> Self {
+        Excluding { files, exclude }
+    }
+
+    fn is_excluded(&self, relative: &Path) -> bool {
+        let mut prefix = PathBuf::new();
+        relative.iter().any(|name| {
+            prefix.push(name);
+            self.exclude.is_match(&prefix)
+        })
+    }
+}
+
+impl<F: RepoFiles + ?Sized> RepoFiles for Excluding<'_, F> {
+    fn read_to_string(&self, relative: &Path) -> io::Result<String> {
+        if self.is_excluded(relative) {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+        self.files.read_to_string(relative)
+    }
+
+    fn read_dir(&self, relative: &Path) -> Vec<(String, bool)> {
+        if self.is_excluded(relative) {
+            return Vec::new();
+        }
+        let mut entries = self.files.read_dir(relative);
+        entries.retain(|(name, _)| !self.exclude.is_match(relative.join(name)));
+        entries
+    }
+
+    fn is_dir(&self, relative: &Path) -> bool {
+        !self.is_excluded(relative) && self.files.is_dir(relative)
+    }
+}
+
+/// A repository held in memory as `(path, size, contents)` entries, e.g.
+/// from a tarball or a git tree object. Directories are implied by the
+/// file paths; files without contents exist but can't be read.
+#[derive(Debug, Default)]
+pub struct MemoryTree<'a> {
+    files: BTreeMap<&'a Path, Option<&'a [u8]>>,
+}
+
+impl<'a> MemoryTree<'a> {
+    pub fn new(entries: &'a [(PathBuf, u64, Option<Vec<u8>>)]) -> Self {
+        let files = entries
+            .iter()
+            .map(|(path, _, contents)| (path.as_path(), contents.as_deref()))
+            .collect();
+        MemoryTree { files }
+    }
+}
+
+impl RepoFiles for MemoryTree<'_> {
+    fn read_to_string(&self, relative: &Path) -> io::Result<String> {
+        let contents = self
+            .files
+            .get(relative)
+            .copied()
+            .flatten()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        String::from_utf8(contents.to_vec())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn read_dir(&self, relative: &Path) -> Vec<(String, bool)> {
+        let mut entries: BTreeMap<String, bool> = BTreeMap::new();
+        for path in self.files.keys() {
+            let Ok(rest) = path.strip_prefix(relative) else {
+                continue;
+            };
+            let mut components = rest.iter();
+            let Some(name) = components.next() else {
+                continue;
+            };
+            let is_dir = components.next().is_some();
+            *entries
+                .entry(name.to_string_lossy().into_owned())
+                .or_default() |= is_dir;
+        }
+        entries.into_iter().collect()
+    }
+
+    fn is_dir(&self, relative: &Path) -> bool {
+        self.files
+            .keys()
+            .any(|path| path.starts_with(relative) && *path != relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_tree() {
+        let entries = vec![
+            (PathBuf::from("Cargo.toml"), 7, Some(b"[a]\nb=1".to_vec())),
+            (PathBuf::from("src/main.rs"), 12, None),
+            (PathBuf::from("src/bin/cli.rs"), 3, Some(vec![0xff, 0xfe])),
+        ];
+        let tree = MemoryTree::new(&entries);
+
+        assert_eq!(
+            tree.read_to_string(Path::new("Cargo.toml")).unwrap(),
+            "[a]\nb=1"
+        );
+        assert!(tree.read_to_string(Path::new("src/main.rs")).is_err());
+        assert!(tree.read_to_string(Path::new("src/bin/cli.rs")).is_err());
+        assert!(tree.read_to_string(Path::new("missing")).is_err());
+
+        assert_eq!(
+            tree.read_dir(Path::new("")),
+            vec![("Cargo.toml".to_string(), false), ("src".to_string(), true)]
+        );
+        assert_eq!(
+            tree.read_dir(Path::new("src")),
+            vec![("bin".to_string(), true), ("main.rs".to_string(), false)]
+        );
+        assert!(tree.is_dir(Path::new("src/bin")));
+        assert!(!tree.is_dir(Path::new("src/main.rs")));
+        assert!(!tree.is_dir(Path::new("docs")));
+    }
+
+    #[test]
+    fn test_excluding() {
+        let entries = vec![
+            (PathBuf::from("package.json"), 2, Some(b"{}".to_vec())),
+            (
+                PathBuf::from("legacy/Api/Api.csproj"),
+                2,
+                Some(b"<>".to_vec()),
+            ),
+            (PathBuf::from("src/lib.rs"), 2, Some(b"//".to_vec())),
+        ];
+        let tree = MemoryTree::new(&entries);
+        let mut builder = globset::GlobSetBuilder::new();
+        builder.add(globset::Glob::new("package.json").unwrap());
+        builder.add(globset::Glob::new("legacy").unwrap());
+        let exclude = builder.build().unwrap();
+        let view = Excluding::new(&tree, &exclude);
+
+        assert!(view.read_to_string(Path::new("package.json")).is_err());
+        assert!(view
+            .read_to_string(Path::new("legacy/Api/Api.csproj"))
+            .is_err());
+        assert_eq!(view.read_to_string(Path::new("src/lib.rs")).unwrap(), "//");
+        assert_eq!(
+            view.read_dir(Path::new("")),
+            vec![("src".to_string(), true)]
+        );
+        assert!(view.read_dir(Path::new("legacy")).is_empty());
+        assert!(!view.is_dir(Path::new("legacy/Api")));
+        assert!(view.is_dir(Path::new("src")));
+    }
+}
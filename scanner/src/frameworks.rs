@@ -1,5 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 
+use crate::files::RepoFiles;
 use crate::output::SignalEntry;
 
 /// File/dir indicator → (name, category).
@@ -9,6 +11,8 @@ const FRAMEWORK_INDICATORS: &[(&str, &str, &str)] = &[
     ("Dockerfile", "Docker", "infrastructure"),
     ("docker-compose.yml", "Docker Compose", "infrastructure"),
     ("docker-compose.yaml", "Docker Compose", "infrastructure"),
+    ("compose.yml", "Docker Compose", "infrastructure"),
+    ("compose.yaml", "Docker Compose", "infrastructure"),
     (".github/workflows", "GitHub Actions", "infrastructure"),
     (".gitlab-ci.yml", "GitLab CI", "infrastructure"),
     (".circleci", "CircleCI", "infrastructure"),
@@ -24,7 +28,23 @@ const FRAMEWORK_INDICATORS: &[(&str, &str, &str)] = &[
     ("render.yaml", "Render", "infrastructure"),
     ("nginx.conf", "Nginx", "infrastructure"),
     ("Vagrantfile", "Vagrant", "infrastructure"),
+    ("Containerfile", "Podman", "infrastructure"),
+    ("flake.nix", "Nix", "infrastructure"),
+    ("default.nix", "Nix", "infrastructure"),
+    ("shell.nix", "Nix", "infrastructure"),
+    (".devcontainer", "Dev Containers", "infrastructure"),
+    (".devcontainer.json", "Dev Containers", "infrastructure"),
+    ("devcontainer.json", "Dev Containers", "infrastructure"),
+    (".buildpacks", "Cloud Native Buildpacks", "infrastructure"),
+    ("project.toml", "Cloud Native Buildpacks", "infrastructure"),
     ("ansible", "Ansible", "infrastructure"),
+    ("serverless.yml", "Serverless Framework", "infrastructure"),
+    ("serverless.yaml", "Serverless Framework", "infrastructure"),
+    ("Pulumi.yaml", "Pulumi", "infrastructure"),
+    ("template.yaml", "AWS SAM", "infrastructure"),
+    ("samconfig.toml", "AWS SAM", "infrastructure"),
+    ("cdk.json", "AWS CDK", "infrastructure"),
+    ("Procfile", "Heroku", "infrastructure"),
     // Tools
     (".eslintrc.js", "ESLint", "tool"),
     (".eslintrc.json", "ESLint", "tool"),
@@ -35,44 +55,146 @@ const FRAMEWORK_INDICATORS: &[(&str, &str, &str)] = &[
     ("vite.config.ts", "Vite", "tool"),
     ("vite.config.js", "Vite", "tool"),
     (".prettierrc", "Prettier", "tool"),
-    ("jest.config.js", "Jest", "tool"),
-    ("jest.config.ts", "Jest", "tool"),
-    ("pytest.ini", "pytest", "tool"),
+    ("jest.config.js", "Jest", "testing"),
+    ("jest.config.ts", "Jest", "testing"),
+    ("pytest.ini", "pytest", "testing"),
     ("pyproject.toml", "Python Package", "tool"),
+    ("environment.yml", "Conda", "tool"),
     ("Cargo.toml", "Rust", "language"),
     ("go.mod", "Go", "language"),
     ("Gemfile", "Ruby", "language"),
     ("composer.json", "PHP", "language"),
     ("build.gradle", "Gradle", "tool"),
+    ("build.gradle.kts", "Gradle", "tool"),
     ("pom.xml", "Maven", "tool"),
+    ("nx.json", "Nx", "tool"),
+    ("turbo.json", "Turborepo", "tool"),
+    ("lerna.json", "Lerna", "tool"),
+    ("pnpm-workspace.yaml", "pnpm workspaces", "tool"),
+    ("WORKSPACE", "Bazel", "tool"),
+    ("WORKSPACE.bazel", "Bazel", "tool"),
+    ("MODULE.bazel", "Bazel", "tool"),
+    // Bare `BUILD` files are shared with Pants, so only the Bazel-specific
+    // name counts. It appears in every package, at any depth.
+    ("BUILD.bazel", "Bazel", "tool"),
+    ("BUCK", "Buck", "tool"),
+    (".buckconfig", "Buck", "tool"),
+    ("Package.swift", "Swift Package Manager", "tool"),
+    ("Podfile", "CocoaPods", "tool"),
+    ("mix.exs", "Elixir", "language"),
+    ("stack.yaml", "Stack", "tool"),
+    ("cabal.project", "Cabal", "tool"),
+    ("pubspec.yaml", "Dart", "language"),
+    ("build.zig", "Zig", "language"),
+    // WebAssembly
+    ("Trunk.toml", "Trunk", "tool"),
+    ("wasmer.toml", "Wasmer", "tool"),
+    // Migrations
+    ("alembic.ini", "Alembic", "tool"),
+    ("schema.prisma", "Prisma", "tool"),
 ];
 
-/// Detect frameworks and infrastructure from top-level file/dir names.
+/// API schema extension → (name, category), for files anywhere in the tree.
+/// Hand-written WebAssembly text is tracked the same way.
+const SCHEMA_EXTENSIONS: &[(&str, &str, &str)] = &[
+    ("proto", "Protocol Buffers", "tool"),
+    ("graphql", "GraphQL", "tool"),
+    ("gql", "GraphQL", "tool"),
+    ("wat", "WebAssembly Text", "tool"),
+];
+
+/// Whether `path` is an API schema file (`.proto`, `.graphql`, `.gql`) or
+/// WebAssembly text (`.wat`).
+pub fn is_schema_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| SCHEMA_EXTENSIONS.iter().any(|&(e, _, _)| e == ext))
+}
+
+/// Detect API contracts from the schema files found during a walk. The
+/// protobuf version is the `syntax` (`proto2`/`proto3`) of the first
+/// `.proto` file that declares one.
+pub fn detect_schema_files(
+    root: &(impl RepoFiles + ?Sized),
+    files: &BTreeSet<PathBuf>,
+    frameworks: &mut HashMap<String, SignalEntry>,
+) {
+    for &(ext, name, category) in SCHEMA_EXTENSIONS {
+        let mut matching = files
+            .iter()
+            .filter(|f| f.extension().and_then(|e| e.to_str()) == Some(ext))
+            .peekable();
+        if matching.peek().is_none() {
+            continue;
+        }
+        let version = if ext == "proto" {
+            matching.find_map(|f| proto_syntax(&root.read_to_string(f).ok()?))
+        } else {
+            None
+        };
+        insert_signal(frameworks, SignalEntry::new(name, category, version));
+    }
+}
+
+/// The `syntax = "proto3";` declaration of a `.proto` file, if any.
+fn proto_syntax(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("syntax")?.trim_start();
+        let value = rest.strip_prefix('=')?.trim().trim_end_matches(';').trim();
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Whether a bare file/dir name is an indicator on its own, wherever it
+/// appears. Path-style indicators such as `.github/workflows` only count
+/// at the top level.
+pub fn is_indicator_name(name: &str) -> bool {
+    FRAMEWORK_INDICATORS
+        .iter()
+        .any(|&(indicator, _, _)| indicator == name && !indicator.contains('/'))
+}
+
+/// Detect frameworks and infrastructure from file/dir names.
 pub fn detect_file_indicators(
-    top_level_names: &[String],
-    frameworks: &mut HashMap<String, String>,
-    infra: &mut HashMap<String, String>,
+    names: &[String],
+    frameworks: &mut HashMap<String, SignalEntry>,
+    infra: &mut HashMap<String, SignalEntry>,
 ) {
     for &(indicator, name, category) in FRAMEWORK_INDICATORS {
-        if top_level_names.iter().any(|n| n == indicator) {
+        if names.iter().any(|n| n == indicator) {
             if category == "infrastructure" {
-                infra.insert(name.to_string(), category.to_string());
+                insert_signal(infra, SignalEntry::new(name, category, None));
             } else {
-                frameworks.insert(name.to_string(), category.to_string());
+                insert_signal(frameworks, SignalEntry::new(name, category, None));
+            }
+        }
+    }
+}
+
+/// Record a detected signal keyed by name. A later detection overrides the
+/// category but keeps a previously recorded version when it has none itself.
+/// An exact detection clears the low confidence of a fuzzy one.
+pub fn insert_signal(map: &mut HashMap<String, SignalEntry>, entry: SignalEntry) {
+    match map.get_mut(&entry.name) {
+        Some(existing) => {
+            existing.category = entry.category;
+            if entry.version.is_some() {
+                existing.version = entry.version;
+            }
+            if entry.confidence.is_none() {
+                existing.confidence = None;
             }
         }
+        None => {
+            map.insert(entry.name.clone(), entry);
+        }
     }
 }
 
 /// Convert HashMap accumulators into sorted SignalEntry vectors.
-pub fn into_sorted_entries(map: &HashMap<String, String>) -> Vec<SignalEntry> {
-    let mut entries: Vec<SignalEntry> = map
-        .iter()
-        .map(|(name, category)| SignalEntry {
-            name: name.clone(),
-            category: category.clone(),
-        })
-        .collect();
+pub fn into_sorted_entries(map: &HashMap<String, SignalEntry>) -> Vec<SignalEntry> {
+    let mut entries: Vec<SignalEntry> = map.values().cloned().collect();
     entries.sort();
     entries
 }
@@ -81,6 +203,15 @@ pub fn into_sorted_entries(map: &HashMap<String, String>) -> Vec<SignalEntry> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_indicator_name() {
+        assert!(is_indicator_name("Dockerfile"));
+        assert!(is_indicator_name("vite.config.ts"));
+        assert!(!is_indicator_name("workflows"));
+        assert!(!is_indicator_name(".github/workflows"));
+        assert!(!is_indicator_name("main.rs"));
+    }
+
     #[test]
     fn test_detect_dockerfile() {
         let names = vec!["Dockerfile".to_string(), "src".to_string()];
@@ -91,6 +222,98 @@ mod tests {
         assert!(!fw.contains_key("Docker"));
     }
 
+    #[test]
+    fn test_detect_containerfile() {
+        let names = vec!["Containerfile".to_string()];
+        let mut fw = HashMap::new();
+        let mut infra = HashMap::new();
+        detect_file_indicators(&names, &mut fw, &mut infra);
+        assert_eq!(infra["Podman"].category, "infrastructure");
+        assert!(fw.is_empty());
+        assert!(is_indicator_name("Containerfile"));
+    }
+
+    #[test]
+    fn test_detect_trunk() {
+        let names = vec!["Trunk.toml".to_string()];
+        let mut fw = HashMap::new();
+        let mut infra = HashMap::new();
+        detect_file_indicators(&names, &mut fw, &mut infra);
+        assert_eq!(fw["Trunk"].category, "tool");
+        assert!(infra.is_empty());
+    }
+
+    #[test]
+    fn test_detect_nix() {
+        for indicator in ["flake.nix", "default.nix", "shell.nix"] {
+            let names = vec![indicator.to_string()];
+            let mut fw = HashMap::new();
+            let mut infra = HashMap::new();
+            detect_file_indicators(&names, &mut fw, &mut infra);
+            assert_eq!(infra["Nix"].category, "infrastructure", "{indicator}");
+            assert!(fw.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_detect_bazel_workspace() {
+        for indicator in ["WORKSPACE", "WORKSPACE.bazel", "MODULE.bazel"] {
+            let names = vec![indicator.to_string()];
+            let mut fw = HashMap::new();
+            let mut infra = HashMap::new();
+            detect_file_indicators(&names, &mut fw, &mut infra);
+            assert_eq!(fw["Bazel"].category, "tool", "{indicator}");
+            assert!(infra.is_empty());
+        }
+        assert!(is_indicator_name("BUILD.bazel"));
+        assert!(!is_indicator_name("BUILD"));
+    }
+
+    #[test]
+    fn test_detect_buck() {
+        for indicator in ["BUCK", ".buckconfig"] {
+            let names = vec![indicator.to_string()];
+            let mut fw = HashMap::new();
+            let mut infra = HashMap::new();
+            detect_file_indicators(&names, &mut fw, &mut infra);
+            assert_eq!(fw["Buck"].category, "tool", "{indicator}");
+        }
+    }
+
+    #[test]
+    fn test_detect_turbo() {
+        let names = vec!["turbo.json".to_string(), "package.json".to_string()];
+        let mut fw = HashMap::new();
+        let mut infra = HashMap::new();
+        detect_file_indicators(&names, &mut fw, &mut infra);
+        assert_eq!(fw["Turborepo"].category, "tool");
+        assert_eq!(fw.len(), 1);
+    }
+
+    #[test]
+    fn test_detect_iac_indicators() {
+        let cases = [
+            ("serverless.yml", "Serverless Framework"),
+            ("serverless.yaml", "Serverless Framework"),
+            ("Pulumi.yaml", "Pulumi"),
+            ("template.yaml", "AWS SAM"),
+            ("samconfig.toml", "AWS SAM"),
+            ("cdk.json", "AWS CDK"),
+            ("Procfile", "Heroku"),
+        ];
+        for (file, expected) in cases {
+            let mut fw = HashMap::new();
+            let mut infra = HashMap::new();
+            detect_file_indicators(&[file.to_string()], &mut fw, &mut infra);
+            assert!(
+                infra.contains_key(expected),
+                "{file} should give {expected}"
+            );
+            assert_eq!(infra[expected].category, "infrastructure");
+            assert!(fw.is_empty());
+        }
+    }
+
     #[test]
     fn test_detect_framework_indicator() {
         let names = vec!["tailwind.config.js".to_string()];
@@ -98,7 +321,8 @@ mod tests {
         let mut infra = HashMap::new();
         detect_file_indicators(&names, &mut fw, &mut infra);
         assert!(fw.contains_key("Tailwind CSS"));
-        assert_eq!(fw["Tailwind CSS"], "framework");
+        assert_eq!(fw["Tailwind CSS"].category, "framework");
+        assert_eq!(fw["Tailwind CSS"].version, None);
     }
 
     #[test]
@@ -108,16 +332,57 @@ mod tests {
         let mut infra = HashMap::new();
         detect_file_indicators(&names, &mut fw, &mut infra);
         assert!(fw.contains_key("TypeScript"));
-        assert_eq!(fw["TypeScript"], "language");
+        assert_eq!(fw["TypeScript"].category, "language");
+    }
+
+    #[test]
+    fn test_detect_schema_files() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            tmp.path().join("user.proto"),
+            "// Users\nsyntax = \"proto3\";\n\npackage users.v1;\n",
+        )
+        .unwrap();
+        let files: BTreeSet<PathBuf> = ["user.proto", "schema.graphql", "add.wat", "main.go"]
+            .iter()
+            .map(PathBuf::from)
+            .filter(|p| is_schema_file(p))
+            .collect();
+        assert_eq!(files.len(), 3);
+
+        let mut fw = HashMap::new();
+        detect_schema_files(tmp.path(), &files, &mut fw);
+        assert_eq!(fw["Protocol Buffers"].version.as_deref(), Some("proto3"));
+        assert_eq!(fw["Protocol Buffers"].category, "tool");
+        assert_eq!(fw["GraphQL"].version, None);
+        assert_eq!(fw["WebAssembly Text"].category, "tool");
+        assert_eq!(fw.len(), 3);
+    }
+
+    #[test]
+    fn test_proto_syntax() {
+        assert_eq!(proto_syntax("syntax='proto2';").as_deref(), Some("proto2"));
+        assert_eq!(proto_syntax("message Empty {}\n"), None);
     }
 
     #[test]
     fn test_sorted_entries() {
         let mut map = HashMap::new();
-        map.insert("Zebra".to_string(), "framework".to_string());
-        map.insert("Alpha".to_string(), "tool".to_string());
+        insert_signal(&mut map, SignalEntry::new("Zebra", "framework", None));
+        insert_signal(&mut map, SignalEntry::new("Alpha", "tool", None));
         let entries = into_sorted_entries(&map);
         assert_eq!(entries[0].name, "Alpha");
         assert_eq!(entries[1].name, "Zebra");
     }
+
+    #[test]
+    fn test_insert_signal_keeps_version() {
+        let mut map = HashMap::new();
+        insert_signal(
+            &mut map,
+            SignalEntry::new("TypeScript", "language", Some("^5.0.0".to_string())),
+        );
+        insert_signal(&mut map, SignalEntry::new("TypeScript", "language", None));
+        assert_eq!(map["TypeScript"].version.as_deref(), Some("^5.0.0"));
+    }
 }
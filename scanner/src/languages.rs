@@ -1,11 +1,26 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
 use std::path::Path;
 
 use crate::output::LanguageEntry;
+use crate::scan::ScanOptions;
 
-/// Map file extensions to language names.
+/// Lowercase `ext` for matching, borrowing it when it already is.
+fn normalize_extension(ext: &str) -> Cow<'_, str> {
+    if ext.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(ext.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(ext)
+    }
+}
+
+/// Map file extensions to language names. Matching ignores case, so
+/// `main.PY` is Python; this also covers R, whose scripts are
+/// conventionally `.R`.
 pub fn extension_to_language(ext: &str) -> Option<&'static str> {
-    match ext {
+    match normalize_extension(ext).as_ref() {
         "py" => Some("Python"),
         "js" => Some("JavaScript"),
         "jsx" => Some("JavaScript"),
@@ -24,7 +39,7 @@ pub fn extension_to_language(ext: &str) -> Option<&'static str> {
         "cs" => Some("C#"),
         "php" => Some("PHP"),
         "scala" => Some("Scala"),
-        "r" | "R" => Some("R"),
+        "r" => Some("R"),
         "dart" => Some("Dart"),
         "lua" => Some("Lua"),
         "ex" | "exs" => Some("Elixir"),
@@ -43,14 +58,219 @@ pub fn extension_to_language(ext: &str) -> Option<&'static str> {
         "sql" => Some("SQL"),
         "sh" | "bash" | "zsh" => Some("Shell"),
         "ps1" => Some("PowerShell"),
+        // `.m` is shared with MATLAB; `record_language` checks the content
+        // and only keeps Objective-C when MATLAB markers are absent.
+        "m" | "mm" => Some("Objective-C"),
+        "vala" => Some("Vala"),
+        "nim" => Some("Nim"),
+        "cr" => Some("Crystal"),
+        "fs" | "fsx" => Some("F#"),
+        "groovy" => Some("Groovy"),
+        "pl" | "pm" => Some("Perl"),
+        "asm" | "s" => Some("Assembly"),
+        "wat" => Some("WebAssembly"),
+        _ => None,
+    }
+}
+
+/// Where a file's bytes come from when weighing it.
+enum Contents<'a> {
+    /// Read from disk at the file's path.
+    Disk,
+    /// Provided by the caller; `None` when it has no contents to give.
+    Memory(Option<&'a [u8]>),
+}
+
+impl Contents<'_> {
+    /// The first `limit` bytes of the file at `path`, or all of it with
+    /// `None`. `None` if the bytes can't be had.
+    fn read(&self, path: &Path, limit: Option<u64>) -> Option<Cow<'_, [u8]>> {
+        match self {
+            Contents::Disk => read_prefix(path, limit.unwrap_or(u64::MAX))
+                .ok()
+                .map(Cow::Owned),
+            Contents::Memory(bytes) => {
+                let bytes = (*bytes)?;
+                let end = limit.map_or(bytes.len(), |l| bytes.len().min(l as usize));
+                Some(Cow::Borrowed(&bytes[..end]))
+            }
+        }
+    }
+
+    /// Lines in the file at `path`, streamed from disk rather than read
+    /// whole. `None` if the file can't be read.
+    fn count_lines(&self, path: &Path) -> Option<u64> {
+        match self {
+            Contents::Disk => count_newlines_streaming(path).ok(),
+            Contents::Memory(bytes) => bytes.map(count_lines_in),
+        }
+    }
+}
+
+/// Buffer size for [`count_newlines_streaming`].
+const STREAM_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Read at most `max` bytes from the start of the file at `path`. The
+/// buffer grows with what is actually read, so a large `max` on a small
+/// file costs nothing extra.
+pub fn read_prefix(path: &Path, max: u64) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    fs::File::open(path)?.take(max).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Count lines in the file at `path` the way [`count_lines`] does,
+/// reading it through a fixed-size buffer so memory use doesn't grow with
+/// the file.
+pub fn count_newlines_streaming(path: &Path) -> io::Result<u64> {
+    count_lines_in_reader(fs::File::open(path)?)
+}
+
+fn count_lines_in_reader(mut reader: impl Read) -> io::Result<u64> {
+    let mut buf = vec![0; STREAM_BUFFER_BYTES];
+    let mut newlines = 0;
+    let mut last = None;
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        newlines += buf[..n].iter().filter(|&&b| b == b'\n').count() as u64;
+        last = Some(buf[n - 1]);
+    }
+    Ok(match last {
+        Some(b'\n') | None => newlines,
+        Some(_) => newlines + 1,
+    })
+}
+
+/// How much of a `.m` file to read when telling Objective-C from MATLAB.
+const M_FILE_PEEK_BYTES: u64 = 4096;
+
+/// Tell an Objective-C `.m` file from a MATLAB one by its first lines.
+/// Preprocessor and `@interface`-style directives mean Objective-C; `%`
+/// comments or `function`/`classdef` openers mean MATLAB. Anything else
+/// stays Objective-C.
+fn m_file_language(head: &[u8]) -> &'static str {
+    let head = String::from_utf8_lossy(head);
+    for line in head.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if [
+            "#import",
+            "#include",
+            "@interface",
+            "@implementation",
+            "@protocol",
+        ]
+        .iter()
+        .any(|marker| line.starts_with(marker))
+        {
+            return "Objective-C";
+        }
+        if line.starts_with('%') || line.starts_with("function") || line.starts_with("classdef") {
+            return "MATLAB";
+        }
+    }
+    "Objective-C"
+}
+
+/// How much of an extensionless file to read when looking for a shebang.
+const SHEBANG_PEEK_BYTES: u64 = 128;
+
+/// Map a shebang interpreter (e.g. `python3`, `bash`) to a language name.
+fn interpreter_to_language(interpreter: &str) -> Option<&'static str> {
+    let base = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match base {
+        "python" => Some("Python"),
+        "bash" | "sh" | "zsh" | "dash" | "ksh" => Some("Shell"),
+        "node" | "nodejs" => Some("JavaScript"),
+        "ruby" => Some("Ruby"),
+        "perl" => Some("Perl"),
         _ => None,
     }
 }
 
-/// Returns true for binary file extensions that should be skipped.
+/// Detect a language from a `#!` line, e.g. `#!/usr/bin/env python3`.
+/// Only the first `SHEBANG_PEEK_BYTES` of the file are read.
+pub fn shebang_language(path: &Path) -> Option<&'static str> {
+    shebang_in(&Contents::Disk.read(path, Some(SHEBANG_PEEK_BYTES))?)
+}
+
+fn shebang_in(head: &[u8]) -> Option<&'static str> {
+    let line = head.strip_prefix(b"#!")?.split(|&b| b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?;
+
+    let mut tokens = line.split_whitespace();
+    let mut program = tokens.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = tokens.find(|t| !t.starts_with('-'))?;
+    }
+    interpreter_to_language(program)
+}
+
+/// How much of a file to sniff when checking whether it is binary.
+const BINARY_SNIFF_BYTES: u64 = 8 * 1024;
+
+/// Styling and markup languages, which never count as a repo's primary
+/// language however much of it they make up, and which `code_only` scans
+/// leave out entirely.
+pub const MARKUP_LANGUAGES: &[&str] = &["HTML", "CSS", "SCSS", "Less"];
+
+/// Values of `LanguageEntry::category`, in the order they are checked.
+pub const LANGUAGE_CATEGORIES: &[&str] = &["programming", "markup", "styling", "data", "shell"];
+
+/// Classify a language for `LanguageEntry::category`: `markup`,
+/// `styling`, `data` (query and data languages) or `shell`, and
+/// `programming` for everything else.
+pub fn language_category(language: &str) -> &'static str {
+    match language {
+        "HTML" => "markup",
+        "CSS" | "SCSS" | "Less" => "styling",
+        "SQL" => "data",
+        "Shell" | "PowerShell" => "shell",
+        _ => "programming",
+    }
+}
+
+/// Share of the total a language needs to be the primary language.
+const PRIMARY_LANGUAGE_MIN_PERCENTAGE: f64 = 40.0;
+
+/// Returns true if the start of a file looks binary: it contains a NUL byte,
+/// or more than 30% of it is not valid UTF-8. Unreadable files return false
+/// and are left to the caller.
+pub fn looks_binary(path: &Path) -> bool {
+    Contents::Disk
+        .read(path, Some(BINARY_SNIFF_BYTES))
+        .is_some_and(|head| looks_binary_in(&head))
+}
+
+fn looks_binary_in(head: &[u8]) -> bool {
+    if head.is_empty() {
+        return false;
+    }
+    if head.contains(&0) {
+        return true;
+    }
+
+    let mut invalid = 0;
+    let mut rest = head;
+    while let Err(e) = std::str::from_utf8(rest) {
+        // A sequence cut off by the sniff window has no error length.
+        let Some(len) = e.error_len() else {
+            break;
+        };
+        invalid += len;
+        rest = &rest[e.valid_up_to() + len..];
+    }
+    invalid * 10 > head.len() * 3
+}
+
+/// Returns true for binary file extensions that should be skipped,
+/// ignoring case.
 pub fn is_binary_extension(ext: &str) -> bool {
     matches!(
-        ext,
+        normalize_extension(ext).as_ref(),
         "png"
             | "jpg"
             | "jpeg"
@@ -99,13 +319,268 @@ pub fn is_binary_extension(ext: &str) -> bool {
     )
 }
 
-/// Accumulate bytes per language from a file path and its metadata size.
-pub fn record_language(path: &Path, size: u64, bytes_by_lang: &mut HashMap<String, u64>) {
-    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-        if let Some(lang) = extension_to_language(ext) {
-            *bytes_by_lang.entry(lang.to_string()).or_insert(0) += size;
+/// Count lines in a file. A final line without a trailing newline still counts.
+pub fn count_lines(path: &Path) -> u64 {
+    count_newlines_streaming(path).unwrap_or(0)
+}
+
+fn count_lines_in(content: &[u8]) -> u64 {
+    let newlines = content.iter().filter(|&&b| b == b'\n').count() as u64;
+    match content.last() {
+        Some(&b'\n') | None => newlines,
+        Some(_) => newlines + 1,
+    }
+}
+
+/// Map a Jupyter kernel language (`python`, `R`, `julia`, ...) to a
+/// language name.
+fn kernel_language(name: &str) -> Option<&'static str> {
+    match name.to_lowercase().as_str() {
+        "python" | "python3" => Some("Python"),
+        "r" => Some("R"),
+        "julia" => Some("Julia"),
+        "scala" => Some("Scala"),
+        "javascript" => Some("JavaScript"),
+        "typescript" => Some("TypeScript"),
+        "rust" => Some("Rust"),
+        "go" => Some("Go"),
+        "ruby" => Some("Ruby"),
+        "java" => Some("Java"),
+        "kotlin" => Some("Kotlin"),
+        "c++" | "cpp" => Some("C++"),
+        "c#" | "csharp" => Some("C#"),
+        "f#" | "fsharp" => Some("F#"),
+        "haskell" => Some("Haskell"),
+        "bash" | "sh" => Some("Shell"),
+        "sql" => Some("SQL"),
+        _ => None,
+    }
+}
+
+/// Map a Markdown code-fence tag (```` ```python ````, ```` ```ts ````, ...)
+/// to a language name, accepting both language names and extensions.
+pub fn fence_language(tag: &str) -> Option<&'static str> {
+    kernel_language(tag).or_else(|| extension_to_language(tag))
+}
+
+/// Attribute a Jupyter notebook to its kernel language, weighted by the
+/// source of its code cells only (markdown cells and outputs are ignored).
+/// The kernel comes from `metadata.kernelspec.language`, falling back to
+/// `metadata.language_info.name`.
+fn notebook_language(content: &[u8], count_lines: bool) -> Option<(&'static str, u64)> {
+    let notebook: serde_json::Value = serde_json::from_slice(content).ok()?;
+    let metadata = &notebook["metadata"];
+    let lang = metadata["kernelspec"]["language"]
+        .as_str()
+        .or_else(|| metadata["language_info"]["name"].as_str())
+        .and_then(kernel_language)?;
+
+    let mut weight = 0;
+    let cells = notebook["cells"].as_array()?;
+    for cell in cells.iter().filter(|c| c["cell_type"] == "code") {
+        // `source` is either one string or a list of line strings.
+        let source = match &cell["source"] {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Array(lines) => lines.iter().filter_map(|l| l.as_str()).collect(),
+            _ => continue,
+        };
+        weight += if count_lines {
+            source.lines().count() as u64
+        } else {
+            source.len() as u64
+        };
+    }
+    (weight > 0).then_some((lang, weight))
+}
+
+/// Language of a `<script>` or `<style>` block, from its `lang` attribute.
+fn sfc_block_language(tag: &str, attrs: &str) -> &'static str {
+    let attrs = attrs.to_lowercase();
+    let lang = attrs.split_once("lang=").map_or("", |(_, rest)| {
+        let rest = rest.trim_start_matches(['"', '\'']);
+        rest.split(|c: char| c == '"' || c == '\'' || c.is_whitespace())
+            .next()
+            .unwrap_or("")
+    });
+    match (tag, lang) {
+        ("script", "ts" | "typescript" | "tsx") => "TypeScript",
+        ("script", _) => "JavaScript",
+        ("style", "scss" | "sass") => "SCSS",
+        ("style", "less") => "Less",
+        _ => "CSS",
+    }
+}
+
+/// Split a Vue/Svelte single-file component into `(language, text)` parts:
+/// the bodies of `<script>` blocks as JavaScript or TypeScript, `<style>`
+/// blocks as CSS, SCSS or Less, and everything else (template and the
+/// block tags themselves) as `component`.
+pub fn split_sfc<'a>(source: &'a str, component: &'static str) -> Vec<(&'static str, &'a str)> {
+    let mut parts = Vec::new();
+    let mut rest = source;
+    loop {
+        let next = ["script", "style"]
+            .into_iter()
+            .filter_map(|tag| rest.find(&format!("<{tag}")).map(|pos| (pos, tag)))
+            .min();
+        let Some((start, tag)) = next else {
+            break;
+        };
+        let Some(open_len) = rest[start..].find('>') else {
+            break;
+        };
+        let body_start = start + open_len + 1;
+        let Some(body_len) = rest[body_start..].find(&format!("</{tag}>")) else {
+            break;
+        };
+        let attrs = &rest[start + 1 + tag.len()..start + open_len];
+        parts.push((component, &rest[..body_start]));
+        parts.push((
+            sfc_block_language(tag, attrs),
+            &rest[body_start..body_start + body_len],
+        ));
+        rest = &rest[body_start + body_len..];
+    }
+    parts.push((component, rest));
+    parts.retain(|(_, text)| !text.is_empty());
+    parts
+}
+
+/// Weigh each embedded language of a `.vue`/`.svelte` file.
+fn sfc_weights(
+    source: &str,
+    component: &'static str,
+    count_lines: bool,
+) -> Vec<(&'static str, u64)> {
+    let mut weights: Vec<(&'static str, u64)> = Vec::new();
+    for (lang, text) in split_sfc(source, component) {
+        let weight = if count_lines {
+            text.lines().filter(|l| !l.trim().is_empty()).count() as u64
+        } else {
+            text.len() as u64
+        };
+        match weights.iter_mut().find(|(l, _)| *l == lang) {
+            Some((_, total)) => *total += weight,
+            None => weights.push((lang, weight)),
         }
     }
+    weights.retain(|&(_, weight)| weight > 0);
+    weights
+}
+
+/// Accumulate weight per language from a file path and its metadata size.
+/// The weight is the byte size, or the line count with `options.count_lines`.
+/// Files over `options.max_file_size` are not counted.
+/// Extensionless files are attributed by shebang, if they have one, and
+/// files whose content looks binary are skipped despite their extension.
+/// Notebooks count only the code in their cells, and with
+/// `options.split_sfc` Vue/Svelte components are split into their parts.
+/// Returns the languages and weights recorded (usually one, possibly none).
+pub fn record_language(
+    path: &Path,
+    size: u64,
+    options: &ScanOptions,
+    bytes_by_lang: &mut HashMap<String, u64>,
+) -> Vec<(&'static str, u64)> {
+    let weights = file_weights(path, size, &Contents::Disk, options);
+    add_weights(bytes_by_lang, &weights);
+    weights
+}
+
+/// Like [`record_language`] for a file that isn't on disk, with its
+/// contents if the caller has them. Without contents only the extension is
+/// used: extensionless files and notebooks are skipped, nothing is sniffed
+/// for binary, and with `options.count_lines` the file isn't counted.
+pub fn record_language_in_memory(
+    path: &Path,
+    size: u64,
+    contents: Option<&[u8]>,
+    options: &ScanOptions,
+    bytes_by_lang: &mut HashMap<String, u64>,
+) -> Vec<(&'static str, u64)> {
+    let weights = file_weights(path, size, &Contents::Memory(contents), options);
+    add_weights(bytes_by_lang, &weights);
+    weights
+}
+
+fn add_weights(bytes_by_lang: &mut HashMap<String, u64>, weights: &[(&'static str, u64)]) {
+    for &(lang, weight) in weights {
+        *bytes_by_lang.entry(lang.to_string()).or_insert(0) += weight;
+    }
+}
+
+fn file_weights(
+    path: &Path,
+    size: u64,
+    contents: &Contents,
+    options: &ScanOptions,
+) -> Vec<(&'static str, u64)> {
+    if size > options.max_file_size {
+        return Vec::new();
+    }
+    if path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("ipynb"))
+    {
+        return contents
+            .read(path, None)
+            .and_then(|content| notebook_language(&content, options.count_lines))
+            .into_iter()
+            .collect();
+    }
+    let lang = match path.extension() {
+        Some(ext) => ext.to_str().and_then(extension_to_language),
+        None => contents
+            .read(path, Some(SHEBANG_PEEK_BYTES))
+            .and_then(|head| shebang_in(&head)),
+    };
+    let lang = match lang {
+        Some("Objective-C")
+            if path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("m")) =>
+        {
+            contents
+                .read(path, Some(M_FILE_PEEK_BYTES))
+                .map_or("Objective-C", |head| m_file_language(&head))
+        }
+        Some(lang) => lang,
+        None => return Vec::new(),
+    };
+    if contents
+        .read(path, Some(BINARY_SNIFF_BYTES))
+        .is_some_and(|head| looks_binary_in(&head))
+    {
+        return Vec::new();
+    }
+    if options.split_sfc && matches!(lang, "Vue" | "Svelte") {
+        if let Some(source) = contents.read(path, None) {
+            return sfc_weights(&String::from_utf8_lossy(&source), lang, options.count_lines);
+        }
+    }
+    if !options.count_lines {
+        return vec![(lang, size)];
+    }
+    match contents.count_lines(path) {
+        Some(lines) => vec![(lang, lines)],
+        None => Vec::new(),
+    }
+}
+
+/// Drop `MARKUP_LANGUAGES`, so `build_language_list` renormalizes the
+/// rest to 100 as if the repo held only code.
+pub fn drop_markup_languages(bytes_by_lang: &mut HashMap<String, u64>) {
+    bytes_by_lang.retain(|lang, _| !MARKUP_LANGUAGES.contains(&lang.as_str()));
+}
+
+/// Drop languages whose share of the total is below `min_percentage`, so
+/// `build_language_list` renormalizes the rest to 100 from exact counts.
+pub fn drop_minor_languages(bytes_by_lang: &mut HashMap<String, u64>, min_percentage: f64) {
+    let total: u64 = bytes_by_lang.values().sum();
+    if total == 0 || min_percentage <= 0.0 {
+        return;
+    }
+    bytes_by_lang.retain(|_, &mut bytes| bytes as f64 * 100.0 / total as f64 >= min_percentage);
 }
 
 /// Convert accumulated byte counts into sorted `LanguageEntry` list.
@@ -114,18 +589,55 @@ pub fn build_language_list(bytes_by_lang: &HashMap<String, u64>) -> Vec<Language
     if total == 0 {
         return Vec::new();
     }
-    let mut entries: Vec<LanguageEntry> = bytes_by_lang
+
+    // Work in tenths of a percent. Floor every share, then hand the tenths
+    // lost to flooring to the languages with the largest remainders
+    // (Hamilton's method) so the list always adds up to exactly 100.0.
+    let mut shares: Vec<(&String, u64, f64)> = bytes_by_lang
         .iter()
-        .map(|(name, &bytes)| LanguageEntry {
+        .map(|(name, &bytes)| {
+            let exact = bytes as f64 * 1000.0 / total as f64;
+            (name, exact.floor() as u64, exact.fract())
+        })
+        .collect();
+    let assigned: u64 = shares.iter().map(|(_, tenths, _)| tenths).sum();
+    shares.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+    for share in shares
+        .iter_mut()
+        .take(1000usize.saturating_sub(assigned as usize))
+    {
+        share.1 += 1;
+    }
+
+    let mut entries: Vec<LanguageEntry> = shares
+        .into_iter()
+        .map(|(name, tenths, _)| LanguageEntry {
             name: name.clone(),
-            category: "language".to_string(),
-            percentage: ((bytes as f64 / total as f64) * 1000.0).round() / 10.0,
+            category: language_category(name).to_string(),
+            percentage: tenths as f64 / 10.0,
         })
         .collect();
-    entries.sort_by(|a, b| b.percentage.partial_cmp(&a.percentage).unwrap());
+    // Largest first; equal percentages in name order so output is stable
+    // across runs despite HashMap iteration order.
+    entries.sort_by(|a, b| {
+        b.percentage
+            .total_cmp(&a.percentage)
+            .then_with(|| a.name.cmp(&b.name))
+    });
     entries
 }
 
+/// The language a repo is mostly written in: the largest language outside
+/// `MARKUP_LANGUAGES`, if it makes up more than 40% of the total. Expects
+/// `languages` sorted as [`build_language_list`] returns them.
+pub fn primary_language(languages: &[LanguageEntry]) -> Option<String> {
+    languages
+        .iter()
+        .find(|l| !MARKUP_LANGUAGES.contains(&l.name.as_str()))
+        .filter(|l| l.percentage > PRIMARY_LANGUAGE_MIN_PERCENTAGE)
+        .map(|l| l.name.clone())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,6 +652,155 @@ mod tests {
         assert_eq!(extension_to_language("unknown"), None);
     }
 
+    #[test]
+    fn test_extension_mapping_ignores_case() {
+        assert_eq!(extension_to_language("PY"), Some("Python"));
+        assert_eq!(extension_to_language("Rs"), Some("Rust"));
+        assert_eq!(extension_to_language("R"), Some("R"));
+        assert_eq!(extension_to_language("r"), Some("R"));
+        assert!(is_binary_extension("PNG"));
+    }
+
+    #[test]
+    fn test_extension_mapping_additions() {
+        assert_eq!(extension_to_language("m"), Some("Objective-C"));
+        assert_eq!(extension_to_language("mm"), Some("Objective-C"));
+        assert_eq!(extension_to_language("vala"), Some("Vala"));
+        assert_eq!(extension_to_language("nim"), Some("Nim"));
+        assert_eq!(extension_to_language("cr"), Some("Crystal"));
+        assert_eq!(extension_to_language("fs"), Some("F#"));
+        assert_eq!(extension_to_language("fsx"), Some("F#"));
+        assert_eq!(extension_to_language("groovy"), Some("Groovy"));
+        assert_eq!(extension_to_language("pl"), Some("Perl"));
+        assert_eq!(extension_to_language("pm"), Some("Perl"));
+        assert_eq!(extension_to_language("wat"), Some("WebAssembly"));
+        assert_eq!(extension_to_language("asm"), Some("Assembly"));
+        assert_eq!(extension_to_language("s"), Some("Assembly"));
+    }
+
+    #[test]
+    fn test_notebook_counts_code_cells() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("analysis.ipynb");
+        let notebook = serde_json::json!({
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n", "Some notes"]},
+                {"cell_type": "code", "source": ["import pandas as pd\n", "df = pd.DataFrame()"], "outputs": []},
+                {"cell_type": "code", "source": "print(df)", "outputs": []}
+            ],
+            "metadata": {"kernelspec": {"name": "python3", "language": "python"}},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        });
+        fs::write(&path, notebook.to_string()).unwrap();
+
+        let mut bytes = HashMap::new();
+        let recorded = record_language(&path, 400, &ScanOptions::default(), &mut bytes);
+        let code_bytes = ("import pandas as pd\n".len()
+            + "df = pd.DataFrame()".len()
+            + "print(df)".len()) as u64;
+        assert_eq!(recorded, vec![("Python", code_bytes)]);
+        assert_eq!(bytes["Python"], code_bytes);
+    }
+
+    #[test]
+    fn test_notebook_language_info_fallback() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("model.ipynb");
+        let notebook = serde_json::json!({
+            "cells": [{"cell_type": "code", "source": "x <- 1"}],
+            "metadata": {"language_info": {"name": "R"}}
+        });
+        fs::write(&path, notebook.to_string()).unwrap();
+
+        let mut bytes = HashMap::new();
+        let recorded = record_language(&path, 100, &ScanOptions::default(), &mut bytes);
+        assert_eq!(recorded, vec![("R", 6)]);
+    }
+
+    #[test]
+    fn test_split_sfc_vue() {
+        let source = r#"<template>
+  <p>{{ msg }}</p>
+</template>
+
+<script setup lang="ts">
+const msg: string = "hi";
+</script>
+
+<style scoped>
+p { color: red; }
+</style>
+"#;
+        let parts = split_sfc(source, "Vue");
+        let script: Vec<&str> = parts
+            .iter()
+            .filter(|(l, _)| *l == "TypeScript")
+            .map(|(_, t)| *t)
+            .collect();
+        assert_eq!(script, vec!["\nconst msg: string = \"hi\";\n"]);
+        let style: Vec<&str> = parts
+            .iter()
+            .filter(|(l, _)| *l == "CSS")
+            .map(|(_, t)| *t)
+            .collect();
+        assert_eq!(style, vec!["\np { color: red; }\n"]);
+        // Every byte lands in exactly one part.
+        let total: usize = parts.iter().map(|(_, t)| t.len()).sum();
+        assert_eq!(total, source.len());
+    }
+
+    #[test]
+    fn test_record_sfc_only_with_flag() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("App.vue");
+        let source = "<template><p/></template>\n<script lang=\"ts\">let a = 1;</script>\n<style lang=\"scss\">p { b: c }</style>\n";
+        fs::write(&path, source).unwrap();
+        let size = source.len() as u64;
+
+        let mut bytes = HashMap::new();
+        let recorded = record_language(&path, size, &ScanOptions::default(), &mut bytes);
+        assert_eq!(recorded, vec![("Vue", size)]);
+
+        let options = ScanOptions {
+            split_sfc: true,
+            ..Default::default()
+        };
+        let mut bytes = HashMap::new();
+        record_language(&path, size, &options, &mut bytes);
+        assert_eq!(bytes["TypeScript"], "let a = 1;".len() as u64);
+        assert_eq!(bytes["SCSS"], "p { b: c }".len() as u64);
+        assert_eq!(bytes.values().sum::<u64>(), size);
+    }
+
+    #[test]
+    fn test_m_file_disambiguation() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let objc = tmp.path().join("AppDelegate.m");
+        fs::write(
+            &objc,
+            "#import \"AppDelegate.h\"\n\n@implementation AppDelegate\n@end\n",
+        )
+        .unwrap();
+        let matlab = tmp.path().join("solve.m");
+        fs::write(
+            &matlab,
+            "% Solve the system\nfunction x = solve(A, b)\n  x = A \\ b;\nend\n",
+        )
+        .unwrap();
+
+        let options = ScanOptions::default();
+        let mut bytes = HashMap::new();
+        assert_eq!(
+            record_language(&objc, 50, &options, &mut bytes),
+            vec![("Objective-C", 50)]
+        );
+        assert_eq!(
+            record_language(&matlab, 50, &options, &mut bytes),
+            vec![("MATLAB", 50)]
+        );
+    }
+
     #[test]
     fn test_binary_detection() {
         assert!(is_binary_extension("png"));
@@ -148,6 +809,108 @@ mod tests {
         assert!(!is_binary_extension("rs"));
     }
 
+    #[test]
+    fn test_looks_binary() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("data.py");
+
+        fs::write(&path, "print('héllo')\n").unwrap();
+        assert!(!looks_binary(&path));
+
+        fs::write(&path, b"print('x')\0\0\x01\x02").unwrap();
+        assert!(looks_binary(&path));
+
+        fs::write(&path, [0xffu8, 0xfe, 0xc0, 0x80, b'a', 0x90, 0xa0, 0xb0]).unwrap();
+        assert!(looks_binary(&path));
+
+        fs::write(&path, "").unwrap();
+        assert!(!looks_binary(&path));
+    }
+
+    #[test]
+    fn test_record_skips_binary_content() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("payload.py");
+        fs::write(&path, b"import os\n\0\0\0\0binary blob").unwrap();
+
+        let mut bytes = HashMap::new();
+        assert!(record_language(&path, 25, &ScanOptions::default(), &mut bytes).is_empty());
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_shebang_detection() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("deploy");
+        fs::write(&path, "#!/usr/bin/env python\nprint('hi')\n").unwrap();
+        assert_eq!(shebang_language(&path), Some("Python"));
+
+        fs::write(&path, "#!/bin/bash\necho hi\n").unwrap();
+        assert_eq!(shebang_language(&path), Some("Shell"));
+
+        fs::write(&path, "#!/usr/bin/env -S node --no-warnings\n").unwrap();
+        assert_eq!(shebang_language(&path), Some("JavaScript"));
+
+        fs::write(&path, "#!/usr/bin/python3.12\n").unwrap();
+        assert_eq!(shebang_language(&path), Some("Python"));
+
+        fs::write(&path, "just some text\n").unwrap();
+        assert_eq!(shebang_language(&path), None);
+    }
+
+    #[test]
+    fn test_record_extensionless_script() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let script = tmp.path().join("deploy");
+        fs::write(&script, "#!/usr/bin/env python\nprint('hi')\n").unwrap();
+        let notes = tmp.path().join("NOTES");
+        fs::write(&notes, "plain text").unwrap();
+
+        let mut bytes = HashMap::new();
+        let options = ScanOptions::default();
+        record_language(&script, 33, &options, &mut bytes);
+        record_language(&notes, 10, &options, &mut bytes);
+        assert_eq!(bytes.len(), 1);
+        assert_eq!(bytes["Python"], 33);
+    }
+
+    #[test]
+    fn test_count_lines() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("a.py");
+        fs::write(&path, "a\nb\nc").unwrap();
+        assert_eq!(count_lines(&path), 3);
+        fs::write(&path, "a\nb\n").unwrap();
+        assert_eq!(count_lines(&path), 2);
+        fs::write(&path, "").unwrap();
+        assert_eq!(count_lines(&path), 0);
+    }
+
+    #[test]
+    fn test_count_lines_large_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("big.py");
+        // ~6 MB, with lines straddling every buffer boundary.
+        let line = "x = 'the quick brown fox'  # padding to 50 bytes.\n";
+        assert_eq!(line.len(), 50);
+        fs::write(&path, line.repeat(120_000) + "tail").unwrap();
+        assert_eq!(count_newlines_streaming(&path).unwrap(), 120_001);
+
+        // 16 MB that never exists in memory: only the buffer is allocated.
+        let reader = io::repeat(b'\n').take(16 * 1024 * 1024);
+        assert_eq!(count_lines_in_reader(reader).unwrap(), 16 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_read_prefix() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("a.txt");
+        fs::write(&path, "abcdef").unwrap();
+        assert_eq!(read_prefix(&path, 3).unwrap(), b"abc");
+        assert_eq!(read_prefix(&path, u64::MAX).unwrap(), b"abcdef");
+        assert!(read_prefix(&tmp.path().join("missing"), 3).is_err());
+    }
+
     #[test]
     fn test_build_language_list() {
         let mut bytes = HashMap::new();
@@ -162,10 +925,102 @@ mod tests {
         assert_eq!(list[1].percentage, 30.0);
     }
 
+    #[test]
+    fn test_language_category() {
+        assert_eq!(language_category("CSS"), "styling");
+        assert_eq!(language_category("SCSS"), "styling");
+        assert_eq!(language_category("HTML"), "markup");
+        assert_eq!(language_category("SQL"), "data");
+        assert_eq!(language_category("Shell"), "shell");
+        assert_eq!(language_category("Rust"), "programming");
+        assert_eq!(language_category("Vue"), "programming");
+        for lang in MARKUP_LANGUAGES {
+            assert_ne!(language_category(lang), "programming", "{lang}");
+        }
+
+        let bytes = HashMap::from([("Rust".to_string(), 300), ("CSS".to_string(), 100)]);
+        let list = build_language_list(&bytes);
+        assert_eq!(list[0].category, "programming");
+        assert_eq!(list[1].category, "styling");
+        assert!(list
+            .iter()
+            .all(|l| LANGUAGE_CATEGORIES.contains(&l.category.as_str())));
+    }
+
+    #[test]
+    fn test_primary_language() {
+        let python_only = HashMap::from([("Python".to_string(), 500)]);
+        assert_eq!(
+            primary_language(&build_language_list(&python_only)),
+            Some("Python".to_string())
+        );
+
+        let markup_heavy = HashMap::from([
+            ("HTML".to_string(), 500),
+            ("CSS".to_string(), 200),
+            ("JavaScript".to_string(), 300),
+        ]);
+        assert_eq!(primary_language(&build_language_list(&markup_heavy)), None);
+
+        let even_markup = HashMap::from([("HTML".to_string(), 100), ("CSS".to_string(), 100)]);
+        assert_eq!(primary_language(&build_language_list(&even_markup)), None);
+        assert_eq!(primary_language(&[]), None);
+    }
+
     #[test]
     fn test_build_language_list_empty() {
         let bytes = HashMap::new();
         let list = build_language_list(&bytes);
         assert!(list.is_empty());
     }
+
+    #[test]
+    fn test_build_language_list_ties_by_name() {
+        let mut bytes = HashMap::new();
+        for name in ["Rust", "Go", "TypeScript", "C"] {
+            bytes.insert(name.to_string(), 250);
+        }
+        bytes.insert("Python".to_string(), 1000);
+
+        let names: Vec<String> = build_language_list(&bytes)
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, vec!["Python", "C", "Go", "Rust", "TypeScript"]);
+    }
+
+    #[test]
+    fn test_percentages_sum_to_100() {
+        let mut bytes = HashMap::new();
+        bytes.insert("Python".to_string(), 100);
+        bytes.insert("Rust".to_string(), 100);
+        bytes.insert("Go".to_string(), 100);
+        let entries = build_language_list(&bytes);
+
+        // Summing the f64s directly can land a hair off 100.0, so compare
+        // in whole tenths of a percent.
+        let tenths: u64 = entries
+            .iter()
+            .map(|e| (e.percentage * 10.0).round() as u64)
+            .sum();
+        assert_eq!(tenths, 1000);
+        assert_eq!(entries[0].percentage, 33.4);
+        assert!(entries[0].percentage >= entries[1].percentage);
+        assert!(entries[1].percentage >= entries[2].percentage);
+    }
+
+    #[test]
+    fn test_drop_minor_languages_renormalizes() {
+        let mut bytes = HashMap::new();
+        bytes.insert("Python".to_string(), 600);
+        bytes.insert("Rust".to_string(), 395);
+        bytes.insert("Shell".to_string(), 5);
+        drop_minor_languages(&mut bytes, 1.0);
+        assert!(!bytes.contains_key("Shell"));
+
+        let entries = build_language_list(&bytes);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].percentage, 60.3);
+        assert_eq!(entries[1].percentage, 39.7);
+    }
 }
@@ -1,9 +1,19 @@
+pub mod cache;
 pub mod dependencies;
+pub mod diff;
+pub mod files;
 pub mod frameworks;
 pub mod languages;
 pub mod output;
+pub mod remote;
+pub mod rules;
 pub mod scan;
 pub mod structures;
 
 pub use output::ScanResult;
-pub use scan::{scan_directories, scan_directory};
+pub use scan::{
+    build_globset, build_ignore, scan_directories, scan_directories_with, scan_directory,
+    scan_directory_raw, scan_directory_raw_with, scan_directory_with, scan_from_entries,
+    scan_from_entries_with, try_scan_directories_with, PathFilter, RawScan, ScanOptions,
+    DEFAULT_MAX_FILE_SIZE,
+};
@@ -1,9 +1,48 @@
-use std::path::PathBuf;
-use std::time::Instant;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
 
-use pb_scan::{scan_directories, scan_directory};
+use pb_scan::cache::ScanCache;
+use pb_scan::diff::diff_scans;
+use pb_scan::output::{scan_result_schema, to_csv, to_summary};
+use pb_scan::remote::shallow_clone;
+use pb_scan::rules::{load_custom_rules, CustomRule};
+use pb_scan::{
+    build_globset, build_ignore, scan_directories_with, scan_directory_with,
+    try_scan_directories_with, PathFilter, ScanOptions, ScanResult, DEFAULT_MAX_FILE_SIZE,
+};
+
+/// Quiet period after the last change before `--watch` rescans.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+/// How often `--watch` checks for Ctrl-C while waiting for events.
+const WATCH_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+    /// A few human-readable lines, for terminals and CI logs.
+    Summary,
+}
+
+/// A part of the result that `--only` can print on its own.
+#[derive(Clone, Copy, ValueEnum)]
+enum Section {
+    Languages,
+    Frameworks,
+    Structures,
+    Infra,
+}
 
 #[derive(Parser)]
 #[command(name = "pb-scan", about = "Scan local repositories for ProjectBridge")]
@@ -16,59 +55,594 @@ struct Cli {
     #[arg(long, num_args = 1..)]
     paths: Option<Vec<PathBuf>>,
 
-    /// Pretty-print JSON output.
+    /// With --paths, scan each root on its own and print one JSON object
+    /// per root (tagged with its `path`) per line, instead of merging them.
+    #[arg(long, requires = "paths", conflicts_with_all = ["pretty", "output", "watch"])]
+    per_path_ndjson: bool,
+
+    /// Shallow-clone this git URL into a temp dir, scan it, and delete the
+    /// clone afterwards. Uses the `git` CLI and its configured credentials.
+    #[arg(long = "clone", value_name = "URL", conflicts_with_all = ["paths", "watch"])]
+    clone_url: Option<String>,
+
+    /// Branch or tag to clone with --clone (default: the remote's HEAD).
+    #[arg(long = "ref", value_name = "BRANCH_OR_TAG", requires = "clone_url")]
+    git_ref: Option<String>,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = Format::Json)]
+    format: Format,
+
+    /// Pretty-print JSON output. An error with other formats, which have a
+    /// single layout.
     #[arg(long)]
     pretty: bool,
 
+    /// Print only this section of the result, as a bare JSON array.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "SECTION",
+        conflicts_with = "per_path_ndjson"
+    )]
+    only: Option<Section>,
+
+    /// Write the result to this file instead of stdout, creating parent
+    /// directories as needed.
+    #[arg(long, short, value_name = "PATH")]
+    output: Option<PathBuf>,
+
     /// Print scan stats to stderr.
     #[arg(long)]
     stats: bool,
+
+    /// Attach a `stats` object (elapsed ms, files scanned and skipped,
+    /// bytes read) to the result instead of printing stats to stderr.
+    #[arg(long)]
+    stats_json: bool,
+
+    /// Print the time spent walking, parsing dependencies, running the
+    /// other detectors and finalizing to stderr. A result reused from
+    /// `--cache` has no profile.
+    #[arg(long)]
+    profile: bool,
+
+    /// Weigh language percentages by line count instead of bytes (the default).
+    #[arg(long)]
+    lines: bool,
+
+    /// Skip paths matching a glob, relative to the scan root (repeatable).
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Ignore paths matching a gitignore-style pattern, on top of the
+    /// repo's own .gitignore (repeatable). Applies to every root.
+    #[arg(long, value_name = "PATTERN")]
+    ignore: Vec<String>,
+
+    /// Skip files with this extension as binary, on top of the built-in
+    /// list (repeatable), e.g. `--treat-binary parquet`.
+    #[arg(long, value_name = "EXT")]
+    treat_binary: Vec<String>,
+
+    /// Count files with this extension even though the built-in list calls
+    /// it binary (repeatable). Wins over --treat-binary.
+    #[arg(long, value_name = "EXT")]
+    treat_text: Vec<String>,
+
+    /// Leave files larger than this many bytes out of language percentages.
+    /// Framework and structure detection are unaffected.
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_FILE_SIZE)]
+    max_file_size: u64,
+
+    /// Only count files up to this many levels below the root.
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Drop languages below this percentage and renormalize the rest.
+    #[arg(long, value_name = "F", default_value_t = 0.0)]
+    min_percentage: f64,
+
+    /// Also scan hidden files and directories (dotfiles), except `.git`.
+    #[arg(long)]
+    include_hidden: bool,
+
+    /// Scan files even if .gitignore (or the global gitignore) excludes
+    /// them. Dependency and build dirs like node_modules are still skipped.
+    #[arg(long)]
+    no_gitignore: bool,
+
+    /// Follow symbolic links while walking (default: symlinks are not
+    /// followed). Symlink cycles are detected and skipped.
+    #[arg(long)]
+    follow_symlinks: bool,
+
+    /// Leave generated files (minified bundles, protobuf/gRPC stubs,
+    /// lockfiles) out of language percentages.
+    #[arg(long)]
+    skip_generated: bool,
+
+    /// Leave markup and styling languages (HTML, CSS, SCSS, Less) out of
+    /// the percentages and renormalize the rest.
+    #[arg(long)]
+    code_only: bool,
+
+    /// Attribute the <script> and <style> blocks of .vue/.svelte files to
+    /// their own languages (changes percentages).
+    #[arg(long)]
+    split_sfc: bool,
+
+    /// Also report the language breakdown of each top-level directory.
+    #[arg(long)]
+    per_dir: bool,
+
+    /// Only report languages: skip framework, infrastructure and structure
+    /// detection (and the manifest parsing behind it) for a faster scan.
+    #[arg(long)]
+    languages_only: bool,
+
+    /// Also detect frameworks from dependencies that contain a known name
+    /// as a word (`@myorg/react-wrapper` → React), such as forks and
+    /// wrappers. These are marked `"confidence": "low"`.
+    #[arg(long)]
+    fuzzy: bool,
+
+    /// Also apply the detection rules in this JSON file: an array of
+    /// `{"manifest", "key", "name", "category"}` objects, e.g. to report
+    /// an in-house package as a framework. `manifest` is one of
+    /// package.json, requirements.txt, Cargo.toml, composer.json or go.mod.
+    #[arg(long, value_name = "FILE")]
+    rules: Option<PathBuf>,
+
+    /// List up to N example files (the largest, as absolute paths) per
+    /// language in an `examples` field, to see why a language shows up.
+    /// N defaults to 3; give another as `--explain=N`.
+    #[arg(long, value_name = "N", num_args = 0..=1, require_equals = true, default_missing_value = "3")]
+    explain: Option<usize>,
+
+    /// Fail, listing the paths, if any file or directory can't be read
+    /// (permission denied, dangling symlink, ...) instead of leaving it out.
+    #[arg(long)]
+    strict: bool,
+
+    /// Write paths in the output (`--explain` examples, `--strict` errors)
+    /// relative to the scan root, so shared output doesn't reveal where the
+    /// repo lives (or the username in a home directory).
+    #[arg(long)]
+    redact: bool,
+
+    /// Reuse the result stored in this cache file when nothing under the
+    /// roots has changed (by file count, size and mtime) since it was
+    /// written with the same options; otherwise scan and update it.
+    #[arg(long, value_name = "PATH")]
+    cache: Option<PathBuf>,
+
+    /// Keep running and print a fresh result whenever a scanned file
+    /// changes (debounced). Ignored paths don't trigger rescans. Stop with
+    /// Ctrl-C.
+    #[arg(long)]
+    watch: bool,
+
+    /// Compare the scan against a result saved earlier (JSON) and print
+    /// what was added, removed or changed instead of the result itself.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["only", "per_path_ndjson", "watch"]
+    )]
+    baseline: Option<PathBuf>,
+
+    /// With --baseline, only report language shares that moved by more
+    /// than this many percentage points.
+    #[arg(long, value_name = "F", default_value_t = 1.0, requires = "baseline")]
+    baseline_threshold: f64,
+
+    /// Print the JSON Schema of the output and exit.
+    #[arg(long)]
+    schema: bool,
 }
 
 // Workaround: clap doesn't natively support "if --paths is given, ignore positional".
 // We handle it manually: if --paths is provided, use those; otherwise use the positional arg.
 
+fn write_output(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
 fn main() {
     let cli = Cli::parse();
-    let start = Instant::now();
 
-    let result = if let Some(ref dirs) = cli.paths {
-        let paths: Vec<&std::path::Path> = dirs.iter().map(|p| p.as_path()).collect();
+    if cli.schema {
+        let schema = scan_result_schema();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema).expect("Failed to serialize schema")
+        );
+        return;
+    }
 
-        // Validate all paths exist.
-        for p in &paths {
-            if !p.is_dir() {
-                eprintln!("Error: not a directory: {}", p.display());
+    if cli.pretty && !matches!(cli.format, Format::Json) {
+        eprintln!("Error: --pretty only applies to --format json");
+        std::process::exit(1);
+    }
+    if cli.only.is_some() && !matches!(cli.format, Format::Json) {
+        eprintln!("Error: --only only applies to --format json");
+        std::process::exit(1);
+    }
+    if cli.baseline.is_some() && !matches!(cli.format, Format::Json) {
+        eprintln!("Error: --baseline only applies to --format json");
+        std::process::exit(1);
+    }
+    if cli.per_path_ndjson && !matches!(cli.format, Format::Json) {
+        eprintln!("Error: --per-path-ndjson only applies to --format json");
+        std::process::exit(1);
+    }
+
+    let baseline: Option<ScanResult> = cli.baseline.as_ref().map(|path| {
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                eprintln!("Error: cannot read baseline {}: {e}", path.display());
                 std::process::exit(1);
-            }
+            })
+    });
+
+    let start = Instant::now();
+    let exclude = match build_globset(&cli.exclude) {
+        Ok(set) => set,
+        Err(e) => {
+            eprintln!("Error: invalid --exclude pattern: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = build_ignore(Path::new("."), &cli.ignore) {
+        eprintln!("Error: invalid --ignore pattern: {e}");
+        std::process::exit(1);
+    }
+    let custom_rules = cli.rules.as_deref().map_or_else(Vec::new, |path| {
+        load_custom_rules(path).unwrap_or_else(|e| {
+            eprintln!("Error: invalid --rules file {}: {e}", path.display());
+            std::process::exit(1);
+        })
+    });
+    let options = ScanOptions {
+        count_lines: cli.lines,
+        exclude,
+        per_directory: cli.per_dir,
+        max_file_size: cli.max_file_size,
+        max_depth: cli.max_depth,
+        min_percentage: cli.min_percentage,
+        include_hidden: cli.include_hidden,
+        respect_gitignore: !cli.no_gitignore,
+        follow_symlinks: cli.follow_symlinks,
+        ignore: cli.ignore.clone(),
+        split_sfc: cli.split_sfc,
+        skip_generated: cli.skip_generated,
+        code_only: cli.code_only,
+        collect_stats: cli.stats_json,
+        strict: cli.strict,
+        languages_only: cli.languages_only,
+        binary_extensions: cli.treat_binary.clone(),
+        text_extensions: cli.treat_text.clone(),
+        explain: cli.explain.unwrap_or(0),
+        profile: cli.profile,
+        fuzzy: cli.fuzzy,
+        custom_rules,
+    };
+
+    let cloned = cli.clone_url.as_deref().map(|url| {
+        shallow_clone(url, cli.git_ref.as_deref()).unwrap_or_else(|e| {
+            eprintln!("Error: cannot clone {url}: {e}");
+            std::process::exit(1);
+        })
+    });
+    let roots: Vec<PathBuf> = match cloned {
+        Some(ref dir) => vec![dir.path().to_path_buf()],
+        None => cli.paths.clone().unwrap_or_else(|| vec![cli.path.clone()]),
+    };
+    // Validate all paths exist.
+    for root in &roots {
+        if !root.is_dir() {
+            eprintln!("Error: not a directory: {}", root.display());
+            std::process::exit(1);
         }
+    }
 
-        scan_directories(&paths)
-    } else {
-        if !cli.path.is_dir() {
-            eprintln!("Error: not a directory: {}", cli.path.display());
+    if cli.per_path_ndjson {
+        emit_per_path(&cli, &roots, &options);
+        return;
+    }
+
+    let result = scan(&cli, &roots, &options);
+    // Remove the clone before emitting, which may exit on a write error.
+    drop(cloned);
+    if let Some(baseline) = baseline {
+        let diff = diff_scans(&baseline, &result, cli.baseline_threshold);
+        write_rendered(&cli, &render_json(&diff, cli.pretty));
+        return;
+    }
+    emit(&cli, &result, start.elapsed());
+
+    if cli.watch {
+        if let Err(e) = watch(&cli, &roots, &options) {
+            eprintln!("Error: cannot watch: {e}");
             std::process::exit(1);
         }
-        scan_directory(&cli.path)
+    }
+}
+
+fn scan(cli: &Cli, roots: &[PathBuf], options: &ScanOptions) -> ScanResult {
+    let paths: Vec<&Path> = roots.iter().map(|p| p.as_path()).collect();
+    let scan_roots = || {
+        if cli.strict {
+            try_scan_directories_with(&paths, options).unwrap_or_else(|e| {
+                eprintln!("Error: {}", redact(cli, roots, &e.to_string()));
+                std::process::exit(1);
+            })
+        } else if cli.paths.is_some() {
+            scan_directories_with(&paths, options)
+        } else {
+            scan_directory_with(&roots[0], options)
+        }
+    };
+    let Some(ref cache_path) = cli.cache else {
+        return redact_result(cli, roots, scan_roots());
     };
 
-    let elapsed = start.elapsed();
+    let mut cache = ScanCache::load(cache_path);
+    let result = cache.get_or_scan(
+        &cache_key(cli, roots, &options.custom_rules),
+        &paths,
+        options,
+        scan_roots,
+    );
+    if let Err(e) = cache.save(cache_path) {
+        eprintln!("Warning: cannot write cache {}: {e}", cache_path.display());
+    }
+    redact_result(cli, roots, result)
+}
+
+/// Rewrite `path` relative to `root` for `--redact`. `path` may also be a
+/// message that mentions paths, like a strict-mode error; every path under
+/// `root` in it is rewritten. `root` is matched as given (when absolute)
+/// and canonicalized; relative paths reveal nothing and are left alone.
+fn redact_path(root: &Path, path: &str) -> String {
+    let forms = root
+        .canonicalize()
+        .into_iter()
+        .chain(root.is_absolute().then(|| root.to_path_buf()));
+    let mut redacted = path.to_string();
+    for form in forms {
+        let prefix = format!("{}{}", form.display(), std::path::MAIN_SEPARATOR);
+        redacted = redacted.replace(&prefix, "");
+    }
+    redacted
+}
+
+/// `text` with paths under any of `roots` redacted, with `--redact`.
+fn redact(cli: &Cli, roots: &[PathBuf], text: &str) -> String {
+    if !cli.redact {
+        return text.to_string();
+    }
+    roots
+        .iter()
+        .fold(text.to_string(), |text, root| redact_path(root, &text))
+}
+
+/// `result` with the paths it reports redacted, with `--redact`.
+fn redact_result(cli: &Cli, roots: &[PathBuf], mut result: ScanResult) -> ScanResult {
+    if let Some(examples) = result.examples.as_mut() {
+        for path in examples.values_mut().flatten() {
+            *path = redact(cli, roots, path);
+        }
+    }
+    result
+}
+
+/// What a cached result depends on besides the files: the roots, every
+/// flag that changes the result (but not how it is printed) and the rules
+/// loaded with `--rules`.
+fn cache_key(cli: &Cli, roots: &[PathBuf], custom_rules: &[CustomRule]) -> String {
+    let roots: Vec<PathBuf> = roots
+        .iter()
+        .map(|r| r.canonicalize().unwrap_or_else(|_| r.clone()))
+        .collect();
+    format!(
+        "{:?} {:?} {:?}",
+        (
+            roots,
+            cli.paths.is_some(),
+            cli.lines,
+            &cli.exclude,
+            &cli.ignore,
+            &cli.treat_binary,
+            &cli.treat_text,
+            cli.max_file_size,
+            cli.max_depth,
+            cli.strict,
+        ),
+        (
+            cli.min_percentage,
+            cli.include_hidden,
+            cli.no_gitignore,
+            cli.follow_symlinks,
+            cli.skip_generated,
+            cli.split_sfc,
+            cli.per_dir,
+            cli.code_only,
+            cli.stats_json,
+            cli.languages_only,
+            cli.explain,
+            cli.fuzzy,
+        ),
+        custom_rules,
+    )
+}
+
+/// Render `result` in the requested format and print or write it.
+fn emit(cli: &Cli, result: &ScanResult, elapsed: Duration) {
+    let rendered = match cli.format {
+        Format::Json => match cli.only {
+            None => render_json(result, cli.pretty),
+            Some(Section::Languages) => render_json(&result.languages, cli.pretty),
+            Some(Section::Frameworks) => render_json(&result.frameworks, cli.pretty),
+            Some(Section::Structures) => render_json(&result.project_structures, cli.pretty),
+            Some(Section::Infra) => render_json(&result.infrastructure_signals, cli.pretty),
+        },
+        Format::Yaml => serde_yaml::to_string(result).expect("Failed to serialize result"),
+        Format::Toml => toml::to_string(result).expect("Failed to serialize result"),
+        Format::Csv => to_csv(result).expect("Failed to serialize result"),
+        Format::Summary => to_summary(result),
+    };
+    write_rendered(cli, &rendered);
 
-    let json = if cli.pretty {
-        serde_json::to_string_pretty(&result).expect("Failed to serialize result")
+    if cli.stats {
+        print_stats(result, elapsed);
+    }
+    if cli.profile {
+        print_profile(result);
+    }
+}
+
+/// Print `rendered`, or write it to `--output`.
+fn write_rendered(cli: &Cli, rendered: &str) {
+    match cli.output {
+        Some(ref out) => {
+            if let Err(e) = write_output(out, rendered) {
+                eprintln!("Error: cannot write {}: {e}", out.display());
+                std::process::exit(1);
+            }
+        }
+        None => {
+            print!("{rendered}");
+            let _ = io::stdout().flush();
+        }
+    }
+}
+
+/// Serialize `value` as a line of JSON, pretty-printed if asked.
+fn render_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> String {
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
     } else {
-        serde_json::to_string(&result).expect("Failed to serialize result")
+        serde_json::to_string(value)
     };
+    format!("{}\n", json.expect("Failed to serialize result"))
+}
 
-    println!("{json}");
+fn print_stats(result: &ScanResult, elapsed: Duration) {
+    eprintln!(
+        "Scanned in {:.1}ms | {} languages | {} frameworks | {} infra signals",
+        elapsed.as_secs_f64() * 1000.0,
+        result.languages.len(),
+        result.frameworks.len(),
+        result.infrastructure_signals.len(),
+    );
+}
 
-    if cli.stats {
-        eprintln!(
-            "Scanned in {:.1}ms | {} languages | {} frameworks | {} infra signals",
-            elapsed.as_secs_f64() * 1000.0,
-            result.languages.len(),
-            result.frameworks.len(),
-            result.infrastructure_signals.len(),
-        );
+fn print_profile(result: &ScanResult) {
+    let Some(profile) = result.profile else {
+        eprintln!("Profile: none (result reused from --cache)");
+        return;
+    };
+    let phases: Vec<String> = profile
+        .phases()
+        .iter()
+        .map(|(label, elapsed)| format!("{label} {:.1}ms", elapsed.as_secs_f64() * 1000.0))
+        .collect();
+    eprintln!("Profile: {}", phases.join(" | "));
+}
+
+/// One line of `--per-path-ndjson` output.
+#[derive(Serialize)]
+struct PathResult<'a> {
+    path: String,
+    #[serde(flatten)]
+    result: &'a ScanResult,
+}
+
+/// Scan each root separately, printing each result as a JSON line as soon
+/// as it is ready.
+fn emit_per_path(cli: &Cli, roots: &[PathBuf], options: &ScanOptions) {
+    let mut stdout = io::stdout().lock();
+    for root in roots {
+        let start = Instant::now();
+        let own_root = std::slice::from_ref(root);
+        let result = if cli.strict {
+            try_scan_directories_with(&[root], options).unwrap_or_else(|e| {
+                eprintln!("Error: {}", redact(cli, own_root, &e.to_string()));
+                std::process::exit(1);
+            })
+        } else {
+            scan_directory_with(root, options)
+        };
+        let result = redact_result(cli, own_root, result);
+        let line = PathResult {
+            path: root.display().to_string(),
+            result: &result,
+        };
+        let json = serde_json::to_string(&line).expect("Failed to serialize result");
+        // A closed pipe (e.g. `| head -1`) just ends the run.
+        if writeln!(stdout, "{json}")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            return;
+        }
+        if cli.stats {
+            print_stats(&result, start.elapsed());
+        }
+        if cli.profile {
+            print_profile(&result);
+        }
+    }
+}
+
+/// Rescan and emit again whenever something the scan would see changes,
+/// until Ctrl-C.
+fn watch(cli: &Cli, roots: &[PathBuf], options: &ScanOptions) -> notify::Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let handler_stop = Arc::clone(&stop);
+    ctrlc::set_handler(move || handler_stop.store(true, Ordering::SeqCst))
+        .map_err(|e| notify::Error::generic(&e.to_string()))?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for root in roots {
+        watcher.watch(root, RecursiveMode::Recursive)?;
+    }
+    // Events carry absolute paths; match them against canonical roots.
+    let filters: Vec<PathFilter> = roots
+        .iter()
+        .map(|root| PathFilter::new(&root.canonicalize().unwrap_or(root.clone()), options))
+        .collect();
+    let relevant = |event: &Event| {
+        !matches!(event.kind, EventKind::Access(_))
+            && event
+                .paths
+                .iter()
+                .any(|path| filters.iter().any(|f| !f.is_ignored(path)))
+    };
+
+    let mut deadline: Option<Instant> = None;
+    while !stop.load(Ordering::SeqCst) {
+        match rx.recv_timeout(WATCH_POLL) {
+            Ok(Ok(event)) if relevant(&event) => {
+                deadline = Some(Instant::now() + WATCH_DEBOUNCE);
+            }
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(e)) => eprintln!("Warning: watch error: {e}"),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            deadline = None;
+            let start = Instant::now();
+            emit(cli, &scan(cli, roots, options), start.elapsed());
+        }
     }
+    Ok(())
 }
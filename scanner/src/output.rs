@@ -1,22 +1,351 @@
-use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
 
-#[derive(Debug, Serialize, Clone)]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::frameworks::{insert_signal, into_sorted_entries};
+use crate::languages::{build_language_list, primary_language};
+
+/// Aggregated scan output, matching the `dev_context` shape the Python
+/// pipeline consumes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct ScanResult {
     pub languages: Vec<LanguageEntry>,
     pub frameworks: Vec<SignalEntry>,
     pub project_structures: Vec<String>,
     pub infrastructure_signals: Vec<SignalEntry>,
+    /// The largest non-markup language, when it makes up more than 40% of
+    /// the total.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_language: Option<String>,
+    /// SPDX identifier of the top-level `LICENSE`, `LICENSE.md` or
+    /// `COPYING` file (MIT, Apache-2.0, GPL-3.0, BSD-3-Clause, MPL-2.0),
+    /// `unknown` when its text isn't recognized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Whether the scanned tree has a top-level `README*` file.
+    #[serde(default)]
+    pub has_readme: bool,
+    /// Size of the README, when there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readme_bytes: Option<u64>,
+    /// Languages of the README's fenced code blocks, most used first. A
+    /// hint about what the project is used with, kept apart from
+    /// `languages`, which only counts real source.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub readme_languages: Vec<String>,
+    /// Targets of the top-level `Makefile` or recipes of the `justfile`, in
+    /// file order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub build_targets: Vec<String>,
+    /// Number of files considered for language counting (binary
+    /// extensions, and generated files with `--skip-generated`, excluded).
+    pub total_files: u64,
+    /// Size of every file the walk visited, binaries included.
+    pub total_bytes: u64,
+    /// Bytes of counted files that are tests: under a `tests/`, `test/`,
+    /// `__tests__/` or `spec/` directory, or named `*_test.*` or `*.spec.*`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_bytes: Option<u64>,
+    /// Bytes of counted files that are not tests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_bytes: Option<u64>,
+    /// Language breakdown per top-level directory (or per package inside a
+    /// monorepo `packages/`/`libs/` dir). Only present with `--per-dir`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_directory: Option<BTreeMap<String, Vec<LanguageEntry>>>,
+    /// The heaviest files counted for each language, for seeing where a
+    /// share comes from. Absolute paths for directory scans. Only present
+    /// with `--explain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub examples: Option<BTreeMap<String, Vec<String>>>,
+    /// Cost of the scan. Only present with `--stats-json`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<ScanStats>,
+    /// Time spent in each phase. Only present with `ScanOptions::profile`;
+    /// never serialized.
+    #[serde(skip)]
+    pub profile: Option<ScanProfile>,
+}
+
+/// Scale for the language weights estimated by [`ScanResult::merge`], so
+/// rounding them to whole numbers loses nothing that shows at one decimal.
+const MERGE_WEIGHT_SCALE: f64 = 1e9;
+
+impl ScanResult {
+    /// Combine results of separate scans, e.g. of repos scanned on
+    /// different machines. Frameworks, infrastructure and structures are
+    /// unioned and counts are summed.
+    ///
+    /// Languages are an approximation: a result only keeps percentages, so
+    /// each one is weighted by the bytes of its counted files
+    /// (`test_bytes + source_bytes`, or `total_bytes` for results without
+    /// them), which includes files of no language and ignores `--lines`.
+    /// When the trees are at hand, merge [`RawScan`](crate::RawScan)s from
+    /// [`scan_directory_raw`](crate::scan_directory_raw) instead, which is
+    /// exact. Per-directory breakdowns and examples keep the first result's
+    /// entry for each directory or language, and stats and profiles are
+    /// dropped.
+    pub fn merge(results: &[ScanResult]) -> ScanResult {
+        let weight = |r: &ScanResult| match (r.test_bytes, r.source_bytes) {
+            (Some(tests), Some(source)) => tests + source,
+            _ => r.total_bytes,
+        };
+        let mut weights: Vec<f64> = results.iter().map(|r| weight(r) as f64).collect();
+        if weights.iter().all(|&w| w == 0.0) {
+            weights.iter_mut().for_each(|w| *w = 1.0);
+        }
+        let total_weight: f64 = weights.iter().sum();
+
+        let mut bytes_by_lang: HashMap<String, u64> = HashMap::new();
+        let mut frameworks = HashMap::new();
+        let mut infra = HashMap::new();
+        let mut structures = BTreeSet::new();
+        let mut per_directory: Option<BTreeMap<String, Vec<LanguageEntry>>> = None;
+        let mut examples: Option<BTreeMap<String, Vec<String>>> = None;
+        let mut readme_languages: Vec<String> = Vec::new();
+        let mut build_targets: Vec<String> = Vec::new();
+        let mut license: Option<String> = None;
+        for (result, weight) in results.iter().zip(&weights) {
+            for lang in &result.languages {
+                let share = weight / total_weight * lang.percentage / 100.0;
+                *bytes_by_lang.entry(lang.name.clone()).or_insert(0) +=
+                    (share * MERGE_WEIGHT_SCALE).round() as u64;
+            }
+            for entry in result.frameworks.iter().cloned() {
+                insert_signal(&mut frameworks, entry);
+            }
+            for entry in result.infrastructure_signals.iter().cloned() {
+                insert_signal(&mut infra, entry);
+            }
+            structures.extend(result.project_structures.iter().cloned());
+            if let Some(dirs) = &result.per_directory {
+                let merged = per_directory.get_or_insert_with(BTreeMap::new);
+                for (dir, languages) in dirs {
+                    merged
+                        .entry(dir.clone())
+                        .or_insert_with(|| languages.clone());
+                }
+            }
+            if let Some(files) = &result.examples {
+                let merged = examples.get_or_insert_with(BTreeMap::new);
+                for (lang, paths) in files {
+                    merged.entry(lang.clone()).or_insert_with(|| paths.clone());
+                }
+            }
+            for lang in &result.readme_languages {
+                if !readme_languages.contains(lang) {
+                    readme_languages.push(lang.clone());
+                }
+            }
+            for target in &result.build_targets {
+                if !build_targets.contains(target) {
+                    build_targets.push(target.clone());
+                }
+            }
+            if matches!(license.as_deref(), None | Some("unknown")) && result.license.is_some() {
+                license.clone_from(&result.license);
+            }
+        }
+
+        let languages = build_language_list(&bytes_by_lang);
+        let sum_all = |field: fn(&ScanResult) -> Option<u64>| -> Option<u64> {
+            results.iter().map(field).sum()
+        };
+        let has_readme = results.iter().any(|r| r.has_readme);
+        ScanResult {
+            primary_language: primary_language(&languages),
+            languages,
+            frameworks: into_sorted_entries(&frameworks),
+            project_structures: structures.into_iter().collect(),
+            infrastructure_signals: into_sorted_entries(&infra),
+            license,
+            has_readme,
+            readme_bytes: has_readme.then(|| results.iter().filter_map(|r| r.readme_bytes).sum()),
+            readme_languages,
+            build_targets,
+            total_files: results.iter().map(|r| r.total_files).sum(),
+            total_bytes: results.iter().map(|r| r.total_bytes).sum(),
+            test_bytes: sum_all(|r| r.test_bytes),
+            source_bytes: sum_all(|r| r.source_bytes),
+            per_directory,
+            examples,
+            stats: None,
+            profile: None,
+        }
+    }
+}
+
+/// Timing and counts for one scan, for tracking scan cost over time.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct ScanStats {
+    /// Wall-clock time spent walking and reading, in milliseconds.
+    pub elapsed_ms: f64,
+    /// Files considered for language counting (same as `total_files`).
+    pub files_scanned: u64,
+    /// Files passed over as binary by extension or as generated (with
+    /// `--skip-generated`), plus entries pruned by
+    /// the skip list, `--exclude` or `--ignore` (a pruned directory counts
+    /// once). Paths hidden by `.gitignore` are never visited or counted.
+    pub files_skipped: u64,
+    /// Size of every file visited (same as `total_bytes`).
+    pub bytes_read: u64,
+}
+
+/// Where a scan spent its time, for finding the phase that dominates.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanProfile {
+    /// Walking the tree and weighing files by language.
+    pub walk: Duration,
+    /// Parsing dependency manifests and the Dockerfile.
+    pub dependencies: Duration,
+    /// Indicator, structure, license and README detection.
+    pub structures: Duration,
+    /// Turning the counts into the result.
+    pub finalize: Duration,
+}
+
+impl ScanProfile {
+    /// Each phase with its label, in the order the scan runs them.
+    pub fn phases(&self) -> [(&'static str, Duration); 4] {
+        [
+            ("walk", self.walk),
+            ("dependencies", self.dependencies),
+            ("structures", self.structures),
+            ("finalize", self.finalize),
+        ]
+    }
+
+    /// Add another profile's times to this one.
+    pub fn add(&mut self, other: &ScanProfile) {
+        self.walk += other.walk;
+        self.dependencies += other.dependencies;
+        self.structures += other.structures;
+        self.finalize += other.finalize;
+    }
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct LanguageEntry {
     pub name: String,
+    /// What kind of language this is: `programming`, `markup` (HTML),
+    /// `styling` (CSS, SCSS, Less), `data` (SQL) or `shell`. Older versions
+    /// always wrote `"language"`.
+    #[schemars(extend("enum" = ["programming", "markup", "styling", "data", "shell"]))]
     pub category: String,
+    /// Share of the scanned total, from 0 to 100 with one decimal place.
+    #[schemars(range(min = 0.0, max = 100.0))]
     pub percentage: f64,
 }
 
-#[derive(Debug, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
 pub struct SignalEntry {
     pub name: String,
+    /// `framework`, `tool`, `testing`, `language` or `infrastructure`.
+    /// Test frameworks are `testing`, so they can be left out of a
+    /// production stack.
     pub category: String,
+    /// Version requirement as declared in the dependency file, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// `low` for signals only found by `--fuzzy` matching, from a
+    /// dependency whose name merely contains a known one. Absent for exact
+    /// matches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("enum" = ["low"]))]
+    pub confidence: Option<String>,
+}
+
+impl SignalEntry {
+    pub fn new(name: &str, category: &str, version: Option<String>) -> Self {
+        SignalEntry {
+            name: name.to_string(),
+            category: category.to_string(),
+            version,
+            confidence: None,
+        }
+    }
+}
+
+/// JSON Schema describing `ScanResult` and its entry types.
+pub fn scan_result_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(ScanResult)).expect("schema is valid JSON")
+}
+
+/// How many frameworks `to_summary` lists before eliding the rest.
+const SUMMARY_TOP_FRAMEWORKS: usize = 3;
+
+/// A short plain-text report for people and CI logs: the dominant
+/// language, the top frameworks (frameworks before tools, then by name),
+/// infrastructure and structures, one labelled line each.
+pub fn to_summary(result: &ScanResult) -> String {
+    let describe = |signal: &SignalEntry| match &signal.version {
+        Some(version) => format!("{} {version}", signal.name),
+        None => signal.name.clone(),
+    };
+    let list = |items: Vec<String>| {
+        if items.is_empty() {
+            "none".to_string()
+        } else {
+            items.join(", ")
+        }
+    };
+
+    let language = result.languages.first().map_or("none".to_string(), |lang| {
+        format!("{} ({:.1}%)", lang.name, lang.percentage)
+    });
+
+    let mut frameworks: Vec<&SignalEntry> = result.frameworks.iter().collect();
+    frameworks.sort_by_key(|fw| match fw.category.as_str() {
+        "framework" => 0,
+        "tool" => 1,
+        _ => 2,
+    });
+    let mut top = list(
+        frameworks
+            .iter()
+            .take(SUMMARY_TOP_FRAMEWORKS)
+            .map(|fw| describe(fw))
+            .collect(),
+    );
+    if frameworks.len() > SUMMARY_TOP_FRAMEWORKS {
+        top.push_str(&format!(
+            " (+{} more)",
+            frameworks.len() - SUMMARY_TOP_FRAMEWORKS
+        ));
+    }
+
+    let infra = list(result.infrastructure_signals.iter().map(describe).collect());
+    let structures = list(result.project_structures.clone());
+
+    format!(
+        "Language:       {language}\n\
+         Frameworks:     {top}\n\
+         Infrastructure: {infra}\n\
+         Structures:     {structures}\n"
+    )
+}
+
+/// Flatten a result into CSV rows of `kind,name,category,percentage`, where
+/// `kind` is `language`, `framework`, `structure` or `infra`. Only language
+/// rows carry a percentage.
+pub fn to_csv(result: &ScanResult) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["kind", "name", "category", "percentage"])?;
+    for lang in &result.languages {
+        let percentage = lang.percentage.to_string();
+        writer.write_record(["language", &lang.name, &lang.category, &percentage])?;
+    }
+    for fw in &result.frameworks {
+        writer.write_record(["framework", &fw.name, &fw.category, ""])?;
+    }
+    for structure in &result.project_structures {
+        writer.write_record(["structure", structure, "", ""])?;
+    }
+    for infra in &result.infrastructure_signals {
+        writer.write_record(["infra", &infra.name, &infra.category, ""])?;
+    }
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes).expect("CSV fields are valid UTF-8"))
 }
@@ -0,0 +1,126 @@
+use std::fmt;
+use std::io;
+use std::process::Command;
+
+use tempfile::TempDir;
+
+/// Why a remote repository could not be cloned.
+#[derive(Debug)]
+pub enum CloneError {
+    /// `git` is not installed or not on PATH.
+    GitNotFound,
+    /// The host refused the credentials, or none were available.
+    AuthFailed(String),
+    /// The host could not be resolved or reached.
+    Unreachable(String),
+    /// Any other clone failure (missing repo or ref, bad URL, ...), with
+    /// git's own message.
+    Failed(String),
+    /// The temp directory to clone into could not be created.
+    Io(io::Error),
+}
+
+impl fmt::Display for CloneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloneError::GitNotFound => write!(f, "git is not installed or not on PATH"),
+            CloneError::AuthFailed(msg) => write!(f, "authentication failed: {msg}"),
+            CloneError::Unreachable(msg) => write!(f, "host unreachable: {msg}"),
+            CloneError::Failed(msg) => write!(f, "clone failed: {msg}"),
+            CloneError::Io(e) => write!(f, "cannot create temp dir: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for CloneError {}
+
+/// Sort git's stderr into a [`CloneError`], keeping its first `fatal:` line
+/// as the message.
+fn classify_git_error(stderr: &str) -> CloneError {
+    let message = stderr
+        .lines()
+        .find_map(|l| l.strip_prefix("fatal: "))
+        .unwrap_or_else(|| stderr.trim())
+        .to_string();
+    let lower = stderr.to_lowercase();
+    if [
+        "authentication failed",
+        "could not read username",
+        "permission denied",
+    ]
+    .iter()
+    .any(|s| lower.contains(s))
+    {
+        CloneError::AuthFailed(message)
+    } else if [
+        "could not resolve host",
+        "connection refused",
+        "connection timed out",
+        "network is unreachable",
+        "failed to connect",
+    ]
+    .iter()
+    .any(|s| lower.contains(s))
+    {
+        CloneError::Unreachable(message)
+    } else {
+        CloneError::Failed(message)
+    }
+}
+
+/// Shallow-clone `url` (at `git_ref`, a branch or tag, if given) into a new
+/// temp dir with the `git` CLI. The directory is deleted when the returned
+/// [`TempDir`] is dropped. Credential prompts are disabled, so a private
+/// repo without configured credentials fails instead of hanging.
+pub fn shallow_clone(url: &str, git_ref: Option<&str>) -> Result<TempDir, CloneError> {
+    let dir = tempfile::Builder::new()
+        .prefix("pb-scan-clone-")
+        .tempdir()
+        .map_err(CloneError::Io)?;
+
+    let mut cmd = Command::new("git");
+    cmd.args(["clone", "--quiet", "--depth", "1"]);
+    if let Some(git_ref) = git_ref {
+        cmd.args(["--branch", git_ref]);
+    }
+    cmd.arg("--").arg(url).arg(dir.path());
+    cmd.env("GIT_TERMINAL_PROMPT", "0");
+
+    let output = cmd.output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => CloneError::GitNotFound,
+        _ => CloneError::Io(e),
+    })?;
+    if !output.status.success() {
+        return Err(classify_git_error(&String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_git_error() {
+        let err = classify_git_error(
+            "Cloning into 'x'...\nfatal: could not read Username for 'https://github.com': terminal prompts disabled\n",
+        );
+        assert!(matches!(err, CloneError::AuthFailed(_)));
+        assert_eq!(
+            err.to_string(),
+            "authentication failed: could not read Username for 'https://github.com': terminal prompts disabled"
+        );
+
+        let err = classify_git_error(
+            "fatal: unable to access 'https://nowhere.invalid/x.git/': Could not resolve host: nowhere.invalid\n",
+        );
+        assert!(matches!(err, CloneError::Unreachable(_)));
+
+        let err = classify_git_error(
+            "warning: Could not find remote branch v9 to clone.\nfatal: Remote branch v9 not found in upstream origin\n",
+        );
+        assert!(
+            matches!(err, CloneError::Failed(ref m) if m == "Remote branch v9 not found in upstream origin")
+        );
+    }
+}
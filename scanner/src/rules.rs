@@ -0,0 +1,477 @@
+//! The dependency → signal table every manifest reader in
+//! [`dependencies`](crate::dependencies) draws from. Adding a framework is
+//! one `rule(...)` line in its manifest's section.
+//!
+//! Rules for in-house packages can also be loaded at runtime from a JSON
+//! file; see [`parse_custom_rules`].
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// One detection rule: a dependency `key` found through `manifest` is
+/// reported as the signal `name` with `category`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameworkRule {
+    /// The manifest whose reader applies the rule (`package.json`,
+    /// `Cargo.toml`, `*.cabal`, ...). Readers of related files, such as
+    /// lockfiles, use the rules of their main manifest.
+    pub manifest: &'static str,
+    /// What the reader looks for. Whether it must match exactly or as a
+    /// prefix or substring is up to the reader, as noted per section below.
+    pub key: &'static str,
+    pub name: &'static str,
+    /// `framework`, `tool`, `testing` (test frameworks and runners),
+    /// `language` or `infrastructure`.
+    pub category: &'static str,
+}
+
+const fn rule(
+    manifest: &'static str,
+    key: &'static str,
+    name: &'static str,
+    category: &'static str,
+) -> FrameworkRule {
+    FrameworkRule {
+        manifest,
+        key,
+        name,
+        category,
+    }
+}
+
+/// Every rule, grouped by manifest. Within a manifest, order matters where
+/// several keys give the same signal: the last match sets the version.
+pub const RULES: &[FrameworkRule] = &[
+    // npm packages in `package.json` or a lockfile, matched exactly. Port of
+    // NPM_FRAMEWORK_MAP from github.py.
+    rule("package.json", "react", "React", "framework"),
+    rule("package.json", "react-native", "React Native", "framework"),
+    rule("package.json", "next", "Next.js", "framework"),
+    rule("package.json", "vue", "Vue", "framework"),
+    rule("package.json", "nuxt", "Nuxt", "framework"),
+    rule("package.json", "svelte", "Svelte", "framework"),
+    rule("package.json", "@angular/core", "Angular", "framework"),
+    rule("package.json", "express", "Express", "framework"),
+    rule("package.json", "fastify", "Fastify", "framework"),
+    rule("package.json", "gatsby", "Gatsby", "framework"),
+    rule("package.json", "remix", "Remix", "framework"),
+    rule("package.json", "@remix-run/react", "Remix", "framework"),
+    rule("package.json", "astro", "Astro", "framework"),
+    rule("package.json", "solid-js", "SolidJS", "framework"),
+    rule("package.json", "@builder.io/qwik", "Qwik", "framework"),
+    rule("package.json", "preact", "Preact", "framework"),
+    rule("package.json", "@sveltejs/kit", "SvelteKit", "framework"),
+    rule("package.json", "@nestjs/core", "NestJS", "framework"),
+    rule("package.json", "koa", "Koa", "framework"),
+    rule("package.json", "tailwindcss", "Tailwind CSS", "framework"),
+    rule("package.json", "prisma", "Prisma", "tool"),
+    rule("package.json", "mongoose", "Mongoose", "tool"),
+    rule("package.json", "sequelize", "Sequelize", "tool"),
+    rule("package.json", "jest", "Jest", "testing"),
+    rule("package.json", "mocha", "Mocha", "testing"),
+    rule("package.json", "webpack", "Webpack", "tool"),
+    rule("package.json", "vite", "Vite", "tool"),
+    rule("package.json", "typescript", "TypeScript", "language"),
+    rule("package.json", "three", "Three.js", "framework"),
+    rule("package.json", "electron", "Electron", "framework"),
+    rule("package.json", "socket.io", "Socket.IO", "framework"),
+    rule("package.json", "graphql", "GraphQL", "tool"),
+    rule("package.json", "@apollo/client", "Apollo", "framework"),
+    rule("package.json", "redis", "Redis", "tool"),
+    rule("package.json", "pg", "PostgreSQL", "tool"),
+    rule("package.json", "mongodb", "MongoDB", "tool"),
+    rule("package.json", "supabase", "Supabase", "tool"),
+    rule("package.json", "firebase", "Firebase", "tool"),
+    rule("package.json", "wasm-pack", "wasm-pack", "tool"),
+    // Python packages, shared by every Python manifest reader (`pyproject.toml`,
+    // `Pipfile` and `environment.yml` too). Keys match the normalized names
+    // of parsed packages exactly, but as substrings of requirements.txt.
+    // Port of PYTHON_FRAMEWORK_MAP from github.py.
+    rule("requirements.txt", "django", "Django", "framework"),
+    rule("requirements.txt", "flask", "Flask", "framework"),
+    rule("requirements.txt", "fastapi", "FastAPI", "framework"),
+    rule("requirements.txt", "tornado", "Tornado", "framework"),
+    rule("requirements.txt", "celery", "Celery", "tool"),
+    rule("requirements.txt", "sqlalchemy", "SQLAlchemy", "tool"),
+    rule("requirements.txt", "pandas", "pandas", "framework"),
+    rule("requirements.txt", "numpy", "NumPy", "framework"),
+    rule("requirements.txt", "scipy", "SciPy", "framework"),
+    rule(
+        "requirements.txt",
+        "scikit-learn",
+        "scikit-learn",
+        "framework",
+    ),
+    rule("requirements.txt", "tensorflow", "TensorFlow", "framework"),
+    rule("requirements.txt", "torch", "PyTorch", "framework"),
+    // Conda's name for `torch`.
+    rule("requirements.txt", "pytorch", "PyTorch", "framework"),
+    rule("requirements.txt", "pytest", "pytest", "testing"),
+    rule("requirements.txt", "pydantic", "Pydantic", "tool"),
+    rule("requirements.txt", "requests", "Requests", "tool"),
+    rule("requirements.txt", "boto3", "AWS SDK", "tool"),
+    rule("requirements.txt", "redis", "Redis", "tool"),
+    rule("requirements.txt", "psycopg2", "PostgreSQL", "tool"),
+    rule("requirements.txt", "psycopg2-binary", "PostgreSQL", "tool"),
+    // Crates in a dependency table or `Cargo.lock`, matched exactly. Port of
+    // RUST_CRATE_MAP from github.py.
+    rule("Cargo.toml", "actix-web", "Actix Web", "framework"),
+    rule("Cargo.toml", "axum", "Axum", "framework"),
+    rule("Cargo.toml", "rocket", "Rocket", "framework"),
+    rule("Cargo.toml", "tokio", "Tokio", "tool"),
+    rule("Cargo.toml", "serde", "Serde", "tool"),
+    rule("Cargo.toml", "diesel", "Diesel", "tool"),
+    rule("Cargo.toml", "sqlx", "SQLx", "tool"),
+    rule("Cargo.toml", "leptos", "Leptos", "framework"),
+    rule("Cargo.toml", "yew", "Yew", "framework"),
+    rule("Cargo.toml", "tauri", "Tauri", "framework"),
+    rule("Cargo.toml", "wasm-bindgen", "WebAssembly", "tool"),
+    rule("Cargo.toml", "wasmtime", "Wasmtime", "tool"),
+    rule("Cargo.toml", "wasmer", "Wasmer", "tool"),
+    // Ruby gems, matched as substrings of the `Gemfile` and exactly in
+    // `Gemfile.lock`. Port of RUBY_GEM_MAP from github.py.
+    rule("Gemfile", "rails", "Ruby on Rails", "framework"),
+    rule("Gemfile", "sinatra", "Sinatra", "framework"),
+    rule("Gemfile", "sidekiq", "Sidekiq", "tool"),
+    rule("Gemfile", "rspec", "RSpec", "testing"),
+    // Lowercase SwiftPM repository names or pod names, shared by the SwiftPM
+    // and CocoaPods readers.
+    rule("Package.swift", "alamofire", "Alamofire", "tool"),
+    rule("Package.swift", "rxswift", "RxSwift", "framework"),
+    rule("Package.swift", "snapkit", "SnapKit", "tool"),
+    rule("Package.swift", "kingfisher", "Kingfisher", "tool"),
+    rule("Package.swift", "moya", "Moya", "tool"),
+    rule("Package.swift", "swiftyjson", "SwiftyJSON", "tool"),
+    rule("Package.swift", "realm-swift", "Realm", "tool"),
+    rule("Package.swift", "realmswift", "Realm", "tool"),
+    rule("Package.swift", "firebase-ios-sdk", "Firebase", "tool"),
+    rule("Package.swift", "firebase", "Firebase", "tool"),
+    rule(
+        "Package.swift",
+        "swift-composable-architecture",
+        "Composable Architecture",
+        "framework",
+    ),
+    rule("Package.swift", "vapor", "Vapor", "framework"),
+    rule("Package.swift", "lottie-ios", "Lottie", "tool"),
+    // Elixir `deps` atoms.
+    rule("mix.exs", "phoenix", "Phoenix", "framework"),
+    rule(
+        "mix.exs",
+        "phoenix_live_view",
+        "Phoenix LiveView",
+        "framework",
+    ),
+    rule("mix.exs", "ecto", "Ecto", "tool"),
+    rule("mix.exs", "ecto_sql", "Ecto", "tool"),
+    rule("mix.exs", "plug", "Plug", "tool"),
+    rule("mix.exs", "plug_cowboy", "Plug", "tool"),
+    rule("mix.exs", "absinthe", "Absinthe", "framework"),
+    rule("mix.exs", "broadway", "Broadway", "tool"),
+    // Dart/Flutter packages.
+    rule("pubspec.yaml", "flutter", "Flutter", "framework"),
+    rule("pubspec.yaml", "provider", "Provider", "tool"),
+    rule("pubspec.yaml", "riverpod", "Riverpod", "tool"),
+    rule("pubspec.yaml", "flutter_riverpod", "Riverpod", "tool"),
+    rule("pubspec.yaml", "hooks_riverpod", "Riverpod", "tool"),
+    rule("pubspec.yaml", "bloc", "BLoC", "tool"),
+    rule("pubspec.yaml", "flutter_bloc", "BLoC", "tool"),
+    rule("pubspec.yaml", "dio", "Dio", "tool"),
+    // Haskell packages, shared by cabal and hpack (`package.yaml`).
+    rule("*.cabal", "servant", "Servant", "framework"),
+    rule("*.cabal", "servant-server", "Servant", "framework"),
+    rule("*.cabal", "yesod", "Yesod", "framework"),
+    rule("*.cabal", "yesod-core", "Yesod", "framework"),
+    rule("*.cabal", "scotty", "Scotty", "framework"),
+    rule("*.cabal", "ihp", "IHP", "framework"),
+    rule("*.cabal", "warp", "Warp", "tool"),
+    rule("*.cabal", "aeson", "Aeson", "tool"),
+    rule("*.cabal", "lens", "lens", "tool"),
+    rule("*.cabal", "persistent", "Persistent", "tool"),
+    rule("*.cabal", "hspec", "Hspec", "testing"),
+    // Zig package names as declared in `build.zig.zon`.
+    rule("build.zig.zon", "zap", "Zap", "framework"),
+    rule("build.zig.zon", "httpz", "http.zig", "framework"),
+    rule("build.zig.zon", "jetzig", "Jetzig", "framework"),
+    rule("build.zig.zon", "tokamak", "Tokamak", "framework"),
+    rule("build.zig.zon", "mach", "Mach", "framework"),
+    rule("build.zig.zon", "capy", "Capy", "framework"),
+    rule("build.zig.zon", "raylib", "raylib", "tool"),
+    rule("build.zig.zon", "raylib_zig", "raylib", "tool"),
+    rule("build.zig.zon", "clap", "zig-clap", "tool"),
+    rule("build.zig.zon", "zig-clap", "zig-clap", "tool"),
+    rule("build.zig.zon", "sqlite", "zig-sqlite", "tool"),
+    rule("build.zig.zon", "pg", "pg.zig", "tool"),
+    rule("build.zig.zon", "ziglyph", "ziglyph", "tool"),
+    // Go modules, matched as substrings of `go.mod`. Port of GO_MODULE_MAP
+    // from github.py.
+    rule("go.mod", "github.com/gin-gonic/gin", "Gin", "framework"),
+    rule(
+        "go.mod",
+        "github.com/gorilla/mux",
+        "Gorilla Mux",
+        "framework",
+    ),
+    rule("go.mod", "github.com/labstack/echo", "Echo", "framework"),
+    rule("go.mod", "github.com/gofiber/fiber", "Fiber", "framework"),
+    rule("go.mod", "gorm.io/gorm", "GORM", "tool"),
+    // Composer packages, in `composer.json` or `composer.lock`. Port of
+    // PHP_PACKAGE_MAP from github.py.
+    rule("composer.json", "laravel/framework", "Laravel", "framework"),
+    rule("composer.json", "symfony/symfony", "Symfony", "framework"),
+    rule("composer.json", "slim/slim", "Slim", "framework"),
+    // Substrings of the `dependencies { }` and `plugins { }` blocks of
+    // `build.gradle` or `build.gradle.kts`.
+    rule(
+        "build.gradle",
+        "spring-boot-starter",
+        "Spring Boot",
+        "framework",
+    ),
+    rule(
+        "build.gradle",
+        "org.springframework.boot",
+        "Spring Boot",
+        "framework",
+    ),
+    rule("build.gradle", "io.ktor", "Ktor", "framework"),
+    rule("build.gradle", "com.android", "Android", "framework"),
+    rule("build.gradle", "retrofit", "Retrofit", "tool"),
+    rule("build.gradle", "junit", "JUnit", "testing"),
+    rule("build.gradle", "hibernate", "Hibernate", "tool"),
+    // Maven coordinates as `groupId:artifactId` prefixes; an empty artifact
+    // matches any artifact of the group.
+    rule(
+        "pom.xml",
+        "org.springframework.boot:",
+        "Spring Boot",
+        "framework",
+    ),
+    rule("pom.xml", "jakarta.:", "Jakarta EE", "framework"),
+    rule("pom.xml", "org.hibernate:", "Hibernate", "tool"),
+    rule("pom.xml", "org.junit:", "JUnit", "testing"),
+    rule("pom.xml", "junit:junit", "JUnit", "testing"),
+    rule("pom.xml", "org.apache.logging.log4j:", "Log4j", "tool"),
+    rule("pom.xml", "log4j:log4j", "Log4j", "tool"),
+    // Lowercase NuGet package id prefixes in `.csproj`/`.fsproj` files.
+    rule(
+        "*.csproj",
+        "microsoft.aspnetcore",
+        "ASP.NET Core",
+        "framework",
+    ),
+    rule(
+        "*.csproj",
+        "microsoft.entityframeworkcore",
+        "Entity Framework Core",
+        "tool",
+    ),
+    rule("*.csproj", "xunit", "xUnit", "testing"),
+    rule("*.csproj", "nunit", "NUnit", "testing"),
+    rule("*.csproj", "serilog", "Serilog", "tool"),
+    rule("*.csproj", "dapper", "Dapper", "tool"),
+    rule("*.csproj", "mediatr", "MediatR", "tool"),
+    // Compose service images, without registry, namespace or tag. Databases
+    // and brokers are tools; proxies are infrastructure.
+    rule("compose.yaml", "postgres", "PostgreSQL", "tool"),
+    rule("compose.yaml", "postgis", "PostgreSQL", "tool"),
+    rule("compose.yaml", "mysql", "MySQL", "tool"),
+    rule("compose.yaml", "mariadb", "MariaDB", "tool"),
+    rule("compose.yaml", "mongo", "MongoDB", "tool"),
+    rule("compose.yaml", "redis", "Redis", "tool"),
+    rule("compose.yaml", "memcached", "Memcached", "tool"),
+    rule("compose.yaml", "rabbitmq", "RabbitMQ", "tool"),
+    rule("compose.yaml", "kafka", "Kafka", "tool"),
+    rule("compose.yaml", "cp-kafka", "Kafka", "tool"),
+    rule("compose.yaml", "elasticsearch", "Elasticsearch", "tool"),
+    rule("compose.yaml", "nginx", "Nginx", "infrastructure"),
+    rule("compose.yaml", "traefik", "Traefik", "infrastructure"),
+    // Terraform provider names.
+    rule("*.tf", "aws", "AWS", "infrastructure"),
+    rule("*.tf", "google", "GCP", "infrastructure"),
+    rule("*.tf", "google-beta", "GCP", "infrastructure"),
+    rule("*.tf", "azurerm", "Azure", "infrastructure"),
+    rule("*.tf", "azuread", "Azure", "infrastructure"),
+    rule("*.tf", "kubernetes", "Kubernetes", "infrastructure"),
+    rule("*.tf", "helm", "Helm", "infrastructure"),
+    rule("*.tf", "cloudflare", "Cloudflare", "infrastructure"),
+    rule("*.tf", "digitalocean", "DigitalOcean", "infrastructure"),
+    // GitHub Actions (`owner/repo`, lowercase). A key ending in `/` matches
+    // every action of that owner.
+    rule(
+        ".github/workflows",
+        "docker/build-push-action",
+        "Docker",
+        "infrastructure",
+    ),
+    rule(".github/workflows", "aws-actions/", "AWS", "infrastructure"),
+    rule(".github/workflows", "azure/", "Azure", "infrastructure"),
+    rule(
+        ".github/workflows",
+        "google-github-actions/",
+        "GCP",
+        "infrastructure",
+    ),
+    rule(
+        ".github/workflows",
+        "actions/deploy-pages",
+        "GitHub Pages",
+        "infrastructure",
+    ),
+];
+
+/// The rules for `manifest`, in table order.
+pub fn rules(manifest: &str) -> impl Iterator<Item = &'static FrameworkRule> + '_ {
+    RULES.iter().filter(move |rule| rule.manifest == manifest)
+}
+
+/// Manifests a custom rule may name. Each is matched the way its built-in
+/// rules are: exactly for `package.json` (and its lockfiles), `Cargo.toml`
+/// and `composer.json`, as a substring for `requirements.txt` and `go.mod`.
+pub const CUSTOM_RULE_MANIFESTS: &[&str] = &[
+    "package.json",
+    "requirements.txt",
+    "Cargo.toml",
+    "composer.json",
+    "go.mod",
+];
+
+/// Categories a custom rule may give its signal.
+const CATEGORIES: &[&str] = &["framework", "tool", "testing", "language", "infrastructure"];
+
+/// A [`FrameworkRule`] loaded at runtime, applied after the built-in ones by
+/// [`detect_custom`](crate::dependencies::detect_custom).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRule {
+    /// One of [`CUSTOM_RULE_MANIFESTS`].
+    pub manifest: String,
+    pub key: String,
+    pub name: String,
+    pub category: String,
+}
+
+/// Why a custom rules file could not be loaded.
+#[derive(Debug)]
+pub enum RulesError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a JSON array of rule objects.
+    Json(serde_json::Error),
+    /// The rule at this index (from 0) is well-formed but not usable.
+    Invalid(usize, String),
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::Io(e) => write!(f, "{e}"),
+            RulesError::Json(e) => write!(f, "expected an array of rules: {e}"),
+            RulesError::Invalid(index, msg) => write!(f, "rule {index}: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+/// Load custom rules from the JSON file at `path`; see [`parse_custom_rules`].
+pub fn load_custom_rules(path: &Path) -> Result<Vec<CustomRule>, RulesError> {
+    let json = std::fs::read_to_string(path).map_err(RulesError::Io)?;
+    parse_custom_rules(&json)
+}
+
+/// Parse and check custom rules: a JSON array of
+/// `{"manifest", "key", "name", "category"}` objects, such as
+/// `[{"manifest": "package.json", "key": "@acme/ui", "name": "Acme UI",
+/// "category": "framework"}]`. Every field is required and non-empty, and
+/// `manifest` and `category` must be ones the scanner knows.
+pub fn parse_custom_rules(json: &str) -> Result<Vec<CustomRule>, RulesError> {
+    let rules: Vec<CustomRule> = serde_json::from_str(json).map_err(RulesError::Json)?;
+    for (index, rule) in rules.iter().enumerate() {
+        let invalid = |msg: String| Err(RulesError::Invalid(index, msg));
+        for (field, value) in [("key", &rule.key), ("name", &rule.name)] {
+            if value.trim().is_empty() {
+                return invalid(format!("`{field}` is empty"));
+            }
+        }
+        if !CUSTOM_RULE_MANIFESTS.contains(&rule.manifest.as_str()) {
+            return invalid(format!(
+                "unsupported manifest `{}` (expected one of {})",
+                rule.manifest,
+                CUSTOM_RULE_MANIFESTS.join(", ")
+            ));
+        }
+        if !CATEGORIES.contains(&rule.category.as_str()) {
+            return invalid(format!(
+                "unknown category `{}` (expected one of {})",
+                rule.category,
+                CATEGORIES.join(", ")
+            ));
+        }
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_for_manifest() {
+        let names: Vec<&str> = rules("go.mod").map(|r| r.name).collect();
+        assert_eq!(names, vec!["Gin", "Gorilla Mux", "Echo", "Fiber", "GORM"]);
+        assert_eq!(rules("nonexistent.lock").count(), 0);
+    }
+
+    #[test]
+    fn test_rules_have_no_duplicate_keys() {
+        for (i, a) in RULES.iter().enumerate() {
+            assert!(
+                !RULES[i + 1..]
+                    .iter()
+                    .any(|b| a.manifest == b.manifest && a.key == b.key),
+                "duplicate rule for {} in {}",
+                a.key,
+                a.manifest
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_custom_rules() {
+        let rules = parse_custom_rules(
+            r#"[{"manifest": "package.json", "key": "@acme/ui", "name": "Acme UI", "category": "framework"}]"#,
+        )
+        .unwrap();
+        assert_eq!(rules[0].name, "Acme UI");
+        assert!(parse_custom_rules("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_custom_rules_rejects_malformed() {
+        let error = |json: &str| parse_custom_rules(json).unwrap_err().to_string();
+        assert!(error(r#"{"manifest": "package.json"}"#).starts_with("expected an array"));
+        assert!(
+            error(r#"[{"manifest": "package.json", "key": "x", "name": "X"}]"#)
+                .contains("missing field `category`")
+        );
+        assert_eq!(
+            error(
+                r#"[{"manifest": "package.json", "key": "x", "name": "X", "category": "tool"},
+                    {"manifest": "pom.xml", "key": "x", "name": "X", "category": "tool"}]"#
+            ),
+            "rule 1: unsupported manifest `pom.xml` (expected one of package.json, requirements.txt, Cargo.toml, composer.json, go.mod)"
+        );
+        assert!(
+            error(r#"[{"manifest": "go.mod", "key": "x", "name": "X", "category": "lib"}]"#)
+                .starts_with("rule 0: unknown category `lib`")
+        );
+        assert_eq!(
+            error(r#"[{"manifest": "go.mod", "key": " ", "name": "X", "category": "tool"}]"#),
+            "rule 0: `key` is empty"
+        );
+    }
+}
@@ -1,16 +1,31 @@
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::WalkBuilder;
 
 use crate::dependencies;
-use crate::frameworks::{detect_file_indicators, into_sorted_entries};
-use crate::languages::{build_language_list, is_binary_extension, record_language};
-use crate::output::ScanResult;
-use crate::structures::detect_structures;
+use crate::files::{Excluding, MemoryTree, RepoFiles};
+use crate::frameworks::{
+    detect_file_indicators, detect_schema_files, insert_signal, into_sorted_entries,
+    is_indicator_name, is_schema_file,
+};
+use crate::languages::{
+    build_language_list, drop_markup_languages, drop_minor_languages, fence_language,
+    is_binary_extension, primary_language, record_language, record_language_in_memory,
+};
+use crate::output::{LanguageEntry, ScanProfile, ScanResult, ScanStats, SignalEntry};
+use crate::rules::CustomRule;
+use crate::structures::{detect_manifest_structures, detect_structures, MONOREPO_DIRS};
 
 /// Directories to skip even without a .gitignore.
-const SKIP_DIRS: &[&str] = &[
+pub(crate) const SKIP_DIRS: &[&str] = &[
     "node_modules",
     "vendor",
     "__pycache__",
@@ -23,6 +38,7 @@ const SKIP_DIRS: &[&str] = &[
 /// Hidden paths that are framework indicators — checked directly on disk
 /// since the walker skips hidden files/dirs.
 const HIDDEN_INDICATORS: &[&str] = &[
+    ".github",
     ".github/workflows",
     ".gitlab-ci.yml",
     ".circleci",
@@ -30,15 +46,571 @@ const HIDDEN_INDICATORS: &[&str] = &[
     ".eslintrc.json",
     ".prettierrc",
     ".travis.yml",
+    ".buckconfig",
+    ".devcontainer",
+    ".devcontainer.json",
+    ".buildpacks",
 ];
 
-/// Raw scan data before percentage conversion. Used internally so that
-/// `scan_directories` can merge byte counts across multiple roots.
-struct RawScanResult {
-    bytes_by_lang: HashMap<String, u64>,
-    frameworks: HashMap<String, String>,
-    infra: HashMap<String, String>,
-    project_structures: Vec<String>,
+/// Directories whose files are tests, wherever they appear in the tree.
+const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "spec"];
+
+/// Top-level files read for [`detect_license`].
+const LICENSE_FILES: &[&str] = &["LICENSE", "LICENSE.md", "COPYING"];
+
+/// SPDX identifier → phrases that must all appear in the license text,
+/// compared lowercased with whitespace collapsed.
+const LICENSE_PATTERNS: &[(&str, &[&str])] = &[
+    ("MIT", &["permission is hereby granted, free of charge"]),
+    ("Apache-2.0", &["apache license", "version 2.0"]),
+    ("GPL-3.0", &["gnu general public license", "version 3"]),
+    (
+        "BSD-3-Clause",
+        &[
+            "redistribution and use in source and binary forms",
+            "neither the name of",
+        ],
+    ),
+    ("MPL-2.0", &["mozilla public license", "2.0"]),
+];
+
+/// File-name globs for generated code and lockfiles, left out of language
+/// counting with `skip_generated`.
+const GENERATED_GLOBS: &[&str] = &[
+    // Minified and bundled assets
+    "*.min.js",
+    "*.min.css",
+    "*.bundle.js",
+    // Protobuf / gRPC stubs
+    "*_pb2.py",
+    "*_pb2_grpc.py",
+    "*.pb.go",
+    "*.pb.cc",
+    "*.pb.h",
+    "*_pb.js",
+    "*_pb.d.ts",
+    // Code generators
+    "*.g.dart",
+    "*.freezed.dart",
+    "*.designer.cs",
+    "*.generated.*",
+    // Lockfiles
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Cargo.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+
+/// Default cap on the size of a file counted towards language percentages.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Options controlling how a scan walks and weighs files.
+/// The defaults match the plain `scan_directory` behavior.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Weigh languages by line count instead of bytes (bytes are the default).
+    pub count_lines: bool,
+    /// Paths to skip entirely, matched relative to each scan root.
+    pub exclude: GlobSet,
+    /// Also break languages down per top-level directory.
+    pub per_directory: bool,
+    /// Files larger than this many bytes are left out of language counting.
+    /// Framework and structure detection still see them.
+    pub max_file_size: u64,
+    /// Only count files at most this many levels below the root (root files
+    /// are depth 1). Top-level names are always collected for detection.
+    pub max_depth: Option<usize>,
+    /// Languages below this share of the total are dropped and the rest
+    /// renormalized to 100. `0.0` keeps everything.
+    pub min_percentage: f64,
+    /// Walk hidden files and directories too (`.git` is always skipped).
+    pub include_hidden: bool,
+    /// Honor `.gitignore`, the global gitignore and `.git/info/exclude`.
+    /// `SKIP_DIRS` is skipped either way.
+    pub respect_gitignore: bool,
+    /// Follow symlinks while walking. Off by default; when on, the walker
+    /// reports symlink cycles as errors, which are skipped.
+    pub follow_symlinks: bool,
+    /// Extra ignore rules in gitignore syntax, applied under every root on
+    /// top of the repo's own `.gitignore`.
+    pub ignore: Vec<String>,
+    /// Extensions (no dot needed, any case) to skip as binary on top of
+    /// the built-in list, e.g. `parquet` or `onnx`.
+    pub binary_extensions: Vec<String>,
+    /// Extensions the built-in list calls binary that should be counted
+    /// anyway. These win over `binary_extensions`. Files whose content
+    /// looks binary still aren't given a language.
+    pub text_extensions: Vec<String>,
+    /// Split Vue/Svelte components into their script, style and template
+    /// languages instead of counting the whole file as Vue/Svelte.
+    pub split_sfc: bool,
+    /// Leave generated files (minified bundles, protobuf stubs, lockfiles;
+    /// see `GENERATED_GLOBS`) out of language counting.
+    pub skip_generated: bool,
+    /// Leave markup and styling languages (`MARKUP_LANGUAGES`) out of the
+    /// percentages, overall and per directory. With `split_sfc` this also
+    /// drops the templates and styles of Vue/Svelte components.
+    pub code_only: bool,
+    /// Attach timing and file counts to the result as `stats`.
+    pub collect_stats: bool,
+    /// Open every counted file to check it can be read, recording failures
+    /// in [`RawScan::unreadable`]. See [`try_scan_directories_with`].
+    pub strict: bool,
+    /// Only count languages: skip indicator files, manifests, structures,
+    /// the license and the README, leaving those parts of the result empty.
+    pub languages_only: bool,
+    /// Keep up to this many example files per language, the heaviest
+    /// first, for [`ScanResult::examples`]. 0 keeps none.
+    pub explain: usize,
+    /// Time the walk, dependency parsing, the other detectors and
+    /// finalizing, for [`ScanResult::profile`].
+    pub profile: bool,
+    /// Also match dependencies that merely contain a known name; see
+    /// [`dependencies::detect_fuzzy`].
+    pub fuzzy: bool,
+    /// Extra detection rules, applied after the built-in ones; see
+    /// [`dependencies::detect_custom`].
+    pub custom_rules: Vec<CustomRule>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            count_lines: false,
+            exclude: GlobSet::empty(),
+            per_directory: false,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_depth: None,
+            min_percentage: 0.0,
+            include_hidden: false,
+            respect_gitignore: true,
+            follow_symlinks: false,
+            ignore: Vec::new(),
+            binary_extensions: Vec::new(),
+            text_extensions: Vec::new(),
+            split_sfc: false,
+            skip_generated: false,
+            code_only: false,
+            collect_stats: false,
+            strict: false,
+            languages_only: false,
+            explain: 0,
+            profile: false,
+            fuzzy: false,
+            custom_rules: Vec::new(),
+        }
+    }
+}
+
+/// Compile glob patterns (e.g. `coverage`, `docs/_build`) into a `GlobSet`.
+pub fn build_globset(patterns: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build()
+}
+
+/// Compile gitignore-syntax rules (e.g. `*.generated.ts`, `!keep.ts`)
+/// relative to `root`.
+pub fn build_ignore(root: &Path, patterns: &[String]) -> Result<Gitignore, ignore::Error> {
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in patterns {
+        builder.add_line(None, pattern)?;
+    }
+    builder.build()
+}
+
+/// Decides whether a path under a scan root would be skipped by the walk,
+/// so callers reacting to filesystem events (`pb-scan --watch`) can ignore
+/// churn in `target/`, `node_modules/` and the like. Applies `SKIP_DIRS`,
+/// hidden paths, `exclude`, `ignore` and the root's own `.gitignore`.
+#[derive(Debug)]
+pub struct PathFilter {
+    root: PathBuf,
+    gitignore: Option<Gitignore>,
+    extra: Gitignore,
+    options: ScanOptions,
+}
+
+impl PathFilter {
+    pub fn new(root: &Path, options: &ScanOptions) -> Self {
+        let gitignore = options.respect_gitignore.then(|| {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add(root.join(".gitignore"));
+            builder.build().unwrap_or_else(|_| Gitignore::empty())
+        });
+        Self {
+            root: root.to_path_buf(),
+            gitignore,
+            extra: build_ignore(root, &options.ignore).unwrap_or_else(|_| Gitignore::empty()),
+            options: options.clone(),
+        }
+    }
+
+    /// Whether `path` is outside the root or would not be scanned.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        let skipped = relative.components().any(|c| {
+            c.as_os_str().to_str().is_some_and(|name| {
+                SKIP_DIRS.contains(&name) || (!self.options.include_hidden && name.starts_with('.'))
+            })
+        });
+        if skipped || self.options.exclude.is_match(relative) {
+            return true;
+        }
+        let is_dir = path.is_dir();
+        self.extra
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+            || self
+                .gitignore
+                .as_ref()
+                .is_some_and(|g| g.matched_path_or_any_parents(path, is_dir).is_ignore())
+    }
+}
+
+/// Raw scan data before percentage conversion. Byte counts add up exactly
+/// across roots, so callers can merge any number of scans with
+/// [`RawScan::merge`] and call [`RawScan::finalize`] once at the end.
+#[derive(Debug, Clone, Default)]
+pub struct RawScan {
+    pub bytes_by_lang: HashMap<String, u64>,
+    pub frameworks: HashMap<String, SignalEntry>,
+    pub infra: HashMap<String, SignalEntry>,
+    pub project_structures: BTreeSet<String>,
+    pub total_files: u64,
+    pub total_bytes: u64,
+    /// Binary files and pruned entries; see [`ScanStats::files_skipped`].
+    pub skipped_files: u64,
+    /// Bytes of counted files that are tests; see [`is_test_path`].
+    pub test_bytes: u64,
+    /// Bytes of counted files that are not tests.
+    pub source_bytes: u64,
+    /// Entries that could not be read, as `path: error`. Walk and metadata
+    /// errors are always recorded; unopenable files only with
+    /// `options.strict`.
+    pub unreadable: Vec<String>,
+    /// See [`ScanResult::license`].
+    pub license: Option<String>,
+    /// Whether any root has a top-level `README*` file.
+    pub has_readme: bool,
+    /// Combined size of those READMEs.
+    pub readme_bytes: u64,
+    /// Fenced code blocks in those READMEs, by language.
+    pub readme_fences: HashMap<String, u64>,
+    /// See [`ScanResult::build_targets`].
+    pub build_targets: Vec<String>,
+    pub bytes_by_dir: Option<HashMap<String, HashMap<String, u64>>>,
+    /// Example files per language; only with `options.explain`.
+    pub examples: Option<LanguageExamples>,
+    /// Time spent per phase so far; only with `options.profile`.
+    pub profile: Option<ScanProfile>,
+}
+
+/// The heaviest files seen for each language, up to a limit, so a result
+/// can show where a language's share comes from.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageExamples {
+    limit: usize,
+    by_lang: HashMap<String, Vec<(u64, PathBuf)>>,
+}
+
+impl LanguageExamples {
+    pub fn new(limit: usize) -> Self {
+        LanguageExamples {
+            limit,
+            by_lang: HashMap::new(),
+        }
+    }
+
+    /// Offer `path` as an example of each language it was weighed as.
+    pub fn add(&mut self, path: &Path, recorded: &[(&'static str, u64)]) {
+        for &(lang, weight) in recorded {
+            let files = self.by_lang.entry(lang.to_string()).or_default();
+            files.push((weight, path.to_path_buf()));
+            keep_heaviest(files, self.limit);
+        }
+    }
+
+    /// Fold in examples from another scan, keeping the larger limit.
+    pub fn merge(&mut self, other: LanguageExamples) {
+        self.limit = self.limit.max(other.limit);
+        for (lang, files) in other.by_lang {
+            let target = self.by_lang.entry(lang).or_default();
+            target.extend(files);
+            keep_heaviest(target, self.limit);
+        }
+    }
+
+    /// Example paths for each of `languages`, heaviest first.
+    fn for_languages(&self, languages: &[LanguageEntry]) -> BTreeMap<String, Vec<String>> {
+        languages
+            .iter()
+            .filter_map(|lang| {
+                let files = self.by_lang.get(&lang.name)?;
+                let paths = files
+                    .iter()
+                    .map(|(_, path)| path.to_string_lossy().into_owned())
+                    .collect();
+                Some((lang.name.clone(), paths))
+            })
+            .collect()
+    }
+}
+
+/// Sort `files` by weight (then path) and drop all but the first `limit`.
+fn keep_heaviest(files: &mut Vec<(u64, PathBuf)>, limit: usize) {
+    files.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    files.truncate(limit);
+}
+
+impl RawScan {
+    /// Fold another raw scan into this one.
+    pub fn merge(&mut self, other: RawScan) {
+        for (lang, bytes) in other.bytes_by_lang {
+            *self.bytes_by_lang.entry(lang).or_insert(0) += bytes;
+        }
+        for entry in other.frameworks.into_values() {
+            insert_signal(&mut self.frameworks, entry);
+        }
+        for entry in other.infra.into_values() {
+            insert_signal(&mut self.infra, entry);
+        }
+        self.project_structures.extend(other.project_structures);
+        self.total_files += other.total_files;
+        self.total_bytes += other.total_bytes;
+        self.skipped_files += other.skipped_files;
+        self.test_bytes += other.test_bytes;
+        self.source_bytes += other.source_bytes;
+        self.unreadable.extend(other.unreadable);
+        // The first recognized license wins over `unknown` or none.
+        if matches!(self.license.as_deref(), None | Some("unknown")) && other.license.is_some() {
+            self.license = other.license;
+        }
+        self.has_readme |= other.has_readme;
+        self.readme_bytes += other.readme_bytes;
+        for (lang, count) in other.readme_fences {
+            *self.readme_fences.entry(lang).or_insert(0) += count;
+        }
+        for target in other.build_targets {
+            if !self.build_targets.contains(&target) {
+                self.build_targets.push(target);
+            }
+        }
+        if let Some(other_dirs) = other.bytes_by_dir {
+            let dirs = self.bytes_by_dir.get_or_insert_with(HashMap::new);
+            for (dir, langs) in other_dirs {
+                let target = dirs.entry(dir).or_default();
+                for (lang, bytes) in langs {
+                    *target.entry(lang).or_insert(0) += bytes;
+                }
+            }
+        }
+        if let Some(other_examples) = other.examples {
+            self.examples
+                .get_or_insert_with(|| LanguageExamples::new(other_examples.limit))
+                .merge(other_examples);
+        }
+        if let Some(other_profile) = other.profile {
+            self.profile
+                .get_or_insert_with(ScanProfile::default)
+                .add(&other_profile);
+        }
+    }
+
+    /// Apply result-level options, then finalize. `started` is when the
+    /// scan began, for `options.collect_stats`.
+    fn finalize_with(mut self, options: &ScanOptions, started: Instant) -> ScanResult {
+        let finalize_started = Instant::now();
+        if options.code_only {
+            drop_markup_languages(&mut self.bytes_by_lang);
+            if let Some(dirs) = self.bytes_by_dir.as_mut() {
+                dirs.values_mut().for_each(drop_markup_languages);
+                dirs.retain(|_, langs| !langs.is_empty());
+            }
+        }
+        drop_minor_languages(&mut self.bytes_by_lang, options.min_percentage);
+        let stats = options.collect_stats.then(|| ScanStats {
+            elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+            files_scanned: self.total_files,
+            files_skipped: self.skipped_files,
+            bytes_read: self.total_bytes,
+        });
+        let profile = self.profile.take();
+        let result = self.finalize();
+        ScanResult {
+            stats,
+            profile: profile.map(|profile| ScanProfile {
+                finalize: finalize_started.elapsed(),
+                ..profile
+            }),
+            ..result
+        }
+    }
+
+    /// Convert accumulated counts into the public result shape.
+    pub fn finalize(self) -> ScanResult {
+        let languages = build_language_list(&self.bytes_by_lang);
+        let examples = self
+            .examples
+            .map(|examples| examples.for_languages(&languages));
+        ScanResult {
+            primary_language: primary_language(&languages),
+            license: self.license,
+            languages,
+            has_readme: self.has_readme,
+            readme_bytes: self.has_readme.then_some(self.readme_bytes),
+            readme_languages: readme_languages(&self.readme_fences),
+            build_targets: self.build_targets,
+            frameworks: into_sorted_entries(&self.frameworks),
+            project_structures: self.project_structures.into_iter().collect(),
+            infrastructure_signals: into_sorted_entries(&self.infra),
+            total_files: self.total_files,
+            total_bytes: self.total_bytes,
+            test_bytes: Some(self.test_bytes),
+            source_bytes: Some(self.source_bytes),
+            per_directory: self.bytes_by_dir.map(|dirs| {
+                dirs.iter()
+                    .map(|(dir, bytes)| (dir.clone(), build_language_list(bytes)))
+                    .collect()
+            }),
+            examples,
+            stats: None,
+            profile: self.profile,
+        }
+    }
+}
+
+/// Directory key for the per-directory breakdown: the top-level directory,
+/// or `packages/<name>` for files inside a monorepo package dir. Files at
+/// the root belong to no directory.
+fn directory_key(relative: &Path) -> Option<String> {
+    let mut parts = relative.iter().filter_map(|p| p.to_str());
+    let top = parts.next()?;
+    let rest: Vec<&str> = parts.collect();
+    if rest.is_empty() {
+        return None;
+    }
+    if MONOREPO_DIRS.contains(&top) && rest.len() > 1 {
+        return Some(format!("{top}/{}", rest[0]));
+    }
+    Some(top.to_string())
+}
+
+/// Whether the file at `relative` is test code: it sits under one of
+/// `TEST_DIRS`, or is named like `*_test.*` or `*.spec.*`.
+fn is_test_path(relative: &Path) -> bool {
+    let Some(parent) = relative.parent() else {
+        return false;
+    };
+    if parent
+        .iter()
+        .any(|dir| dir.to_str().is_some_and(|d| TEST_DIRS.contains(&d)))
+    {
+        return true;
+    }
+    relative
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|name| name.contains("_test.") || name.contains(".spec."))
+}
+
+/// Identify the license from the first of [`LICENSE_FILES`] among
+/// `top_level_names`: an `SPDX-License-Identifier:` line wins, then the
+/// texts in [`LICENSE_PATTERNS`]. Unrecognized or unreadable files give
+/// `unknown`; no license file gives `None`.
+fn detect_license(root: &(impl RepoFiles + ?Sized), top_level_names: &[String]) -> Option<String> {
+    let file = LICENSE_FILES
+        .iter()
+        .find(|f| top_level_names.iter().any(|n| n == *f))?;
+    let Ok(content) = root.read_to_string(Path::new(file)) else {
+        return Some("unknown".to_string());
+    };
+
+    for line in content.lines() {
+        if let Some((_, id)) = line.split_once("SPDX-License-Identifier:") {
+            if let Some(id) = id.split_whitespace().next() {
+                return Some(id.to_string());
+            }
+        }
+    }
+
+    let text = content
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let id = LICENSE_PATTERNS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|p| text.contains(p)))
+        .map_or("unknown", |&(id, _)| id);
+    Some(id.to_string())
+}
+
+/// Record the top-level `README*` among `top_level_names`, if any: its
+/// size and the languages of its fenced code blocks. Fences never add to
+/// `bytes_by_lang`, so they stay a hint next to real source.
+fn detect_readme(root: &(impl RepoFiles + ?Sized), top_level_names: &[String], raw: &mut RawScan) {
+    let Some(name) = top_level_names
+        .iter()
+        .filter(|n| n.to_ascii_uppercase().starts_with("README"))
+        .min()
+    else {
+        return;
+    };
+    raw.has_readme = true;
+    let Ok(content) = root.read_to_string(Path::new(name)) else {
+        return;
+    };
+    raw.readme_bytes += content.len() as u64;
+    for lang in fence_languages(&content) {
+        *raw.readme_fences.entry(lang.to_string()).or_insert(0) += 1;
+    }
+}
+
+/// Languages of the fenced code blocks (```` ``` ```` or `~~~`) in a
+/// Markdown document, one per recognized opening fence.
+fn fence_languages(markdown: &str) -> Vec<&'static str> {
+    let mut languages = Vec::new();
+    let mut open: Option<&str> = None;
+    for line in markdown.lines() {
+        let line = line.trim_start();
+        let Some(marker) = ["```", "~~~"].into_iter().find(|m| line.starts_with(m)) else {
+            continue;
+        };
+        match open {
+            Some(fence) if fence == marker => open = None,
+            Some(_) => {}
+            None => {
+                open = Some(marker);
+                let tag = line.trim_start_matches(marker.as_bytes()[0] as char);
+                let tag = tag
+                    .trim_start_matches(|c: char| c.is_whitespace() || c == '{' || c == '.')
+                    .split(|c: char| c.is_whitespace() || c == ',' || c == '}')
+                    .next()
+                    .unwrap_or("");
+                if let Some(lang) = fence_language(tag) {
+                    languages.push(lang);
+                }
+            }
+        }
+    }
+    languages
+}
+
+/// README fence languages, most fences first, then by name.
+fn readme_languages(fences: &HashMap<String, u64>) -> Vec<String> {
+    let mut entries: Vec<(&String, &u64)> = fences.iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    entries.into_iter().map(|(lang, _)| lang.clone()).collect()
 }
 
 /// Check for hidden-file indicators that the gitignore-aware walker skips.
@@ -50,21 +622,70 @@ fn check_hidden_indicators(root: &Path, top_level_names: &mut Vec<String>) {
     }
 }
 
-/// Scan a single directory, returning raw byte counts and detection results.
-fn scan_directory_raw(root: &Path) -> RawScanResult {
-    let mut bytes_by_lang: HashMap<String, u64> = HashMap::new();
+/// Walk `root` once and accumulate byte counts and detection results into `raw`.
+fn scan_into(root: &Path, options: &ScanOptions, raw: &mut RawScan) {
     let mut top_level_names: Vec<String> = Vec::new();
-    let mut frameworks: HashMap<String, String> = HashMap::new();
-    let mut infra: HashMap<String, String> = HashMap::new();
+    // Indicator names found below the top level, e.g. `deploy/Dockerfile`.
+    let mut nested_indicators: BTreeSet<String> = BTreeSet::new();
+    let mut schema_files: BTreeSet<PathBuf> = BTreeSet::new();
+    if options.per_directory {
+        raw.bytes_by_dir.get_or_insert_with(HashMap::new);
+    }
+    // Examples are reported as absolute paths.
+    let example_root = (options.explain > 0).then(|| {
+        raw.examples
+            .get_or_insert_with(|| LanguageExamples::new(options.explain));
+        root.canonicalize().unwrap_or_else(|_| root.to_path_buf())
+    });
+    if options.profile {
+        raw.profile.get_or_insert_with(ScanProfile::default);
+    }
+    let walk_started = Instant::now();
 
+    let exclude = options.exclude.clone();
+    let generated = generated_globset(options);
+    // Patterns are validated up front by the CLI; a library caller passing
+    // a bad one just gets no extra rules.
+    let extra_ignore = build_ignore(root, &options.ignore).unwrap_or_else(|_| Gitignore::empty());
+    let walk_root = root.to_path_buf();
+    let pruned = Arc::new(AtomicU64::new(0));
+    let pruned_in_filter = Arc::clone(&pruned);
     let walker = WalkBuilder::new(root)
-        .hidden(true) // skip hidden files/dirs
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
+        .hidden(!options.include_hidden)
+        .git_ignore(options.respect_gitignore)
+        .git_global(options.respect_gitignore)
+        .git_exclude(options.respect_gitignore)
+        .follow_links(options.follow_symlinks)
+        .max_depth(options.max_depth.map(|d| d.max(1)))
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            let skipped = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name == ".git" || (is_dir && SKIP_DIRS.contains(&name)));
+            let keep = !skipped
+                && !extra_ignore.matched(entry.path(), is_dir).is_ignore()
+                && entry
+                    .path()
+                    .strip_prefix(&walk_root)
+                    .map_or(true, |rel| !exclude.is_match(rel));
+            if !keep {
+                pruned_in_filter.fetch_add(1, Ordering::Relaxed);
+            }
+            keep
+        })
         .build();
 
-    for entry in walker.flatten() {
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            // Symlink cycles are expected with `follow_symlinks`, not errors.
+            Err(e) if is_symlink_loop(&e) => continue,
+            Err(e) => {
+                raw.unreadable.push(e.to_string());
+                continue;
+            }
+        };
         let path = entry.path();
 
         // Compute depth relative to root.
@@ -85,91 +706,410 @@ fn scan_directory_raw(root: &Path) -> RawScanResult {
             if let Some(name) = relative.file_name().and_then(|n| n.to_str()) {
                 top_level_names.push(name.to_string());
             }
+        } else {
+            if let Some(name) = relative.file_name().and_then(|n| n.to_str()) {
+                if is_indicator_name(name) {
+                    nested_indicators.insert(name.to_string());
+                }
+            }
+            if options.include_hidden {
+                // Nested hidden indicators such as `.github/workflows`.
+                let rel = relative.to_string_lossy().replace('\\', "/");
+                if HIDDEN_INDICATORS.contains(&rel.as_str()) {
+                    top_level_names.push(rel);
+                }
+            }
         }
 
-        // Skip known junk directories.
+        // Directories only matter for top-level names; known junk
+        // directories were already pruned by `filter_entry`.
         if entry.file_type().is_some_and(|ft| ft.is_dir()) {
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if SKIP_DIRS.contains(&name) {
-                    continue;
-                }
-            }
-            continue; // skip directories for language counting
+            continue;
+        }
+        if is_schema_file(relative) {
+            schema_files.insert(relative.to_path_buf());
+        }
+
+        // Depth 1 is walked even with --max-depth 0, for top-level names only.
+        if options.max_depth.is_some_and(|max| depth > max) {
+            continue;
         }
 
-        // Skip binary files.
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            if is_binary_extension(ext) {
+        let meta = match entry.metadata() {
+            Ok(m) => m,
+            Err(e) => {
+                raw.unreadable.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+        if options.strict {
+            if let Err(e) = fs::File::open(path) {
+                raw.unreadable.push(format!("{}: {e}", path.display()));
                 continue;
             }
         }
-
-        // Count bytes per language.
-        if let Ok(meta) = entry.metadata() {
-            record_language(path, meta.len(), &mut bytes_by_lang);
+        let recorded = count_file(
+            raw,
+            relative,
+            meta.len(),
+            options,
+            generated.as_ref(),
+            |bytes_by_lang| record_language(path, meta.len(), options, bytes_by_lang),
+        );
+        if let (Some(examples), Some(example_root)) = (raw.examples.as_mut(), &example_root) {
+            examples.add(&example_root.join(relative), &recorded);
         }
     }
 
+    raw.skipped_files += pruned.load(Ordering::Relaxed);
+    if let Some(profile) = raw.profile.as_mut() {
+        profile.walk += walk_started.elapsed();
+    }
+
+    if options.languages_only {
+        return;
+    }
     // Check for hidden indicators the walker skips (e.g. .github/workflows).
-    check_hidden_indicators(root, &mut top_level_names);
+    // With hidden files walked, they were already recorded in the loop.
+    if !options.include_hidden {
+        check_hidden_indicators(root, &mut top_level_names);
+    }
+    detect_signals(
+        root,
+        top_level_names,
+        nested_indicators,
+        schema_files,
+        options,
+        raw,
+    );
+}
+
+/// Whether a walk error is (or wraps) a symlink cycle.
+fn is_symlink_loop(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. }
+        | ignore::Error::WithDepth { err, .. }
+        | ignore::Error::WithLineNumber { err, .. } => is_symlink_loop(err),
+        _ => false,
+    }
+}
+
+/// Compile `GENERATED_GLOBS` when `options.skip_generated` asks for them.
+fn generated_globset(options: &ScanOptions) -> Option<GlobSet> {
+    options.skip_generated.then(|| {
+        let patterns: Vec<String> = GENERATED_GLOBS.iter().map(|g| g.to_string()).collect();
+        build_globset(&patterns).expect("GENERATED_GLOBS are valid globs")
+    })
+}
+
+/// Whether files with extension `ext` are skipped as binary: the built-in
+/// list, adjusted by `options.binary_extensions` and `text_extensions`.
+fn is_binary(ext: &str, options: &ScanOptions) -> bool {
+    let listed = |list: &[String]| {
+        list.iter()
+            .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(ext))
+    };
+    if listed(&options.text_extensions) {
+        return false;
+    }
+    listed(&options.binary_extensions) || is_binary_extension(ext)
+}
+
+/// Count one file of `size` bytes at `relative` into `raw`. Binary (see
+/// [`is_binary`]) and generated files are only counted as skipped; anything else is weighed
+/// by `record`, which adds its languages to the map it is given. Returns
+/// the languages and weights recorded.
+fn count_file(
+    raw: &mut RawScan,
+    relative: &Path,
+    size: u64,
+    options: &ScanOptions,
+    generated: Option<&GlobSet>,
+    record: impl FnOnce(&mut HashMap<String, u64>) -> Vec<(&'static str, u64)>,
+) -> Vec<(&'static str, u64)> {
+    raw.total_bytes += size;
+
+    // Skip binary files.
+    if let Some(ext) = relative.extension().and_then(|e| e.to_str()) {
+        if is_binary(ext, options) {
+            raw.skipped_files += 1;
+            return Vec::new();
+        }
+    }
+    if let (Some(generated), Some(name)) = (generated, relative.file_name()) {
+        if generated.is_match(name) {
+            raw.skipped_files += 1;
+            return Vec::new();
+        }
+    }
+    raw.total_files += 1;
+    if is_test_path(relative) {
+        raw.test_bytes += size;
+    } else {
+        raw.source_bytes += size;
+    }
 
-    // Detect frameworks from file indicators.
-    detect_file_indicators(&top_level_names, &mut frameworks, &mut infra);
+    // Count bytes per language.
+    let recorded = record(&mut raw.bytes_by_lang);
+    if let (Some(dirs), Some(key)) = (raw.bytes_by_dir.as_mut(), directory_key(relative)) {
+        if !recorded.is_empty() {
+            let dir = dirs.entry(key).or_default();
+            for &(lang, weight) in &recorded {
+                *dir.entry(lang.to_string()).or_insert(0) += weight;
+            }
+        }
+    }
+    recorded
+}
+
+/// Detect frameworks, infrastructure and structures from the names seen
+/// during a walk and from the manifests under `root`.
+fn detect_signals(
+    root: &(impl RepoFiles + ?Sized),
+    mut top_level_names: Vec<String>,
+    nested_indicators: BTreeSet<String>,
+    schema_files: BTreeSet<PathBuf>,
+    options: &ScanOptions,
+    raw: &mut RawScan,
+) {
+    if options.languages_only {
+        return;
+    }
+    let started = Instant::now();
+    top_level_names.retain(|name| !options.exclude.is_match(name));
+    let root = &Excluding::new(root, &options.exclude);
+
+    // Detect frameworks from file indicators at any depth. Vendored and
+    // build directories never reach here, so their files can't leak in.
+    let mut indicator_names = top_level_names.clone();
+    indicator_names.extend(nested_indicators);
+    detect_file_indicators(&indicator_names, &mut raw.frameworks, &mut raw.infra);
+    detect_schema_files(root, &schema_files, &mut raw.frameworks);
 
     // Detect structures.
-    let project_structures = detect_structures(&top_level_names);
+    raw.project_structures
+        .extend(detect_structures(&top_level_names));
+    raw.project_structures
+        .extend(detect_manifest_structures(root));
 
     // Parse dependency files.
-    dependencies::detect_all(root, &mut frameworks);
+    let dependencies_started = Instant::now();
+    dependencies::detect_all(root, &mut raw.frameworks, &mut raw.infra);
+    dependencies::detect_custom(
+        root,
+        &options.custom_rules,
+        &mut raw.frameworks,
+        &mut raw.infra,
+    );
+    if options.fuzzy {
+        dependencies::detect_fuzzy(root, &mut raw.frameworks);
+    }
+    if top_level_names.iter().any(|n| n == "Dockerfile") {
+        dependencies::detect_dockerfile_details(root, &mut raw.infra);
+    }
+    for target in dependencies::detect_make_targets(root) {
+        if !raw.build_targets.contains(&target) {
+            raw.build_targets.push(target);
+        }
+    }
+    let dependencies_elapsed = dependencies_started.elapsed();
 
-    RawScanResult {
-        bytes_by_lang,
-        frameworks,
-        infra,
-        project_structures,
+    raw.license = detect_license(root, &top_level_names);
+    detect_readme(root, &top_level_names, raw);
+    if let Some(profile) = raw.profile.as_mut() {
+        profile.dependencies += dependencies_elapsed;
+        profile.structures += started.elapsed() - dependencies_elapsed;
     }
 }
 
 /// Scan a single directory and return aggregated results.
 pub fn scan_directory(root: &Path) -> ScanResult {
-    let raw = scan_directory_raw(root);
-    ScanResult {
-        languages: build_language_list(&raw.bytes_by_lang),
-        frameworks: into_sorted_entries(&raw.frameworks),
-        project_structures: raw.project_structures,
-        infrastructure_signals: into_sorted_entries(&raw.infra),
-    }
+    scan_directory_with(root, &ScanOptions::default())
+}
+
+/// Scan a single directory with explicit options.
+pub fn scan_directory_with(root: &Path, options: &ScanOptions) -> ScanResult {
+    let started = Instant::now();
+    scan_directory_raw_with(root, options).finalize_with(options, started)
+}
+
+/// Scan a single directory and return the counts before percentage
+/// conversion.
+pub fn scan_directory_raw(root: &Path) -> RawScan {
+    scan_directory_raw_with(root, &ScanOptions::default())
+}
+
+/// Like [`scan_directory_raw`], with explicit options.
+pub fn scan_directory_raw_with(root: &Path, options: &ScanOptions) -> RawScan {
+    let mut raw = RawScan::default();
+    scan_into(root, options, &mut raw);
+    raw
 }
 
 /// Scan multiple directories and merge results.
 pub fn scan_directories(roots: &[&Path]) -> ScanResult {
-    let mut bytes_by_lang: HashMap<String, u64> = HashMap::new();
-    let mut frameworks: HashMap<String, String> = HashMap::new();
-    let mut infra: HashMap<String, String> = HashMap::new();
-    let mut all_structures: std::collections::BTreeSet<String> = std::collections::BTreeSet::new();
+    scan_directories_with(roots, &ScanOptions::default())
+}
 
+/// Scan multiple directories with explicit options and merge results.
+pub fn scan_directories_with(roots: &[&Path], options: &ScanOptions) -> ScanResult {
+    let started = Instant::now();
+    let mut raw = RawScan::default();
     for root in roots {
-        let raw = scan_directory_raw(root);
+        scan_into(root, options, &mut raw);
+    }
+    raw.finalize_with(options, started)
+}
 
-        // Merge byte counts for accurate cross-root language percentages.
-        for (lang, bytes) in raw.bytes_by_lang {
-            *bytes_by_lang.entry(lang).or_insert(0) += bytes;
+/// Entries a strict scan could not read; see [`try_scan_directories_with`].
+#[derive(Debug)]
+pub struct UnreadableFiles(pub Vec<String>);
+
+impl fmt::Display for UnreadableFiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot read {} entries:", self.0.len())?;
+        for entry in &self.0 {
+            write!(f, "\n  {entry}")?;
         }
-        for (name, category) in raw.frameworks {
-            frameworks.insert(name, category);
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnreadableFiles {}
+
+/// Like [`scan_directories_with`], but fails if any entry could not be read
+/// (permission denied, dangling symlink, ...) instead of leaving it out.
+/// Set `options.strict` to also catch files whose metadata is readable but
+/// whose contents are not.
+pub fn try_scan_directories_with(
+    roots: &[&Path],
+    options: &ScanOptions,
+) -> Result<ScanResult, UnreadableFiles> {
+    let started = Instant::now();
+    let mut raw = RawScan::default();
+    for root in roots {
+        scan_into(root, options, &mut raw);
+    }
+    if !raw.unreadable.is_empty() {
+        return Err(UnreadableFiles(std::mem::take(&mut raw.unreadable)));
+    }
+    Ok(raw.finalize_with(options, started))
+}
+
+/// Scan a repository already held in memory as `(path, size, contents)`
+/// entries, e.g. from a tarball or a git tree object, without touching
+/// disk. Paths are relative to the repository root.
+pub fn scan_from_entries(entries: &[(PathBuf, u64, Option<Vec<u8>>)]) -> ScanResult {
+    scan_from_entries_with(entries, &ScanOptions::default())
+}
+
+/// Like [`scan_from_entries`], with explicit options. Entries stand in for
+/// a walk of the tree: the skip list, hidden paths, `exclude`, `ignore`
+/// and `max_depth` apply as usual, but there is no `.gitignore` to honor.
+/// Languages are detected from contents where given (see
+/// [`record_language_in_memory`]) and manifests are parsed from them.
+pub fn scan_from_entries_with(
+    entries: &[(PathBuf, u64, Option<Vec<u8>>)],
+    options: &ScanOptions,
+) -> ScanResult {
+    let started = Instant::now();
+    let mut raw = RawScan::default();
+    if options.per_directory {
+        raw.bytes_by_dir.get_or_insert_with(HashMap::new);
+    }
+    if options.explain > 0 {
+        raw.examples = Some(LanguageExamples::new(options.explain));
+    }
+    if options.profile {
+        raw.profile = Some(ScanProfile::default());
+    }
+    let generated = generated_globset(options);
+    let extra_ignore =
+        build_ignore(Path::new(""), &options.ignore).unwrap_or_else(|_| Gitignore::empty());
+
+    let mut top_level_names: BTreeSet<String> = BTreeSet::new();
+    let mut nested_indicators: BTreeSet<String> = BTreeSet::new();
+    let mut schema_files: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut pruned: BTreeSet<PathBuf> = BTreeSet::new();
+    let walk_started = Instant::now();
+    'entries: for (relative, size, contents) in entries {
+        let names: Vec<&str> = relative.iter().filter_map(|c| c.to_str()).collect();
+        let depth = names.len();
+
+        // Hidden indicators count even where hidden paths aren't scanned.
+        for end in 1..=depth {
+            let prefix = names[..end].join("/");
+            if HIDDEN_INDICATORS.contains(&prefix.as_str()) {
+                top_level_names.insert(prefix);
+            }
         }
-        for (name, category) in raw.infra {
-            infra.insert(name, category);
+
+        let mut prefix = PathBuf::new();
+        for (i, &name) in names.iter().enumerate() {
+            prefix.push(name);
+            let is_dir = i + 1 < depth;
+            if name.starts_with('.') && !options.include_hidden {
+                continue 'entries;
+            }
+            if name == ".git"
+                || (is_dir && SKIP_DIRS.contains(&name))
+                || extra_ignore.matched(&prefix, is_dir).is_ignore()
+                || options.exclude.is_match(&prefix)
+            {
+                if pruned.insert(prefix) {
+                    raw.skipped_files += 1;
+                }
+                continue 'entries;
+            }
+            if i == 0 {
+                top_level_names.insert(name.to_string());
+            } else if is_indicator_name(name) {
+                nested_indicators.insert(name.to_string());
+            }
         }
-        all_structures.extend(raw.project_structures);
-    }
 
-    ScanResult {
-        languages: build_language_list(&bytes_by_lang),
-        frameworks: into_sorted_entries(&frameworks),
-        project_structures: all_structures.into_iter().collect(),
-        infrastructure_signals: into_sorted_entries(&infra),
+        if is_schema_file(relative) {
+            schema_files.insert(relative.clone());
+        }
+
+        if options.max_depth.is_some_and(|max| depth > max) {
+            continue;
+        }
+        let recorded = count_file(
+            &mut raw,
+            relative,
+            *size,
+            options,
+            generated.as_ref(),
+            |bytes_by_lang| {
+                record_language_in_memory(
+                    relative,
+                    *size,
+                    contents.as_deref(),
+                    options,
+                    bytes_by_lang,
+                )
+            },
+        );
+        if let Some(examples) = raw.examples.as_mut() {
+            examples.add(relative, &recorded);
+        }
+    }
+    if let Some(profile) = raw.profile.as_mut() {
+        profile.walk += walk_started.elapsed();
     }
+
+    let tree = MemoryTree::new(entries);
+    detect_signals(
+        &tree,
+        top_level_names.into_iter().collect(),
+        nested_indicators,
+        schema_files,
+        options,
+        &mut raw,
+    );
+    raw.finalize_with(options, started)
 }
 
 #[cfg(test)]
@@ -186,6 +1126,8 @@ mod tests {
         assert!(result.frameworks.is_empty());
         assert!(result.project_structures.is_empty());
         assert!(result.infrastructure_signals.is_empty());
+        assert_eq!(result.total_files, 0);
+        assert_eq!(result.total_bytes, 0);
     }
 
     #[test]
@@ -196,6 +1138,8 @@ mod tests {
         assert_eq!(result.languages.len(), 1);
         assert_eq!(result.languages[0].name, "Python");
         assert_eq!(result.languages[0].percentage, 100.0);
+        assert_eq!(result.total_files, 1);
+        assert_eq!(result.total_bytes, 14);
     }
 
     #[test]
@@ -206,6 +1150,8 @@ mod tests {
         let result = scan_directory(tmp.path());
         assert_eq!(result.languages.len(), 1);
         assert_eq!(result.languages[0].name, "Python");
+        assert_eq!(result.total_files, 1);
+        assert_eq!(result.total_bytes, 21);
     }
 
     #[test]
@@ -231,6 +1177,782 @@ mod tests {
             .any(|s| s.name == "Docker"));
     }
 
+    #[test]
+    fn test_scan_line_mode() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("bundle.js"), "x=1;".repeat(5000)).unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hello')\n".repeat(20)).unwrap();
+
+        let by_bytes = scan_directory(tmp.path());
+        assert_eq!(by_bytes.languages[0].name, "JavaScript");
+
+        let options = ScanOptions {
+            count_lines: true,
+            ..Default::default()
+        };
+        let by_lines = scan_directory_with(tmp.path(), &options);
+        assert_eq!(by_lines.languages[0].name, "Python");
+        assert!(by_lines.languages[0].percentage > 90.0);
+    }
+
+    #[test]
+    fn test_scan_skip_generated() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("app.py"), "print('hello')").unwrap();
+        fs::create_dir(tmp.path().join("static")).unwrap();
+        fs::write(
+            tmp.path().join("static/bundle.min.js"),
+            "var a=1;".repeat(100),
+        )
+        .unwrap();
+
+        let result = scan_directory(tmp.path());
+        assert_eq!(result.languages[0].name, "JavaScript");
+        assert_eq!(result.total_files, 2);
+
+        let options = ScanOptions {
+            skip_generated: true,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        let names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, vec!["Python"]);
+        assert_eq!(result.total_files, 1);
+    }
+
+    #[test]
+    fn test_generated_globs_are_valid() {
+        let patterns: Vec<String> = GENERATED_GLOBS.iter().map(|g| g.to_string()).collect();
+        assert!(build_globset(&patterns).is_ok());
+    }
+
+    #[test]
+    fn test_scan_max_file_size() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::write(
+            tmp.path().join("huge.sql"),
+            "INSERT INTO t VALUES (1);\n".repeat(200),
+        )
+        .unwrap();
+
+        let uncapped = scan_directory(tmp.path());
+        assert_eq!(uncapped.languages[0].name, "SQL");
+
+        let options = ScanOptions {
+            max_file_size: 1024,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert_eq!(result.languages.len(), 1);
+        assert_eq!(result.languages[0].name, "Rust");
+        assert_eq!(result.languages[0].percentage, 100.0);
+        assert_eq!(result.total_files, 2);
+    }
+
+    #[test]
+    fn test_scan_max_depth() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(tmp.path().join("a/b/c")).unwrap();
+        fs::write(tmp.path().join("a/shallow.go"), "package a").unwrap();
+        fs::write(tmp.path().join("a/b/c/deep.py"), "print('deep')").unwrap();
+        fs::create_dir_all(tmp.path().join(".github/workflows")).unwrap();
+
+        let options = ScanOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        let names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        assert!(names.contains(&"Rust"));
+        assert!(names.contains(&"Go"));
+        assert!(!names.contains(&"Python"));
+
+        let options = ScanOptions {
+            max_depth: Some(0),
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert!(result.languages.is_empty());
+        assert_eq!(result.total_files, 0);
+        assert!(result
+            .infrastructure_signals
+            .iter()
+            .any(|s| s.name == "GitHub Actions"));
+    }
+
+    #[test]
+    fn test_scan_exclude_glob() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hello')").unwrap();
+        fs::create_dir(tmp.path().join("fixtures")).unwrap();
+        fs::write(tmp.path().join("fixtures/sample.rs"), "fn main() {}").unwrap();
+        fs::write(tmp.path().join("fixtures/Dockerfile"), "FROM rust").unwrap();
+
+        let options = ScanOptions {
+            exclude: build_globset(&["fixtures".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        let names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, vec!["Python"]);
+    }
+
+    #[test]
+    fn test_scan_exclude_manifests() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package.json"),
+            r#"{"dependencies": {"react": "18.0.0"}}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(tmp.path().join("legacy/Api")).unwrap();
+        fs::write(
+            tmp.path().join("legacy/Api/Api.csproj"),
+            r#"<Project Sdk="Microsoft.NET.Sdk.Web"></Project>"#,
+        )
+        .unwrap();
+
+        let result = scan_directory(tmp.path());
+        assert!(result.frameworks.iter().any(|s| s.name == "React"));
+        assert!(result.frameworks.iter().any(|s| s.name == "ASP.NET Core"));
+
+        let options = ScanOptions {
+            exclude: build_globset(&["package.json".to_string(), "legacy".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert!(result.frameworks.is_empty(), "{:?}", result.frameworks);
+
+        let entries: Vec<(PathBuf, u64, Option<Vec<u8>>)> =
+            ["package.json", "legacy/Api/Api.csproj"]
+                .iter()
+                .map(|name| {
+                    let contents = fs::read(tmp.path().join(name)).unwrap();
+                    (PathBuf::from(name), contents.len() as u64, Some(contents))
+                })
+                .collect();
+        let result = scan_from_entries_with(&entries, &options);
+        assert!(result.frameworks.is_empty(), "{:?}", result.frameworks);
+    }
+
+    #[test]
+    fn test_scan_nested_indicators() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("deploy")).unwrap();
+        fs::write(tmp.path().join("deploy/Dockerfile"), "FROM rust").unwrap();
+        fs::create_dir_all(tmp.path().join("frontend")).unwrap();
+        fs::write(
+            tmp.path().join("frontend/vite.config.ts"),
+            "export default {}",
+        )
+        .unwrap();
+        fs::create_dir_all(tmp.path().join("node_modules/pkg")).unwrap();
+        fs::write(
+            tmp.path().join("node_modules/pkg/Jenkinsfile"),
+            "pipeline {}",
+        )
+        .unwrap();
+
+        let result = scan_directory(tmp.path());
+        let infra: Vec<&str> = result
+            .infrastructure_signals
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(infra, vec!["Docker"]);
+        assert!(result.frameworks.iter().any(|s| s.name == "Vite"));
+        // Nested indicators are signals, not top-level structure.
+        assert!(result.project_structures.is_empty());
+    }
+
+    #[test]
+    fn test_scan_exclude_top_level_indicator() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("Dockerfile"), "FROM python:3.12").unwrap();
+
+        let options = ScanOptions {
+            exclude: build_globset(&["Dockerfile".to_string(), "src".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert!(result.infrastructure_signals.is_empty());
+        assert!(result.project_structures.is_empty());
+    }
+
+    #[test]
+    fn test_scan_include_hidden() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::write(tmp.path().join(".hiddenscript.py"), "print('hi')").unwrap();
+        fs::create_dir_all(tmp.path().join(".github/workflows")).unwrap();
+        fs::write(tmp.path().join(".github/workflows/ci.yml"), "on: push").unwrap();
+
+        let result = scan_directory(tmp.path());
+        assert_eq!(result.languages.len(), 1);
+        assert_eq!(result.languages[0].name, "Rust");
+
+        let options = ScanOptions {
+            include_hidden: true,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        let names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        assert!(names.contains(&"Python"), "expected Python in {names:?}");
+        // Hidden indicators are still detected, exactly once.
+        let actions = result
+            .infrastructure_signals
+            .iter()
+            .filter(|s| s.name == "GitHub Actions")
+            .count();
+        assert_eq!(actions, 1);
+    }
+
+    #[test]
+    fn test_scan_no_gitignore() {
+        let tmp = TempDir::new().unwrap();
+        // `.gitignore` only applies inside a git repository.
+        fs::create_dir(tmp.path().join(".git")).unwrap();
+        fs::write(tmp.path().join(".gitignore"), "generated.py\n").unwrap();
+        fs::write(tmp.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::write(tmp.path().join("generated.py"), "x = 1").unwrap();
+        fs::create_dir(tmp.path().join("node_modules")).unwrap();
+        fs::write(
+            tmp.path().join("node_modules/index.js"),
+            "module.exports = 1",
+        )
+        .unwrap();
+
+        let result = scan_directory(tmp.path());
+        assert_eq!(result.languages.len(), 1);
+        assert_eq!(result.languages[0].name, "Rust");
+
+        let options = ScanOptions {
+            respect_gitignore: false,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        let names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        assert!(names.contains(&"Python"), "expected Python in {names:?}");
+        // SKIP_DIRS still applies without gitignore rules.
+        assert!(
+            !names.contains(&"JavaScript"),
+            "unexpected JavaScript in {names:?}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_follow_symlinks() {
+        let tmp = TempDir::new().unwrap();
+        let real = TempDir::new().unwrap();
+        fs::write(real.path().join("lib.rs"), "pub fn f() {}").unwrap();
+        std::os::unix::fs::symlink(real.path(), tmp.path().join("src")).unwrap();
+
+        let result = scan_directory(tmp.path());
+        assert!(result.languages.is_empty());
+
+        let options = ScanOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert_eq!(result.languages[0].name, "Rust");
+        assert_eq!(result.total_files, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_follow_symlinks_cycle() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("a")).unwrap();
+        fs::write(tmp.path().join("a/main.py"), "print('hi')").unwrap();
+        // a/loop points back at the scan root.
+        std::os::unix::fs::symlink(tmp.path(), tmp.path().join("a/loop")).unwrap();
+
+        let options = ScanOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert_eq!(result.total_files, 1);
+    }
+
+    #[test]
+    fn test_scan_extra_ignore() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/app.ts"), "x".repeat(100)).unwrap();
+        fs::write(tmp.path().join("src/api.generated.ts"), "x".repeat(900)).unwrap();
+        fs::write(tmp.path().join("main.py"), "x".repeat(100)).unwrap();
+
+        let options = ScanOptions {
+            ignore: vec!["*.generated.ts".to_string()],
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert_eq!(result.total_files, 2);
+        assert_eq!(result.languages[0].percentage, 50.0);
+        assert_eq!(result.languages[1].percentage, 50.0);
+    }
+
+    #[test]
+    fn test_scan_extra_ignore_applies_to_every_root() {
+        let a = TempDir::new().unwrap();
+        let b = TempDir::new().unwrap();
+        fs::write(a.path().join("a.generated.ts"), "x").unwrap();
+        fs::write(b.path().join("b.generated.ts"), "x").unwrap();
+        fs::write(b.path().join("main.go"), "package main").unwrap();
+
+        let options = ScanOptions {
+            ignore: vec!["*.generated.ts".to_string()],
+            ..Default::default()
+        };
+        let result = scan_directories_with(&[a.path(), b.path()], &options);
+        assert_eq!(result.total_files, 1);
+        assert_eq!(result.languages[0].name, "Go");
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join(".gitignore"), "*.log\n").unwrap();
+        let options = ScanOptions {
+            ignore: vec!["generated/".to_string()],
+            ..Default::default()
+        };
+        let filter = PathFilter::new(tmp.path(), &options);
+        assert!(!filter.is_ignored(&tmp.path().join("src/main.rs")));
+        assert!(filter.is_ignored(&tmp.path().join("target/debug/pb-scan")));
+        assert!(filter.is_ignored(&tmp.path().join("web/node_modules/x.js")));
+        assert!(filter.is_ignored(&tmp.path().join(".git/index")));
+        assert!(filter.is_ignored(&tmp.path().join("debug.log")));
+        assert!(filter.is_ignored(&tmp.path().join("generated/api.rs")));
+        assert!(filter.is_ignored(Path::new("/elsewhere/main.rs")));
+    }
+
+    #[test]
+    fn test_scan_stats() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hello')").unwrap();
+        fs::write(tmp.path().join("logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        fs::create_dir(tmp.path().join("node_modules")).unwrap();
+        fs::write(tmp.path().join("node_modules/index.js"), "x").unwrap();
+
+        assert!(scan_directory(tmp.path()).stats.is_none());
+
+        let options = ScanOptions {
+            collect_stats: true,
+            ..Default::default()
+        };
+        let stats = scan_directory_with(tmp.path(), &options)
+            .stats
+            .expect("stats requested");
+        assert_eq!(stats.files_scanned, 1);
+        // logo.png, plus node_modules pruned as a whole.
+        assert_eq!(stats.files_skipped, 2);
+        assert_eq!(stats.bytes_read, 18);
+        assert!(stats.elapsed_ms >= 0.0);
+    }
+
+    #[test]
+    fn test_build_globset_invalid() {
+        assert!(build_globset(&["[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_is_test_path() {
+        assert!(is_test_path(Path::new("tests/api.rs")));
+        assert!(is_test_path(Path::new("src/__tests__/App.tsx")));
+        assert!(is_test_path(Path::new("packages/a/spec/user_spec.rb")));
+        assert!(is_test_path(Path::new("pkg/server_test.go")));
+        assert!(is_test_path(Path::new("src/app.spec.ts")));
+        assert!(!is_test_path(Path::new("src/main.rs")));
+        assert!(!is_test_path(Path::new("src/testing.py")));
+        assert!(!is_test_path(Path::new("test")));
+        assert!(!is_test_path(Path::new("docs/tests.md")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_strict_reports_dangling_symlink() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hi')").unwrap();
+        std::os::unix::fs::symlink(tmp.path().join("gone.py"), tmp.path().join("link.py")).unwrap();
+
+        // Lenient by default: the link is left out silently.
+        let result = try_scan_directories_with(&[tmp.path()], &ScanOptions::default()).unwrap();
+        assert_eq!(result.languages[0].name, "Python");
+
+        let options = ScanOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let err = try_scan_directories_with(&[tmp.path()], &options).unwrap_err();
+        assert_eq!(err.0.len(), 1);
+        assert!(err.0[0].contains("link.py"), "{err}");
+        assert!(err.to_string().starts_with("cannot read 1 entries:"));
+    }
+
+    #[test]
+    fn test_scan_code_only() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("site")).unwrap();
+        fs::write(tmp.path().join("site/index.html"), "<p>hi</p>".repeat(200)).unwrap();
+        fs::write(
+            tmp.path().join("site/style.css"),
+            "p { color: red; }".repeat(50),
+        )
+        .unwrap();
+        fs::write(tmp.path().join("app.py"), "print('hi')\n").unwrap();
+
+        let result = scan_directory(tmp.path());
+        assert_eq!(result.languages[0].name, "HTML");
+        assert_eq!(result.primary_language, None);
+
+        let options = ScanOptions {
+            code_only: true,
+            per_directory: true,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert_eq!(result.languages.len(), 1);
+        assert_eq!(result.languages[0].name, "Python");
+        assert_eq!(result.languages[0].percentage, 100.0);
+        assert_eq!(result.primary_language.as_deref(), Some("Python"));
+        assert!(result.per_directory.unwrap().is_empty());
+        // Totals still cover every file.
+        assert_eq!(result.total_files, 3);
+    }
+
+    #[test]
+    fn test_detect_license() {
+        let tmp = TempDir::new().unwrap();
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(detect_license(tmp.path(), &[]), None);
+
+        fs::write(
+            tmp.path().join("LICENSE.md"),
+            "                                 Apache License\n\
+             Version 2.0, January 2004\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_license(tmp.path(), &names(&["LICENSE.md"])).as_deref(),
+            Some("Apache-2.0")
+        );
+
+        fs::write(
+            tmp.path().join("COPYING"),
+            "GNU GENERAL PUBLIC LICENSE\n   Version 3, 29 June 2007\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_license(tmp.path(), &names(&["COPYING"])).as_deref(),
+            Some("GPL-3.0")
+        );
+
+        fs::write(
+            tmp.path().join("LICENSE"),
+            "// SPDX-License-Identifier: MPL-2.0\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_license(tmp.path(), &names(&["LICENSE"])).as_deref(),
+            Some("MPL-2.0")
+        );
+
+        fs::write(tmp.path().join("LICENSE"), "All rights reserved.\n").unwrap();
+        assert_eq!(
+            detect_license(tmp.path(), &names(&["LICENSE"])).as_deref(),
+            Some("unknown")
+        );
+    }
+
+    #[test]
+    fn test_scan_detects_nested_proto() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("api/users/v1")).unwrap();
+        fs::write(
+            tmp.path().join("api/users/v1/users.proto"),
+            "syntax = \"proto3\";\n\nservice Users {}\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("main.go"), "package main\n").unwrap();
+
+        let result = scan_directory(tmp.path());
+        let proto = result
+            .frameworks
+            .iter()
+            .find(|f| f.name == "Protocol Buffers")
+            .expect("Protocol Buffers detected");
+        assert_eq!(proto.version.as_deref(), Some("proto3"));
+
+        let entries = vec![(
+            PathBuf::from("schema/schema.graphql"),
+            12,
+            Some(b"type Query {}".to_vec()),
+        )];
+        let result = scan_from_entries(&entries);
+        assert_eq!(result.frameworks[0].name, "GraphQL");
+    }
+
+    #[test]
+    fn test_fence_languages() {
+        let readme = "# Demo\n\n```python\nimport flask\n```\n\n\
+                      ~~~ {.rust}\nfn main() {}\n~~~\n\n\
+                      ```\nplain\n```\n\n\
+                      ~~~\n```js\nquoted\n```\n~~~\n";
+        assert_eq!(fence_languages(readme), vec!["Python", "Rust"]);
+    }
+
+    #[test]
+    fn test_detect_readme() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("app.py"), "print('hi')\n".repeat(100)).unwrap();
+        let result = scan_directory(tmp.path());
+        assert!(!result.has_readme);
+        assert_eq!(result.readme_bytes, None);
+        assert!(result.readme_languages.is_empty());
+
+        let readme = "# App\n\n```bash\npip install app\n```\n\n```py\nimport app\n```\n\n```python\napp.run()\n```\n";
+        fs::write(tmp.path().join("README.md"), readme).unwrap();
+        let result = scan_directory(tmp.path());
+        assert!(result.has_readme);
+        assert_eq!(result.readme_bytes, Some(readme.len() as u64));
+        assert_eq!(result.readme_languages, vec!["Python", "Shell"]);
+        // Fences never count towards the language breakdown.
+        assert_eq!(result.languages.len(), 1);
+        assert_eq!(result.languages[0].name, "Python");
+    }
+
+    #[test]
+    fn test_scan_uppercase_extensions() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.PY"), "print('hi')\n").unwrap();
+        fs::write(tmp.path().join("lib.RS"), "fn main() {}\n").unwrap();
+        fs::write(tmp.path().join("LOGO.PNG"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let result = scan_directory(tmp.path());
+        let mut names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Python", "Rust"]);
+        assert_eq!(result.total_files, 2);
+    }
+
+    #[test]
+    fn test_scan_explain_examples() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("app.py"), "x".repeat(300)).unwrap();
+        fs::write(tmp.path().join("util.py"), "x".repeat(200)).unwrap();
+        fs::write(tmp.path().join("tiny.py"), "x").unwrap();
+        fs::write(tmp.path().join("run.sh"), "echo hi\n").unwrap();
+
+        let options = ScanOptions {
+            explain: 2,
+            ..Default::default()
+        };
+        let examples = scan_directory_with(tmp.path(), &options)
+            .examples
+            .expect("examples with explain");
+        let root = tmp.path().canonicalize().unwrap();
+        let expected = |names: &[&str]| -> Vec<String> {
+            names
+                .iter()
+                .map(|n| root.join(n).to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(examples["Python"], expected(&["app.py", "util.py"]));
+        assert_eq!(examples["Shell"], expected(&["run.sh"]));
+
+        assert!(scan_directory(tmp.path()).examples.is_none());
+    }
+
+    #[test]
+    fn test_language_examples_merge() {
+        let mut a = LanguageExamples::new(2);
+        a.add(Path::new("a/main.rs"), &[("Rust", 10)]);
+        let mut b = LanguageExamples::new(2);
+        b.add(Path::new("b/lib.rs"), &[("Rust", 30)]);
+        b.add(Path::new("b/mod.rs"), &[("Rust", 20)]);
+        a.merge(b);
+
+        let rust = [LanguageEntry {
+            name: "Rust".to_string(),
+            category: "programming".to_string(),
+            percentage: 100.0,
+        }];
+        assert_eq!(
+            a.for_languages(&rust)["Rust"],
+            vec!["b/lib.rs".to_string(), "b/mod.rs".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_scan_detects_prisma_migrations() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("prisma")).unwrap();
+        fs::write(
+            tmp.path().join("prisma/schema.prisma"),
+            "datasource db {\n  provider = \"postgresql\"\n  url = env(\"DATABASE_URL\")\n}\n",
+        )
+        .unwrap();
+
+        let result = scan_directory(tmp.path());
+        let prisma = result.frameworks.iter().find(|f| f.name == "Prisma");
+        assert_eq!(prisma.map(|f| f.category.as_str()), Some("tool"));
+        assert!(result
+            .project_structures
+            .contains(&"has_migrations".to_string()));
+    }
+
+    #[test]
+    fn test_scan_binary_extension_overrides() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hi')\n").unwrap();
+        fs::write(tmp.path().join("events.parquet"), "PAR1").unwrap();
+        fs::write(tmp.path().join("logo.svg"), "<svg/>").unwrap();
+
+        let default = scan_directory_with(tmp.path(), &ScanOptions::default());
+        assert_eq!(default.total_files, 2);
+
+        let options = ScanOptions {
+            binary_extensions: vec![".parquet".to_string()],
+            text_extensions: vec!["SVG".to_string()],
+            ..Default::default()
+        };
+        let raw = scan_directory_raw_with(tmp.path(), &options);
+        assert_eq!(raw.total_files, 2);
+        assert_eq!(raw.skipped_files, 1);
+        // logo.svg is counted but has no language.
+        assert_eq!(raw.bytes_by_lang.keys().collect::<Vec<_>>(), vec!["Python"]);
+    }
+
+    #[test]
+    fn test_scan_detects_hidden_container_indicators() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join(".devcontainer")).unwrap();
+        fs::write(tmp.path().join(".devcontainer/devcontainer.json"), "{}").unwrap();
+        fs::write(tmp.path().join(".buildpacks"), "").unwrap();
+
+        let result = scan_directory(tmp.path());
+        let names: Vec<&str> = result
+            .infrastructure_signals
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Cloud Native Buildpacks", "Dev Containers"]);
+    }
+
+    #[test]
+    fn test_scan_profile() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("app.py"), "print('hi')\n").unwrap();
+        assert_eq!(scan_directory(tmp.path()).profile, None);
+
+        let options = ScanOptions {
+            profile: true,
+            ..Default::default()
+        };
+        assert!(scan_directory_with(tmp.path(), &options).profile.is_some());
+        let entries = vec![(PathBuf::from("app.py"), 12, None)];
+        assert!(scan_from_entries_with(&entries, &options).profile.is_some());
+    }
+
+    #[test]
+    fn test_scan_languages_only() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("Dockerfile"), "FROM python:3.12\n").unwrap();
+        fs::write(tmp.path().join("requirements.txt"), "flask\n").unwrap();
+        fs::write(tmp.path().join("README.md"), "# App\n").unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/app.py"), "print('hi')\n").unwrap();
+
+        let options = ScanOptions {
+            languages_only: true,
+            ..Default::default()
+        };
+        let result = scan_directory_with(tmp.path(), &options);
+        assert_eq!(result.languages[0].name, "Python");
+        assert!(result.frameworks.is_empty());
+        assert!(result.infrastructure_signals.is_empty());
+        assert!(result.project_structures.is_empty());
+        assert!(!result.has_readme);
+
+        let entries = vec![(PathBuf::from("Dockerfile"), 5, None)];
+        assert!(scan_from_entries_with(&entries, &options)
+            .infrastructure_signals
+            .is_empty());
+    }
+
+    #[test]
+    fn test_directory_key() {
+        assert_eq!(directory_key(Path::new("main.py")), None);
+        assert_eq!(
+            directory_key(Path::new("src/lib/a.rs")),
+            Some("src".to_string())
+        );
+        assert_eq!(
+            directory_key(Path::new("packages/web/index.js")),
+            Some("packages/web".to_string())
+        );
+        assert_eq!(
+            directory_key(Path::new("packages/index.js")),
+            Some("packages".to_string())
+        );
+    }
+
+    #[test]
+    fn test_scan_per_directory_off_by_default() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/main.py"), "print('hello')").unwrap();
+        let result = scan_directory(tmp.path());
+        assert!(result.per_directory.is_none());
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(json.get("per_directory").is_none());
+    }
+
+    #[test]
+    fn test_scan_multiple_dirs_merges_bytes_exactly() {
+        let tmp1 = TempDir::new().unwrap();
+        let tmp2 = TempDir::new().unwrap();
+        fs::write(tmp1.path().join("main.py"), "x".repeat(300)).unwrap();
+        fs::write(tmp2.path().join("app.rs"), "x".repeat(100)).unwrap();
+        fs::write(tmp2.path().join("util.py"), "x".repeat(100)).unwrap();
+
+        let result = scan_directories(&[tmp1.path(), tmp2.path()]);
+        assert_eq!(result.languages[0].name, "Python");
+        assert_eq!(result.languages[0].percentage, 80.0);
+        assert_eq!(result.languages[1].name, "Rust");
+        assert_eq!(result.languages[1].percentage, 20.0);
+    }
+
+    #[test]
+    fn test_merge_raw_scans() {
+        let a = TempDir::new().unwrap();
+        let b = TempDir::new().unwrap();
+        fs::write(a.path().join("main.py"), "x".repeat(300)).unwrap();
+        fs::write(a.path().join("Dockerfile"), "FROM python").unwrap();
+        fs::write(b.path().join("main.rs"), "x".repeat(100)).unwrap();
+        fs::write(b.path().join("Makefile"), "all:").unwrap();
+
+        let mut raw = scan_directory_raw(a.path());
+        raw.merge(scan_directory_raw(b.path()));
+        assert_eq!(raw.bytes_by_lang["Python"], 300);
+        assert_eq!(raw.bytes_by_lang["Rust"], 100);
+
+        let result = raw.finalize();
+        assert_eq!(result.languages[0].name, "Python");
+        assert_eq!(result.languages[0].percentage, 75.0);
+        assert_eq!(result.languages[1].percentage, 25.0);
+        assert_eq!(result.project_structures, vec!["makefile".to_string()]);
+        assert_eq!(result.infrastructure_signals[0].name, "Docker");
+        assert_eq!(result.total_files, 4);
+    }
+
     #[test]
     fn test_scan_multiple_dirs() {
         let tmp1 = TempDir::new().unwrap();
@@ -239,8 +1961,82 @@ mod tests {
         fs::write(tmp2.path().join("app.rs"), "fn main() {}").unwrap();
 
         let result = scan_directories(&[tmp1.path(), tmp2.path()]);
+        assert_eq!(result.total_files, 2);
+        assert_eq!(result.total_bytes, 26);
         let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
         assert!(lang_names.contains(&"Python"));
         assert!(lang_names.contains(&"Rust"));
     }
+
+    #[test]
+    fn test_scan_from_entries() {
+        let package = br#"{"dependencies": {"react": "^18.2.0"}}"#.to_vec();
+        let cargo = b"[dependencies]\nactix-web = \"4\"\n".to_vec();
+        let entries = vec![
+            (
+                PathBuf::from("package.json"),
+                package.len() as u64,
+                Some(package),
+            ),
+            (PathBuf::from("Cargo.toml"), cargo.len() as u64, Some(cargo)),
+            (PathBuf::from("src/App.tsx"), 300, None),
+            (PathBuf::from("src/main.rs"), 100, None),
+            (PathBuf::from("src/logo.png"), 5000, None),
+            (PathBuf::from("deploy/Dockerfile"), 40, None),
+            (PathBuf::from("node_modules/x/index.js"), 900, None),
+            (PathBuf::from("node_modules/x/Dockerfile"), 40, None),
+            (PathBuf::from(".github/workflows/ci.yml"), 60, None),
+        ];
+
+        let result = scan_from_entries(&entries);
+        let langs: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(langs[0], "TypeScript");
+        assert!(langs.contains(&"Rust"));
+        assert!(!langs.contains(&"JavaScript"));
+        assert!(!langs.contains(&"YAML"), "hidden files are not counted");
+
+        let react = result
+            .frameworks
+            .iter()
+            .find(|f| f.name == "React")
+            .unwrap();
+        assert_eq!(react.version.as_deref(), Some("^18.2.0"));
+        assert!(result.frameworks.iter().any(|f| f.name == "Actix Web"));
+
+        let infra: Vec<&str> = result
+            .infrastructure_signals
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert!(infra.contains(&"Docker"));
+        assert!(infra.contains(&"GitHub Actions"));
+
+        // The vendored directory and the image are left out of the totals.
+        assert_eq!(result.total_files, 5);
+    }
+
+    #[test]
+    fn test_scan_from_entries_options() {
+        let entries = vec![
+            (
+                PathBuf::from("main.py"),
+                20,
+                Some(b"print('a')\nprint('b')\n".to_vec()),
+            ),
+            (PathBuf::from("gen/out.py"), 500, None),
+            (PathBuf::from("docs/deep/a/b.md"), 50, None),
+            (PathBuf::from(".config/x.toml"), 10, None),
+        ];
+        let options = ScanOptions {
+            ignore: vec!["gen/".to_string()],
+            max_depth: Some(2),
+            include_hidden: true,
+            ..Default::default()
+        };
+
+        let result = scan_from_entries_with(&entries, &options);
+        assert_eq!(result.total_files, 2);
+        let langs: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(langs, vec!["Python"]);
+    }
 }
@@ -1,3 +1,20 @@
+use std::path::Path;
+
+use crate::files::RepoFiles;
+
+/// Top-level directories that hold one package per subdirectory.
+pub const MONOREPO_DIRS: &[&str] = &["packages", "libs"];
+
+/// Config files of monorepo tools (Nx, Turborepo, Lerna, pnpm workspaces).
+const MONOREPO_FILES: &[&str] = &["nx.json", "turbo.json", "lerna.json", "pnpm-workspace.yaml"];
+
+/// Directories of migration scripts: plain SQL or tool-managed
+/// `migrations/`, `db/migrations/`, and Rails' `db/migrate/`.
+const MIGRATION_DIRS: &[&str] = &["migrations", "db/migrations", "db/migrate"];
+
+/// Files of migration tools: Alembic's config and the Prisma schema.
+const MIGRATION_FILES: &[&str] = &["alembic.ini", "schema.prisma", "prisma/schema.prisma"];
+
 /// Detect project structures from top-level directory/file names.
 /// Direct port of GitHubAnalyzer._detect_structures() from github.py.
 pub fn detect_structures(top_level_names: &[String]) -> Vec<String> {
@@ -8,7 +25,11 @@ pub fn detect_structures(top_level_names: &[String]) -> Vec<String> {
     if names.contains("src") {
         structures.push("src_layout".to_string());
     }
-    if names.contains("packages") || names.contains("libs") {
+    if MONOREPO_DIRS
+        .iter()
+        .chain(MONOREPO_FILES)
+        .any(|d| names.contains(d))
+    {
         structures.push("monorepo".to_string());
     }
     if names.contains("setup.py") || names.contains("pyproject.toml") {
@@ -20,14 +41,97 @@ pub fn detect_structures(top_level_names: &[String]) -> Vec<String> {
     if names.contains("Makefile") {
         structures.push("makefile".to_string());
     }
+    if names.contains("tests") || names.contains("test") {
+        structures.push("tests_dir".to_string());
+    }
+    if names.contains("docs") {
+        structures.push("docs_dir".to_string());
+    }
+    if names.contains(".github") {
+        structures.push("github_templates".to_string());
+    }
+    if names.iter().any(|n| n.ends_with(".sln")) {
+        structures.push("dotnet_solution".to_string());
+    }
+    if names.iter().any(|n| n.ends_with(".cabal")) || names.contains("stack.yaml") {
+        structures.push("haskell_package".to_string());
+    }
+    if names.contains("settings.gradle") || names.contains("settings.gradle.kts") {
+        structures.push("gradle_project".to_string());
+    }
+    if names.contains("build.zig") {
+        structures.push("zig_package".to_string());
+    }
+    if names.contains("CMakeLists.txt") {
+        structures.push("cmake_project".to_string());
+    }
+    if ["WORKSPACE", "WORKSPACE.bazel", "MODULE.bazel"]
+        .iter()
+        .any(|n| names.contains(n))
+    {
+        structures.push("bazel_workspace".to_string());
+    }
 
     structures.sort();
+    structures.dedup();
+    structures
+}
+
+/// Detect structures that depend on manifest contents or on files below
+/// the top level rather than on top-level names.
+pub fn detect_manifest_structures(root: &(impl RepoFiles + ?Sized)) -> Vec<String> {
+    let mut structures: Vec<String> = Vec::new();
+    if pubspec_uses_flutter(root) {
+        structures.push("flutter_project".to_string());
+    }
+    if has_migrations(root) {
+        structures.push("has_migrations".to_string());
+    }
     structures
 }
 
+/// Whether the project keeps database migrations: a non-empty
+/// [`MIGRATION_DIRS`] entry, one of [`MIGRATION_FILES`], or a Django app
+/// with a `migrations/__init__.py`.
+fn has_migrations(root: &(impl RepoFiles + ?Sized)) -> bool {
+    let has_file = |path: &Path, file: &str| {
+        root.read_dir(path)
+            .iter()
+            .any(|(name, is_dir)| !is_dir && name == file)
+    };
+
+    MIGRATION_DIRS
+        .iter()
+        .any(|dir| !root.read_dir(Path::new(dir)).is_empty())
+        || MIGRATION_FILES.iter().any(|file| {
+            let path = Path::new(file);
+            let parent = path.parent().unwrap_or(Path::new(""));
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| has_file(parent, name))
+        })
+        || root
+            .read_dir(Path::new(""))
+            .iter()
+            .filter(|(_, is_dir)| *is_dir)
+            .any(|(app, _)| has_file(&Path::new(app).join("migrations"), "__init__.py"))
+}
+
+/// Whether `pubspec.yaml` lists the Flutter SDK under `dependencies`.
+fn pubspec_uses_flutter(root: &(impl RepoFiles + ?Sized)) -> bool {
+    let Ok(content) = root.read_to_string(Path::new("pubspec.yaml")) else {
+        return false;
+    };
+    serde_yaml::from_str::<serde_yaml::Value>(&content)
+        .ok()
+        .and_then(|parsed| parsed.get("dependencies")?.get("flutter").cloned())
+        .is_some()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
 
     #[test]
     fn test_detect_src_layout() {
@@ -43,6 +147,15 @@ mod tests {
         assert!(result.contains(&"monorepo".to_string()));
     }
 
+    #[test]
+    fn test_detect_monorepo_tool() {
+        for file in ["nx.json", "turbo.json", "lerna.json", "pnpm-workspace.yaml"] {
+            let names = vec![file.to_string()];
+            let result = detect_structures(&names);
+            assert_eq!(result, vec!["monorepo".to_string()], "{file}");
+        }
+    }
+
     #[test]
     fn test_detect_python_package() {
         let names = vec!["pyproject.toml".to_string(), "src".to_string()];
@@ -65,6 +178,84 @@ mod tests {
         assert!(result.contains(&"makefile".to_string()));
     }
 
+    #[test]
+    fn test_detect_tests_dir() {
+        for dir in ["tests", "test"] {
+            let names = vec![dir.to_string()];
+            let result = detect_structures(&names);
+            assert_eq!(result, vec!["tests_dir".to_string()]);
+        }
+    }
+
+    #[test]
+    fn test_detect_docs_dir() {
+        let names = vec!["docs".to_string()];
+        let result = detect_structures(&names);
+        assert!(result.contains(&"docs_dir".to_string()));
+    }
+
+    #[test]
+    fn test_detect_github_templates() {
+        let names = vec![".github".to_string(), ".github/workflows".to_string()];
+        let result = detect_structures(&names);
+        assert!(result.contains(&"github_templates".to_string()));
+    }
+
+    #[test]
+    fn test_detect_dotnet_solution() {
+        let names = vec!["MyApp.sln".to_string()];
+        let result = detect_structures(&names);
+        assert!(result.contains(&"dotnet_solution".to_string()));
+    }
+
+    #[test]
+    fn test_detect_haskell_package() {
+        for marker in ["api.cabal", "stack.yaml"] {
+            let names = vec![marker.to_string()];
+            let result = detect_structures(&names);
+            assert_eq!(result, vec!["haskell_package".to_string()]);
+        }
+    }
+
+    #[test]
+    fn test_detect_gradle_project() {
+        let names = vec!["settings.gradle.kts".to_string()];
+        let result = detect_structures(&names);
+        assert!(result.contains(&"gradle_project".to_string()));
+    }
+
+    #[test]
+    fn test_detect_zig_package() {
+        let names = vec!["build.zig".to_string(), "build.zig.zon".to_string()];
+        let result = detect_structures(&names);
+        assert_eq!(result, vec!["zig_package".to_string()]);
+    }
+
+    #[test]
+    fn test_detect_cmake_project() {
+        let names = vec!["CMakeLists.txt".to_string()];
+        let result = detect_structures(&names);
+        assert!(result.contains(&"cmake_project".to_string()));
+    }
+
+    #[test]
+    fn test_detect_bazel_workspace() {
+        for marker in ["WORKSPACE", "WORKSPACE.bazel", "MODULE.bazel"] {
+            let names = vec![marker.to_string()];
+            let result = detect_structures(&names);
+            assert_eq!(result, vec!["bazel_workspace".to_string()]);
+        }
+        let names = vec!["BUILD.bazel".to_string()];
+        assert!(detect_structures(&names).is_empty());
+    }
+
+    #[test]
+    fn test_detect_no_duplicates() {
+        let names = vec!["tests".to_string(), "test".to_string()];
+        let result = detect_structures(&names);
+        assert_eq!(result, vec!["tests_dir".to_string()]);
+    }
+
     #[test]
     fn test_detect_empty() {
         let names: Vec<String> = Vec::new();
@@ -84,4 +275,70 @@ mod tests {
         sorted.sort();
         assert_eq!(result, sorted);
     }
+
+    #[test]
+    fn test_detect_flutter_project() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        assert!(detect_manifest_structures(tmp.path()).is_empty());
+
+        fs::write(
+            tmp.path().join("pubspec.yaml"),
+            "name: app\ndependencies:\n  flutter:\n    sdk: flutter\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_manifest_structures(tmp.path()),
+            vec!["flutter_project".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_detect_migrations() {
+        let layouts: &[&[&str]] = &[
+            &["migrations/0001_init.sql"],
+            &["db/migrations/20240101_users.up.sql"],
+            &["db/migrate/20240101000000_create_users.rb"],
+            &[
+                "blog/migrations/__init__.py",
+                "blog/migrations/0001_initial.py",
+            ],
+            &["alembic.ini"],
+            &["prisma/schema.prisma"],
+        ];
+        for files in layouts {
+            let tmp = tempfile::TempDir::new().unwrap();
+            for file in *files {
+                let path = tmp.path().join(file);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, "").unwrap();
+            }
+            assert_eq!(
+                detect_manifest_structures(tmp.path()),
+                vec!["has_migrations".to_string()],
+                "{files:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_no_migrations() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        // An empty migrations dir and a Python package without Django's
+        // marker don't count.
+        fs::create_dir(tmp.path().join("migrations")).unwrap();
+        fs::create_dir_all(tmp.path().join("app/migrations")).unwrap();
+        fs::write(tmp.path().join("app/migrations/notes.txt"), "").unwrap();
+        assert!(detect_manifest_structures(tmp.path()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_dart_package_is_not_flutter() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pubspec.yaml"),
+            "name: cli\ndependencies:\n  args: ^2.4.0\n",
+        )
+        .unwrap();
+        assert!(detect_manifest_structures(tmp.path()).is_empty());
+    }
 }
@@ -0,0 +1,507 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command as StdCommand;
+
+use assert_cmd::cargo::cargo_bin_cmd;
+use assert_cmd::Command;
+use tempfile::TempDir;
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(name)
+        .display()
+        .to_string()
+}
+
+fn pb_scan() -> Command {
+    cargo_bin_cmd!("pb-scan")
+}
+
+#[test]
+fn test_output_writes_file() {
+    let tmp = TempDir::new().unwrap();
+    let out = tmp.path().join("nested/dir/scan.json");
+
+    pb_scan()
+        .arg(fixture("simple-python"))
+        .arg("--output")
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("");
+
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+    assert_eq!(json["languages"][0]["name"], "Python");
+}
+
+#[test]
+fn test_output_unwritable_path() {
+    let tmp = TempDir::new().unwrap();
+    let blocker = tmp.path().join("file");
+    fs::write(&blocker, "").unwrap();
+
+    pb_scan()
+        .arg(fixture("simple-python"))
+        .arg("--output")
+        .arg(blocker.join("scan.json"))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error: cannot write"));
+}
+
+fn scan_rust_actix(format: &str) -> String {
+    let output = pb_scan()
+        .arg(fixture("rust-actix"))
+        .args(["--format", format])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "--format {format} failed");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_format_variants() {
+    let json: serde_json::Value = serde_json::from_str(&scan_rust_actix("json")).unwrap();
+    assert_eq!(json["languages"][0]["name"], "Rust");
+
+    let yaml: serde_yaml::Value = serde_yaml::from_str(&scan_rust_actix("yaml")).unwrap();
+    assert_eq!(yaml["languages"][0]["name"].as_str(), Some("Rust"));
+
+    let toml: toml::Value = toml::from_str(&scan_rust_actix("toml")).unwrap();
+    assert_eq!(toml["languages"][0]["name"].as_str(), Some("Rust"));
+    let frameworks = toml["frameworks"].as_array().unwrap();
+    assert!(frameworks
+        .iter()
+        .any(|f| f["name"].as_str() == Some("Actix Web")));
+
+    let csv = scan_rust_actix("csv");
+    assert!(csv.starts_with("kind,name,category,percentage\n"));
+    assert!(csv.contains("framework,Actix Web,"));
+}
+
+#[test]
+fn test_languages_only() {
+    let output = pb_scan()
+        .arg(fixture("rust-actix"))
+        .arg("--languages-only")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["languages"][0]["name"], "Rust");
+    assert_eq!(json["frameworks"], serde_json::json!([]));
+    assert_eq!(json["infrastructure_signals"], serde_json::json!([]));
+    assert_eq!(json["project_structures"], serde_json::json!([]));
+}
+
+#[test]
+fn test_profile() {
+    let output = pb_scan()
+        .arg(fixture("simple-python"))
+        .arg("--profile")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for phase in ["walk", "dependencies", "structures", "finalize"] {
+        assert!(stderr.contains(&format!("{phase} ")), "{stderr}");
+    }
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json.get("profile").is_none());
+}
+
+#[test]
+fn test_explain_examples() {
+    let output = pb_scan()
+        .arg(fixture("simple-python"))
+        .arg("--explain")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let app = Path::new(&fixture("simple-python"))
+        .canonicalize()
+        .unwrap()
+        .join("app.py");
+    assert_eq!(
+        json["examples"]["Python"],
+        serde_json::json!([app.to_string_lossy()])
+    );
+}
+
+#[test]
+fn test_explain_redact() {
+    let root = Path::new(&fixture("simple-python")).canonicalize().unwrap();
+    let output = pb_scan()
+        .arg(&root)
+        .args(["--explain", "--redact"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["examples"]["Python"], serde_json::json!(["app.py"]));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains(root.to_str().unwrap()), "{stdout}");
+}
+
+#[test]
+fn test_custom_rules() {
+    let tmp = TempDir::new().unwrap();
+    let rules = tmp.path().join("rules.json");
+    fs::write(
+        &rules,
+        r#"[{"manifest": "package.json", "key": "@acme/design-system", "name": "Acme Design System", "category": "framework"}]"#,
+    )
+    .unwrap();
+    let output = pb_scan()
+        .arg(fixture("node-react"))
+        .arg("--rules")
+        .arg(&rules)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let acme = json["frameworks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|fw| fw["name"] == "Acme Design System")
+        .expect("custom rule should match");
+    assert_eq!(acme["category"], "framework");
+    assert_eq!(acme["version"], "^2.1.0");
+}
+
+#[test]
+fn test_custom_rules_malformed() {
+    let tmp = TempDir::new().unwrap();
+    let rules = tmp.path().join("rules.json");
+    fs::write(
+        &rules,
+        r#"[{"manifest": "package.json", "key": "@acme/ui", "name": "Acme UI", "category": "widget"}]"#,
+    )
+    .unwrap();
+    let output = pb_scan()
+        .arg(fixture("node-react"))
+        .arg("--rules")
+        .arg(&rules)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("rule 0: unknown category `widget`"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_treat_binary() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(tmp.path().join("main.py"), "print('hi')\n").unwrap();
+    fs::write(tmp.path().join("events.parquet"), "PAR1").unwrap();
+
+    let total_files = |args: &[&str]| -> u64 {
+        let output = pb_scan().arg(tmp.path()).args(args).output().unwrap();
+        assert!(output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["total_files"].as_u64().unwrap()
+    };
+    assert_eq!(total_files(&[]), 2);
+    assert_eq!(total_files(&["--treat-binary", "parquet"]), 1);
+    assert_eq!(
+        total_files(&["--treat-binary", "parquet", "--treat-text", "parquet"]),
+        2
+    );
+}
+
+#[test]
+fn test_format_summary() {
+    assert_eq!(
+        scan_rust_actix("summary"),
+        "Language:       Rust (100.0%)\n\
+         Frameworks:     Actix Web 4, Serde 1, Tokio 1 (+1 more)\n\
+         Infrastructure: none\n\
+         Structures:     src_layout\n"
+    );
+}
+
+#[test]
+fn test_pretty_requires_json() {
+    pb_scan()
+        .arg(fixture("rust-actix"))
+        .args(["--format", "json", "--pretty"])
+        .assert()
+        .success()
+        .stdout(predicates::str::starts_with("{\n"));
+
+    pb_scan()
+        .arg(fixture("rust-actix"))
+        .args(["--format", "toml", "--pretty"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "--pretty only applies to --format json",
+        ));
+}
+
+#[test]
+fn test_only_languages() {
+    let output = pb_scan()
+        .arg(fixture("rust-actix"))
+        .args(["--only", "languages"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let languages = json.as_array().expect("a bare array");
+    assert_eq!(languages[0]["name"], "Rust");
+    assert_eq!(languages[0]["category"], "programming");
+    assert!(languages[0]["percentage"].is_number());
+
+    let output = pb_scan()
+        .arg(fixture("rust-actix"))
+        .args(["--only", "structures"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json, serde_json::json!(["src_layout"]));
+}
+
+#[test]
+fn test_only_requires_json() {
+    pb_scan()
+        .arg(fixture("rust-actix"))
+        .args(["--only", "frameworks", "--format", "csv"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "--only only applies to --format json",
+        ));
+}
+
+#[test]
+fn test_baseline_diff() {
+    let tmp = TempDir::new().unwrap();
+    let output = pb_scan().arg(fixture("node-react")).output().unwrap();
+    let mut baseline: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    baseline["frameworks"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|f| f["name"] != "React");
+    let baseline_path = tmp.path().join("baseline.json");
+    fs::write(&baseline_path, baseline.to_string()).unwrap();
+
+    let output = pb_scan()
+        .arg(fixture("node-react"))
+        .arg("--baseline")
+        .arg(&baseline_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let diff: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diff["added"]["frameworks"], serde_json::json!(["React"]));
+    assert_eq!(diff["added"]["languages"], serde_json::json!([]));
+    assert_eq!(diff["removed"]["frameworks"], serde_json::json!([]));
+    assert_eq!(diff["changed"], serde_json::json!([]));
+}
+
+#[test]
+fn test_baseline_unreadable() {
+    pb_scan()
+        .arg(fixture("node-react"))
+        .args(["--baseline", "does-not-exist.json"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error: cannot read baseline"));
+}
+
+#[test]
+fn test_per_path_ndjson() {
+    let output = pb_scan()
+        .arg("--paths")
+        .arg(fixture("simple-python"))
+        .arg(fixture("rust-actix"))
+        .arg("--per-path-ndjson")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["path"], fixture("simple-python"));
+    assert_eq!(lines[0]["languages"][0]["name"], "Python");
+    assert_eq!(lines[1]["path"], fixture("rust-actix"));
+    assert_eq!(lines[1]["languages"][0]["name"], "Rust");
+}
+
+#[test]
+fn test_per_path_ndjson_requires_paths() {
+    pb_scan()
+        .arg(fixture("simple-python"))
+        .arg("--per-path-ndjson")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cache_file() {
+    let tmp = TempDir::new().unwrap();
+    let cache = tmp.path().join("cache/scan.json");
+    let run = || {
+        let output = pb_scan()
+            .arg(fixture("rust-actix"))
+            .arg("--cache")
+            .arg(&cache)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    let first = run();
+    assert!(cache.is_file());
+    assert_eq!(run(), first);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_strict_fails_on_dangling_symlink() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(tmp.path().join("main.py"), "print('hi')\n").unwrap();
+    std::os::unix::fs::symlink(tmp.path().join("missing.py"), tmp.path().join("broken.py"))
+        .unwrap();
+
+    pb_scan().arg(tmp.path()).assert().success();
+    pb_scan()
+        .arg(tmp.path())
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicates::str::contains("Error: cannot read 1 entries:"))
+        .stderr(predicates::str::contains("broken.py"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cache_does_not_bypass_strict() {
+    let tmp = TempDir::new().unwrap();
+    fs::write(tmp.path().join("main.py"), "print('hi')\n").unwrap();
+    std::os::unix::fs::symlink(tmp.path().join("missing.py"), tmp.path().join("broken.py"))
+        .unwrap();
+    let cache_dir = TempDir::new().unwrap();
+    let cache = cache_dir.path().join("scan.json");
+
+    pb_scan()
+        .arg(tmp.path())
+        .arg("--cache")
+        .arg(&cache)
+        .assert()
+        .success();
+    pb_scan()
+        .arg(tmp.path())
+        .arg("--cache")
+        .arg(&cache)
+        .arg("--strict")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("broken.py"));
+}
+
+#[test]
+fn test_stats_json() {
+    let output = pb_scan()
+        .arg(fixture("simple-python"))
+        .arg("--stats-json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let stats = json["stats"].as_object().expect("stats object");
+    let mut keys: Vec<&str> = stats.keys().map(|k| k.as_str()).collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec!["bytes_read", "elapsed_ms", "files_scanned", "files_skipped"]
+    );
+    let elapsed = stats["elapsed_ms"].as_f64().unwrap();
+    assert!((0.0..60_000.0).contains(&elapsed), "elapsed_ms = {elapsed}");
+    assert_eq!(stats["files_scanned"], json["total_files"]);
+    assert_eq!(stats["bytes_read"], json["total_bytes"]);
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = StdCommand::new("git")
+        .args(["-c", "user.name=pb", "-c", "user.email=pb@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_clone_local_repo_at_ref() {
+    let tmp = TempDir::new().unwrap();
+    git(tmp.path(), &["init", "--quiet"]);
+    fs::write(tmp.path().join("main.py"), "print('v1')\n").unwrap();
+    git(tmp.path(), &["add", "."]);
+    git(tmp.path(), &["commit", "--quiet", "-m", "v1"]);
+    git(tmp.path(), &["tag", "v1"]);
+    fs::remove_file(tmp.path().join("main.py")).unwrap();
+    fs::write(tmp.path().join("main.rs"), "fn main() {}\n").unwrap();
+    git(tmp.path(), &["add", "-A"]);
+    git(tmp.path(), &["commit", "--quiet", "-m", "v2"]);
+
+    let url = format!("file://{}", tmp.path().display());
+    let output = pb_scan()
+        .args(["--clone", &url, "--ref", "v1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["languages"][0]["name"], "Python");
+    assert_eq!(json["total_files"], 1);
+}
+
+#[test]
+fn test_clone_missing_repo() {
+    let tmp = TempDir::new().unwrap();
+    let url = format!("file://{}", tmp.path().join("missing").display());
+    pb_scan()
+        .args(["--clone", &url])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Error: cannot clone"));
+}
+
+#[cfg(feature = "network-tests")]
+#[test]
+fn test_clone_public_repo() {
+    let output = pb_scan()
+        .args(["--clone", "https://github.com/octocat/Spoon-Knife.git"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = json["languages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|l| l["name"].as_str())
+        .collect();
+    assert!(names.contains(&"HTML"), "expected HTML in {names:?}");
+}
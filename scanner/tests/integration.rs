@@ -1,6 +1,7 @@
 use std::path::Path;
 
-use pb_scan::{scan_directories, scan_directory};
+use pb_scan::output::{scan_result_schema, to_csv, ScanResult};
+use pb_scan::{scan_directories, scan_directory, scan_directory_with, ScanOptions};
 
 fn fixtures_dir() -> &'static Path {
     Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -11,6 +12,7 @@ fn fixtures_dir() -> &'static Path {
 #[test]
 fn test_simple_python() {
     let result = scan_directory(&fixtures_dir().join("simple-python"));
+    assert_eq!(result.primary_language.as_deref(), Some("Python"));
 
     // Languages
     let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
@@ -38,6 +40,17 @@ fn test_simple_python() {
         "expected Python Package in {fw_names:?}"
     );
 
+    // Test frameworks are kept apart from production ones.
+    let pytest = result.frameworks.iter().find(|f| f.name == "pytest");
+    assert_eq!(pytest.map(|f| f.category.as_str()), Some("testing"));
+
+    // Python versions from the CI matrix
+    let python = result.frameworks.iter().find(|f| f.name == "Python");
+    assert_eq!(
+        python.and_then(|f| f.version.as_deref()),
+        Some("3.10, 3.11, 3.12")
+    );
+
     // Infrastructure (Dockerfile)
     let infra_names: Vec<&str> = result
         .infrastructure_signals
@@ -59,6 +72,20 @@ fn test_simple_python() {
     );
 }
 
+#[test]
+fn test_conda_env() {
+    let result = scan_directory(&fixtures_dir().join("conda-env"));
+
+    // Frameworks (from environment.yml, including the pip: list)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["Conda", "PyTorch", "NumPy", "pandas"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+}
+
 #[test]
 fn test_node_react() {
     let result = scan_directory(&fixtures_dir().join("node-react"));
@@ -91,6 +118,27 @@ fn test_node_react() {
         .contains(&"src_layout".to_string()));
 }
 
+#[test]
+fn test_astro_site() {
+    let result = scan_directory(&fixtures_dir().join("astro-site"));
+
+    let astro = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Astro")
+        .expect("Astro detected");
+    assert_eq!(astro.category, "framework");
+    assert_eq!(astro.version.as_deref(), Some("^4.16.0"));
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    assert!(
+        fw_names.contains(&"Preact"),
+        "expected Preact in {fw_names:?}"
+    );
+    assert!(result
+        .project_structures
+        .contains(&"node_project".to_string()));
+}
+
 #[test]
 fn test_rust_actix() {
     let result = scan_directory(&fixtures_dir().join("rust-actix"));
@@ -121,6 +169,341 @@ fn test_rust_actix() {
     assert!(result
         .project_structures
         .contains(&"src_layout".to_string()));
+
+    assert_eq!(result.license.as_deref(), Some("MIT"));
+}
+
+#[test]
+fn test_rust_workspace() {
+    let result = scan_directory(&fixtures_dir().join("rust-workspace"));
+
+    // Frameworks (from member crates)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    assert!(fw_names.contains(&"Axum"), "expected Axum in {fw_names:?}");
+    assert!(
+        fw_names.contains(&"Serde"),
+        "expected Serde in {fw_names:?}"
+    );
+
+    let serde = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Serde")
+        .unwrap();
+    assert_eq!(serde.version.as_deref(), Some("1"));
+}
+
+#[test]
+fn test_java_maven() {
+    let result = scan_directory(&fixtures_dir().join("java-maven"));
+
+    // Languages
+    let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+    assert!(
+        lang_names.contains(&"Java"),
+        "expected Java in {lang_names:?}"
+    );
+
+    // Frameworks (from pom.xml)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["Maven", "Spring Boot", "Hibernate", "JUnit"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+
+    let hibernate = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Hibernate")
+        .unwrap();
+    assert_eq!(hibernate.version.as_deref(), Some("6.4.1.Final"));
+}
+
+#[test]
+fn test_swift_package() {
+    let result = scan_directory(&fixtures_dir().join("swift-package"));
+
+    // Languages
+    let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+    assert!(
+        lang_names.contains(&"Swift"),
+        "expected Swift in {lang_names:?}"
+    );
+
+    // Frameworks (from Package.swift)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["Swift Package Manager", "Alamofire"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+}
+
+#[test]
+fn test_elixir_phoenix() {
+    let result = scan_directory(&fixtures_dir().join("elixir-phoenix"));
+
+    // Languages
+    let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+    assert!(
+        lang_names.contains(&"Elixir"),
+        "expected Elixir in {lang_names:?}"
+    );
+
+    // Frameworks (from mix.exs)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["Elixir", "Phoenix", "Ecto"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+}
+
+#[test]
+fn test_rails_gemfile_lock() {
+    let result = scan_directory(&fixtures_dir().join("rails-app"));
+
+    let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+    assert!(
+        lang_names.contains(&"Ruby"),
+        "expected Ruby in {lang_names:?}"
+    );
+
+    // Versions come from the resolved specs in Gemfile.lock.
+    let rails = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Ruby on Rails")
+        .expect("Ruby on Rails detected");
+    assert_eq!(rails.version.as_deref(), Some("7.1.3"));
+    let sidekiq = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Sidekiq")
+        .expect("Sidekiq detected");
+    assert_eq!(sidekiq.version.as_deref(), Some("7.2.2"));
+}
+
+#[test]
+fn test_laravel_composer_lock() {
+    let result = scan_directory(&fixtures_dir().join("laravel-app"));
+
+    // Laravel is only a transitive dependency, visible in composer.lock.
+    let laravel = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Laravel")
+        .expect("Laravel detected");
+    assert_eq!(laravel.version.as_deref(), Some("v10.48.4"));
+}
+
+#[test]
+fn test_dotnet_webapi() {
+    let result = scan_directory(&fixtures_dir().join("dotnet-webapi"));
+
+    // Languages
+    let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+    assert!(lang_names.contains(&"C#"), "expected C# in {lang_names:?}");
+
+    // Frameworks (from src/Api/Api.csproj)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["ASP.NET Core", "Entity Framework Core", "xUnit"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+
+    assert!(
+        result
+            .project_structures
+            .contains(&"dotnet_solution".to_string()),
+        "expected dotnet_solution in {:?}",
+        result.project_structures
+    );
+}
+
+#[test]
+fn test_merge_scan_results() {
+    let python = fixtures_dir().join("simple-python");
+    let rust = fixtures_dir().join("rust-actix");
+    let merged = ScanResult::merge(&[scan_directory(&python), scan_directory(&rust)]);
+    let exact = scan_directories(&[&python, &rust]);
+
+    assert_eq!(merged.frameworks, exact.frameworks);
+    assert_eq!(merged.infrastructure_signals, exact.infrastructure_signals);
+    assert_eq!(merged.project_structures, exact.project_structures);
+    assert_eq!(merged.total_files, exact.total_files);
+    assert_eq!(merged.total_bytes, exact.total_bytes);
+    assert_eq!(merged.license.as_deref(), Some("MIT"));
+
+    // Weighted by counted bytes, which include the manifests too.
+    let names = |r: &ScanResult| -> Vec<String> {
+        let mut names: Vec<String> = r.languages.iter().map(|l| l.name.clone()).collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names(&merged), names(&exact));
+    let total: f64 = merged.languages.iter().map(|l| l.percentage).sum();
+    assert!((total - 100.0).abs() < 1e-9);
+
+    // With only source files, the weights are exact.
+    let a = tempfile::TempDir::new().unwrap();
+    let b = tempfile::TempDir::new().unwrap();
+    std::fs::write(a.path().join("main.py"), "x".repeat(300)).unwrap();
+    std::fs::write(b.path().join("main.rs"), "x".repeat(100)).unwrap();
+    let merged = ScanResult::merge(&[scan_directory(a.path()), scan_directory(b.path())]);
+    assert_eq!(
+        merged.languages,
+        scan_directories(&[a.path(), b.path()]).languages
+    );
+    assert_eq!(merged.languages[0].percentage, 75.0);
+}
+
+#[test]
+fn test_dotnet_webapi_test_bytes() {
+    let root = fixtures_dir().join("dotnet-webapi");
+    let result = scan_directory(&root);
+
+    let size = |p: &str| std::fs::metadata(root.join(p)).unwrap().len();
+    let tests = size("tests/Api.Tests/Api.Tests.csproj") + size("tests/Api.Tests/HealthTests.cs");
+    assert_eq!(result.test_bytes, Some(tests));
+    assert_eq!(result.source_bytes, Some(result.total_bytes - tests));
+}
+
+#[test]
+fn test_compose_services() {
+    let result = scan_directory(&fixtures_dir().join("compose-services"));
+
+    // Services (from docker-compose.yml)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["PostgreSQL", "Redis"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+
+    let infra_names: Vec<&str> = result
+        .infrastructure_signals
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect();
+    assert!(
+        infra_names.contains(&"Docker Compose"),
+        "expected Docker Compose in {infra_names:?}"
+    );
+
+    // From the two-stage Dockerfile: the runtime stage's tag wins.
+    let python = result
+        .infrastructure_signals
+        .iter()
+        .find(|i| i.name == "Docker: python")
+        .expect("base image detected");
+    assert_eq!(python.version.as_deref(), Some("3.12-slim"));
+    assert!(
+        infra_names.contains(&"Docker: port 8000"),
+        "expected Docker: port 8000 in {infra_names:?}"
+    );
+}
+
+#[test]
+fn test_haskell_servant() {
+    let result = scan_directory(&fixtures_dir().join("haskell-servant"));
+    assert_eq!(result.primary_language.as_deref(), Some("Haskell"));
+
+    let servant = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Servant")
+        .expect("Servant detected");
+    assert_eq!(servant.category, "framework");
+    assert_eq!(servant.version.as_deref(), Some("^>=0.20"));
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["Aeson", "Warp", "Stack"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+    assert!(result
+        .project_structures
+        .contains(&"haskell_package".to_string()));
+}
+
+#[test]
+fn test_zig_http() {
+    let result = scan_directory(&fixtures_dir().join("zig-http"));
+    assert_eq!(result.primary_language.as_deref(), Some("Zig"));
+
+    let zap = result
+        .frameworks
+        .iter()
+        .find(|f| f.name == "Zap")
+        .expect("Zap detected");
+    assert_eq!(zap.category, "framework");
+    assert_eq!(zap.version.as_deref(), Some("v0.9.1"));
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["pg.zig", "Zig"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+    assert!(result
+        .project_structures
+        .contains(&"zig_package".to_string()));
+}
+
+#[test]
+fn test_terraform_aws() {
+    let result = scan_directory(&fixtures_dir().join("terraform-aws"));
+
+    // Infrastructure (from main.tf)
+    let infra_names: Vec<&str> = result
+        .infrastructure_signals
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect();
+    for expected in ["Terraform", "AWS"] {
+        assert!(
+            infra_names.contains(&expected),
+            "expected {expected} in {infra_names:?}"
+        );
+    }
+}
+
+#[test]
+fn test_flutter_app() {
+    let result = scan_directory(&fixtures_dir().join("flutter-app"));
+
+    // Languages
+    let lang_names: Vec<&str> = result.languages.iter().map(|l| l.name.as_str()).collect();
+    assert!(
+        lang_names.contains(&"Dart"),
+        "expected Dart in {lang_names:?}"
+    );
+
+    // Frameworks (from pubspec.yaml)
+    let fw_names: Vec<&str> = result.frameworks.iter().map(|f| f.name.as_str()).collect();
+    for expected in ["Flutter", "Provider", "Dio"] {
+        assert!(
+            fw_names.contains(&expected),
+            "expected {expected} in {fw_names:?}"
+        );
+    }
+
+    assert!(
+        result
+            .project_structures
+            .contains(&"flutter_project".to_string()),
+        "expected flutter_project in {:?}",
+        result.project_structures
+    );
 }
 
 #[test]
@@ -141,11 +524,36 @@ fn test_monorepo() {
         infra_names.contains(&"GitHub Actions"),
         "expected GitHub Actions in {infra_names:?}"
     );
+    // Only packages/app-b has a Dockerfile.
+    assert!(
+        infra_names.contains(&"Docker"),
+        "expected Docker in {infra_names:?}"
+    );
+    // From aws-actions/configure-aws-credentials in the deploy workflow.
+    assert!(
+        infra_names.contains(&"AWS"),
+        "expected AWS in {infra_names:?}"
+    );
 
     // Structure
     assert!(result.project_structures.contains(&"monorepo".to_string()));
 }
 
+#[test]
+fn test_monorepo_per_directory() {
+    let options = ScanOptions {
+        per_directory: true,
+        ..Default::default()
+    };
+    let result = scan_directory_with(&fixtures_dir().join("monorepo"), &options);
+    let per_dir = result.per_directory.expect("per-directory breakdown");
+
+    let keys: Vec<&str> = per_dir.keys().map(|k| k.as_str()).collect();
+    assert_eq!(keys, vec!["packages/app-a", "packages/app-b"]);
+    assert_eq!(per_dir["packages/app-a"][0].name, "JavaScript");
+    assert_eq!(per_dir["packages/app-b"][0].name, "Python");
+}
+
 #[test]
 fn test_multi_directory_scan() {
     let fixtures = fixtures_dir();
@@ -195,7 +603,7 @@ fn test_json_output_shape() {
     assert!(first_lang["name"].is_string());
     assert!(first_lang["category"].is_string());
     assert!(first_lang["percentage"].is_f64());
-    assert_eq!(first_lang["category"], "language");
+    assert_eq!(first_lang["category"], "programming");
 
     // Check framework entry shape
     if let Some(first_fw) = json["frameworks"].as_array().and_then(|a| a.first()) {
@@ -203,3 +611,54 @@ fn test_json_output_shape() {
         assert!(first_fw["category"].is_string());
     }
 }
+
+#[test]
+fn test_schema_describes_result() {
+    let schema = scan_result_schema();
+
+    let props = &schema["properties"];
+    for key in [
+        "languages",
+        "frameworks",
+        "project_structures",
+        "infrastructure_signals",
+    ] {
+        assert!(props[key].is_object(), "missing {key} in schema");
+    }
+
+    let lang = &schema["$defs"]["LanguageEntry"]["properties"];
+    assert_eq!(
+        lang["category"]["enum"],
+        serde_json::json!(["programming", "markup", "styling", "data", "shell"])
+    );
+    assert_eq!(lang["percentage"]["type"], "number");
+    assert_eq!(lang["percentage"]["maximum"], 100.0);
+
+    let signal = &schema["$defs"]["SignalEntry"];
+    assert!(signal["properties"]["version"].is_object());
+}
+
+#[test]
+fn test_csv_output_rows() {
+    let result = scan_directory(&fixtures_dir().join("simple-python"));
+    let csv = to_csv(&result).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines[0], "kind,name,category,percentage");
+    assert!(
+        lines.contains(&"language,Python,programming,100"),
+        "{lines:?}"
+    );
+    assert!(lines.contains(&"framework,Flask,framework,"), "{lines:?}");
+    assert!(lines.contains(&"structure,python_package,,"), "{lines:?}");
+    assert!(lines.contains(&"infra,Docker,infrastructure,"), "{lines:?}");
+}
+
+#[test]
+fn test_yaml_round_trip() {
+    let result = scan_directory(&fixtures_dir().join("node-react"));
+    let yaml = serde_yaml::to_string(&result).unwrap();
+    let parsed: ScanResult = serde_yaml::from_str(&yaml).unwrap();
+
+    assert_eq!(parsed, result);
+}